@@ -4,8 +4,6 @@ use std::fs;
 use std::path::PathBuf;
 use walkdir::WalkDir;
 
-use crate::Config;
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AssetUsage {
     pub post_path: String,
@@ -41,26 +39,34 @@ fn extract_cloudinary_urls(content: &str) -> Vec<(String, usize, String)> {
     // https://res.cloudinary.com/CLOUD_NAME/image/upload/...
     // https://res.cloudinary.com/CLOUD_NAME/video/upload/...
     for (line_num, line) in content.lines().enumerate() {
-        for cap in crate::patterns::CLOUDINARY_URL.captures_iter(line) {
-            let _full_url = cap.get(0).map(|m| m.as_str()).unwrap_or("");
-            let public_id = cap.get(3).map(|m| m.as_str()).unwrap_or("");
-
-            // Remove file extension from public_id
-            let public_id = public_id
-                .rsplit_once('.')
-                .map(|(id, _)| id)
-                .unwrap_or(public_id);
+        for url in crate::patterns::CLOUDINARY_URL.find_iter(line) {
+            let Some(public_id) = public_id(url.as_str()) else {
+                continue;
+            };
 
             // Get some context (trimmed line)
             let context = line.trim().chars().take(100).collect::<String>();
 
-            results.push((public_id.to_string(), line_num + 1, context));
+            results.push((public_id, line_num + 1, context));
         }
     }
 
     results
 }
 
+/// The public_id of a Cloudinary URL, without its file extension
+fn public_id(url: &str) -> Option<String> {
+    let cap = crate::patterns::CLOUDINARY_URL.captures(url)?;
+    let public_id = cap.get(3).map(|m| m.as_str()).unwrap_or("");
+    Some(
+        public_id
+            .rsplit_once('.')
+            .map(|(id, _)| id)
+            .unwrap_or(public_id)
+            .to_string(),
+    )
+}
+
 /// Extract title from markdown content
 fn extract_title(content: &str) -> Option<String> {
     for line in content.lines() {
@@ -78,62 +84,48 @@ fn extract_title(content: &str) -> Option<String> {
 /// Scan all markdown files in the vault for Cloudinary URLs
 pub fn scan_vault_for_usage() -> Result<UsageScanResult, String> {
     let start = std::time::Instant::now();
-    let config = Config::from_app_config()?;
 
     let mut by_asset: HashMap<String, Vec<AssetUsage>> = HashMap::new();
     let mut by_post: HashMap<String, Vec<String>> = HashMap::new();
     let mut all_urls: Vec<String> = Vec::new();
     let mut total_posts = 0;
 
-    // Scan vault
-    for entry in WalkDir::new(&config.vault_path)
-        .into_iter()
-        .filter_map(|e| e.ok())
-        .filter(|e| e.path().extension().is_some_and(|ext| ext == "md"))
-    {
-        let path = entry.path();
-        let path_str = path.to_string_lossy().to_string();
-
-        // Skip certain directories
-        if path_str.contains("/templates/")
-            || path_str.contains("/.obsidian/")
-            || path_str.contains("/node_modules/")
-        {
+    // Vault notes come from the index, which already holds each note's
+    // media references.
+    for note in crate::vault_index::notes()? {
+        if note.path.contains("/templates/") {
             continue;
         }
+        total_posts += 1;
 
-        if let Ok(content) = fs::read_to_string(path) {
-            total_posts += 1;
-            let title = extract_title(&content);
-            let urls = extract_cloudinary_urls(&content);
-
-            if !urls.is_empty() {
-                let mut post_assets = Vec::new();
-
-                for (public_id, line_num, context) in urls {
-                    // Track by asset
-                    by_asset
-                        .entry(public_id.clone())
-                        .or_default()
-                        .push(AssetUsage {
-                            post_path: path_str.clone(),
-                            post_title: title.clone(),
-                            line_number: line_num,
-                            context: context.clone(),
-                        });
-
-                    post_assets.push(public_id.clone());
-
-                    // Reconstruct approximate URL for reference
-                    all_urls.push(format!(
-                        "https://res.cloudinary.com/ejf/image/upload/{}",
-                        public_id
-                    ));
-                }
+        let mut post_assets = Vec::new();
+        for media in &note.media {
+            let Some(public_id) = public_id(&media.src) else {
+                continue;
+            };
+            // Track by asset
+            by_asset
+                .entry(public_id.clone())
+                .or_default()
+                .push(AssetUsage {
+                    post_path: note.path.clone(),
+                    post_title: note.heading.clone(),
+                    line_number: media.line,
+                    context: media.context.clone(),
+                });
+
+            post_assets.push(public_id.clone());
+
+            // Reconstruct approximate URL for reference
+            all_urls.push(format!(
+                "https://res.cloudinary.com/ejf/image/upload/{}",
+                public_id
+            ));
+        }
 
-                // Track by post
-                by_post.insert(path_str.clone(), post_assets);
-            }
+        // Track by post
+        if !post_assets.is_empty() {
+            by_post.insert(note.path, post_assets);
         }
     }
 
@@ -142,18 +134,13 @@ pub fn scan_vault_for_usage() -> Result<UsageScanResult, String> {
     // missed posts under "content/posts", "content/reading", project pages,
     // and anything outside the default folder, which made the "0 posts use
     // this image" guard report unused for assets that were in fact in use.
-    // Derive the content base from each target's content_path_pattern, same
-    // pattern vault.rs::find_published_info_inner uses.
+    // Derive the content base from each target's content_path_pattern via
+    // PublishTarget::content_base.
     let mut scanned_roots: HashSet<PathBuf> = HashSet::new();
     if let Ok(app_config) = crate::config::get() {
         for target in &app_config.publish_targets {
             // "content/blog/{year}" → "content/blog", "content/posts" → "content/posts"
-            let content_base = target
-                .content_path_pattern
-                .split("/{year}")
-                .next()
-                .unwrap_or("content/blog");
-            let root = PathBuf::from(&target.repo_path).join(content_base);
+            let root = PathBuf::from(&target.repo_path).join(target.content_base());
             if !root.exists() || !scanned_roots.insert(root.clone()) {
                 continue;
             }
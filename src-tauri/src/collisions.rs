@@ -0,0 +1,251 @@
+//! Slug collisions between the vault and a publish target's repo.
+//!
+//! The site routes posts by slug alone, so two copies of one slug (say
+//! `content/blog/2025/x.md` and `content/blog/2026/x.md`) leave one of them
+//! unreachable. `scan` reports every such problem for a target, and
+//! `check_publish` is the guard the publish flow runs before copying a file.
+
+use crate::config::{self, PublishTarget, VaultConfig};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CollisionKind {
+    /// The slug is live in more than one place in the repo.
+    Duplicate,
+    /// A live post with no vault note of the same slug.
+    Orphan,
+    /// Several vault notes share the slug, or the vault note and the live
+    /// post have different titles and look like different posts.
+    Ambiguous,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Collision {
+    pub kind: CollisionKind,
+    pub slug: String,
+    pub repo_paths: Vec<String>,
+    pub vault_paths: Vec<String>,
+    pub detail: String,
+}
+
+/// Scan the vault against a target (None = default target).
+pub fn scan_target(target_id: Option<&str>) -> Result<Vec<Collision>, String> {
+    let app_config = config::get()?;
+    let target = config::resolve_target(target_id)?;
+    Ok(scan(&target, &vault_notes(&app_config.vault)))
+}
+
+/// Publishable vault notes, grouped by slug. Same folders `publish` accepts
+/// sources from, minus stale/archive copies.
+pub(crate) fn vault_notes(vault: &VaultConfig) -> BTreeMap<String, Vec<PathBuf>> {
+    let publishable = &vault.publishable_dirs;
+    let excluded: Vec<&String> = vault
+        .excluded_dirs
+        .iter()
+        .filter(|d| !publishable.contains(d))
+        .collect();
+
+    let mut notes: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+    for entry in WalkDir::new(&vault.path)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "md"))
+    {
+        let path_str = entry.path().to_string_lossy();
+        let in_dir = |dir: &String| path_str.contains(&format!("/{}/", dir));
+        if !publishable.iter().any(in_dir)
+            || excluded.iter().any(|d| in_dir(d))
+            || path_str.contains("/_stale/")
+            || path_str.contains("/_archive/")
+            || path_str.contains("/blog/week-notes/")
+        {
+            continue;
+        }
+        if let Some(slug) = entry.path().file_stem() {
+            notes
+                .entry(slug.to_string_lossy().to_string())
+                .or_default()
+                .push(entry.path().to_path_buf());
+        }
+    }
+    notes
+}
+
+fn scan(target: &PublishTarget, notes: &BTreeMap<String, Vec<PathBuf>>) -> Vec<Collision> {
+    let mut live: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+    for loc in crate::vault::find_all_published(target) {
+        live.entry(loc.slug).or_default().push(loc.path);
+    }
+
+    let strings = |paths: &[PathBuf]| -> Vec<String> {
+        paths
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect()
+    };
+    let mut collisions = Vec::new();
+    let mut push = |kind, slug: &str, repo: &[PathBuf], vault: &[PathBuf], detail: String| {
+        collisions.push(Collision {
+            kind,
+            slug: slug.to_string(),
+            repo_paths: strings(repo),
+            vault_paths: strings(vault),
+            detail,
+        })
+    };
+
+    for (slug, repo_paths) in &live {
+        let vault_paths = notes.get(slug).map(Vec::as_slice).unwrap_or_default();
+        if repo_paths.len() > 1 {
+            push(
+                CollisionKind::Duplicate,
+                slug,
+                repo_paths,
+                vault_paths,
+                format!(
+                    "Live in {} places; the site can only serve one",
+                    repo_paths.len()
+                ),
+            );
+        }
+        match vault_paths {
+            [] => push(
+                CollisionKind::Orphan,
+                slug,
+                repo_paths,
+                vault_paths,
+                "No vault note with this slug".into(),
+            ),
+            [note] => {
+                let ours = crate::vault::post_title(note);
+                let theirs = crate::vault::post_title(&repo_paths[0]);
+                if let (Some(ours), Some(theirs)) = (ours, theirs) {
+                    if !ours.trim().eq_ignore_ascii_case(theirs.trim()) {
+                        push(
+                            CollisionKind::Ambiguous,
+                            slug,
+                            repo_paths,
+                            vault_paths,
+                            format!(
+                                "Vault note is \"{}\" but the live post is \"{}\"",
+                                ours, theirs
+                            ),
+                        );
+                    }
+                }
+            }
+            _ => push(
+                CollisionKind::Ambiguous,
+                slug,
+                repo_paths,
+                vault_paths,
+                format!("{} vault notes share this slug", vault_paths.len()),
+            ),
+        }
+    }
+
+    for (slug, vault_paths) in notes {
+        if vault_paths.len() > 1 && !live.contains_key(slug) {
+            push(
+                CollisionKind::Ambiguous,
+                slug,
+                &[],
+                vault_paths,
+                format!("{} vault notes share this slug", vault_paths.len()),
+            );
+        }
+    }
+
+    collisions
+}
+
+/// Refuse a publish of `slug` to `dest_path` while the slug is live anywhere
+/// else in the repo — the new copy and the old one would fight over one URL.
+pub fn check_publish(target: &PublishTarget, slug: &str, dest_path: &str) -> Result<(), String> {
+    let others: Vec<String> = crate::vault::find_published_locations(target, slug)
+        .into_iter()
+        .filter(|l| l.path != Path::new(dest_path))
+        .map(|l| repo_relative(target, &l.path))
+        .collect();
+    if others.is_empty() {
+        return Ok(());
+    }
+    Err(format!(
+        "\"{}\" is already live at {}; publishing to {} would leave two copies and hide one. Unpublish or move the existing copy first.",
+        slug,
+        others.join(", "),
+        repo_relative(target, Path::new(dest_path))
+    ))
+}
+
+fn repo_relative(target: &PublishTarget, path: &Path) -> String {
+    path.strip_prefix(&target.repo_path)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+    use crate::git::tests::{scratch, write};
+
+    fn fixture(name: &str) -> (PublishTarget, AppConfig) {
+        let root = scratch(name);
+        let mut app_config = AppConfig::default();
+        app_config.vault.path = root.join("vault").to_string_lossy().to_string();
+        let mut target = app_config.publish_targets[0].clone();
+        target.repo_path = root.join("site").to_string_lossy().to_string();
+        (target, app_config)
+    }
+
+    #[test]
+    fn scan_reports_duplicates_orphans_and_ambiguous_slugs() {
+        let (target, app_config) = fixture("scan");
+        let (site, vault) = (&target.repo_path, &app_config.vault.path);
+        write(site, "content/blog/2025/twice.md", "# Twice\n");
+        write(site, "content/blog/2026/twice.md", "# Twice\n");
+        write(vault, "blog/twice.md", "# Twice\n");
+        write(site, "content/blog/2024/lost.md", "# Lost\n");
+        write(site, "content/blog/2024/fine.md", "# Fine\n");
+        write(vault, "blog/fine.md", "---\ntitle: Fine\n---\nbody\n");
+        write(site, "content/blog/2023/reused.md", "# Old Post\n");
+        write(vault, "drafts/reused.md", "# Brand New Thing\n");
+        write(vault, "blog/split.md", "# a\n");
+        write(vault, "drafts/split.md", "# b\n");
+        write(vault, "private/split.md", "# c\n");
+
+        let found = scan(&target, &vault_notes(&app_config.vault));
+        let kinds: Vec<(&str, &CollisionKind)> =
+            found.iter().map(|c| (c.slug.as_str(), &c.kind)).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("lost", &CollisionKind::Orphan),
+                ("reused", &CollisionKind::Ambiguous),
+                ("twice", &CollisionKind::Duplicate),
+                ("split", &CollisionKind::Ambiguous),
+            ]
+        );
+        assert_eq!(found[3].vault_paths.len(), 2);
+    }
+
+    #[test]
+    fn publish_is_refused_when_the_slug_lives_elsewhere() {
+        let (target, _) = fixture("check");
+        write(&target.repo_path, "content/blog/2024/post.md", "# Post\n");
+        let same = format!("{}/content/blog/2024/post.md", target.repo_path);
+        let other = format!("{}/content/blog/2025/post.md", target.repo_path);
+
+        assert!(check_publish(&target, "post", &same).is_ok());
+        let err = check_publish(&target, "post", &other).unwrap_err();
+        assert!(err.contains("content/blog/2024/post.md"), "{}", err);
+        assert!(check_publish(&target, "new", &other).is_ok());
+    }
+}
@@ -0,0 +1,308 @@
+//! Publish commit messages and authorship from the target's `commit` config.
+//!
+//! A template is plain text with `{token}`s (see `CommitConfig`); tokens it
+//! doesn't know are left as written. Trailers go after a blank line, the way
+//! `git interpret-trailers` expects them.
+
+use crate::config::{CommitConfig, GitIdentity};
+use crate::git::Authorship;
+use crate::vault::PostSummary;
+use regex::Regex;
+use std::path::Path;
+
+/// What a message can say about the post being published.
+#[derive(Debug, Clone)]
+pub struct MessageFields<'a> {
+    pub slug: &'a str,
+    pub title: Option<&'a str>,
+    pub word_count: usize,
+    pub tags: &'a [String],
+    pub content_type: &'a str,
+    /// Words (added, removed) against the copy being replaced. Set for a
+    /// republish, which picks the republish template.
+    pub delta: Option<(usize, usize)>,
+}
+
+impl<'a> MessageFields<'a> {
+    /// Fields for the vault note at `source_path`. `summary` is its
+    /// `vault::post_summary`; `previous` is what the target has now, if
+    /// anything, and `rendered` what a publish would write over it.
+    pub fn for_post(
+        source_path: &'a str,
+        slug: &'a str,
+        summary: &'a PostSummary,
+        previous: Option<&str>,
+        rendered: &str,
+    ) -> Self {
+        MessageFields {
+            slug,
+            title: summary.title.as_deref(),
+            word_count: summary.word_count,
+            tags: &summary.tags,
+            content_type: crate::vault::content_type_for_path(source_path),
+            delta: previous.map(|p| crate::publish_diff::word_delta(p, rendered)),
+        }
+    }
+
+    /// Just the slug, for commits without a vault note to read (unpublish).
+    pub fn slug_only(slug: &'a str) -> Self {
+        MessageFields {
+            slug,
+            title: None,
+            word_count: 0,
+            tags: &[],
+            content_type: "post",
+            delta: None,
+        }
+    }
+}
+
+/// Message for publishing one post, republish template if `fields.delta` is set.
+pub fn publish(commit: &CommitConfig, fields: &MessageFields) -> String {
+    let (template, action) = match fields.delta {
+        Some(_) => (&commit.republish_template, "republish"),
+        None => (&commit.publish_template, "publish"),
+    };
+    with_trailers(render(template, fields, action), commit, fields, action)
+}
+
+pub fn unpublish(commit: &CommitConfig, slug: &str) -> String {
+    let fields = MessageFields::slug_only(slug);
+    let message = render(&commit.unpublish_template, &fields, "unpublish");
+    with_trailers(message, commit, &fields, "unpublish")
+}
+
+/// A batch commit lists its slugs (`Publish: a, b, c`, which journal backfill
+/// reads); only the trailers come from the config.
+pub fn batch(commit: &CommitConfig, slugs: &[&str]) -> String {
+    let joined = slugs.join(", ");
+    let message = format!("Publish: {}", joined);
+    with_trailers(
+        message,
+        commit,
+        &MessageFields::slug_only(&joined),
+        "publish",
+    )
+}
+
+/// Renames aren't templated: `Rename: old -> new`, plus any trailers.
+pub fn rename(commit: &CommitConfig, old: &str, new: &str) -> String {
+    let message = format!("Rename: {} -> {}", old, new);
+    with_trailers(message, commit, &MessageFields::slug_only(new), "rename")
+}
+
+/// Reverts keep git's own message (`Revert "…"`); only the trailers come
+/// from the config.
+pub fn revert(commit: &CommitConfig, message: &str) -> String {
+    let fields = MessageFields::slug_only("");
+    with_trailers(message.to_string(), commit, &fields, "revert")
+}
+
+/// The target's author/committer override, for `Repo::commit_as`.
+pub fn authorship(commit: &CommitConfig) -> Authorship {
+    let person = |id: &GitIdentity| {
+        let (name, email) = (id.name.trim(), id.email.trim());
+        (!name.is_empty() && !email.is_empty()).then(|| (name.to_string(), email.to_string()))
+    };
+    Authorship {
+        author: commit.author.as_ref().and_then(person),
+        committer: commit.committer.as_ref().and_then(person),
+    }
+}
+
+/// The slugs a publish commit's subject names, read back through the
+/// publish and republish templates, else as a batch (`Publish: a, b, c`).
+/// A template without `{slug}` in its first line can't be read back.
+pub fn published_slugs(commit: &CommitConfig, subject: &str) -> Vec<String> {
+    let subject = subject.trim();
+    for template in [&commit.publish_template, &commit.republish_template] {
+        let slug = subject_pattern(template)
+            .and_then(|pattern| pattern.captures(subject))
+            .map(|caps| caps["slug"].to_string());
+        if let Some(slug) = slug {
+            return vec![slug];
+        }
+    }
+    match crate::patterns::GIT_PUBLISH_SUBJECT.captures(subject) {
+        Some(caps) => caps[1]
+            .split(", ")
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+/// A template's first line as a regex over commit subjects: `{slug}` is
+/// captured, any other token matches anything.
+fn subject_pattern(template: &str) -> Option<Regex> {
+    let line = template.lines().next().unwrap_or_default().trim();
+    if !line.contains("{slug}") {
+        return None;
+    }
+    let mut pattern = String::from("^");
+    let mut captured = false;
+    let mut rest = line;
+    while let Some((start, len)) = rest
+        .find('{')
+        .and_then(|start| Some((start, rest[start..].find('}')? + 1)))
+    {
+        pattern.push_str(&regex::escape(&rest[..start]));
+        pattern.push_str(match &rest[start..start + len] {
+            "{slug}" if !captured => {
+                captured = true;
+                r"(?P<slug>[^\s,]+)"
+            }
+            "{slug}" => r"[^\s,]+",
+            _ => ".*?",
+        });
+        rest = &rest[start + len..];
+    }
+    pattern.push_str(&regex::escape(rest));
+    pattern.push('$');
+    Regex::new(&pattern).ok()
+}
+
+/// Summary for `source_path`, or an empty one if it can't be read (the
+/// message then falls back to the slug).
+pub fn summary(source_path: &str) -> PostSummary {
+    crate::vault::post_summary(Path::new(source_path)).unwrap_or_default()
+}
+
+fn render(template: &str, fields: &MessageFields, action: &str) -> String {
+    let (added, removed) = fields.delta.unwrap_or((0, 0));
+    let net = added as i64 - removed as i64;
+    template
+        .replace(
+            "{title}",
+            fields
+                .title
+                .map(|t| t.trim().trim_start_matches('#').trim())
+                .unwrap_or(fields.slug),
+        )
+        .replace("{slug}", fields.slug)
+        .replace("{word_count}", &fields.word_count.to_string())
+        .replace("{tags}", &fields.tags.join(", "))
+        .replace("{content_type}", fields.content_type)
+        .replace("{action}", action)
+        .replace("{words_added}", &added.to_string())
+        .replace("{words_removed}", &removed.to_string())
+        .replace(
+            "{word_delta}",
+            &if net > 0 {
+                format!("+{}", net)
+            } else {
+                net.to_string()
+            },
+        )
+}
+
+fn with_trailers(
+    message: String,
+    commit: &CommitConfig,
+    fields: &MessageFields,
+    action: &str,
+) -> String {
+    let trailers: Vec<String> = commit
+        .trailers
+        .iter()
+        .map(|t| render(t.trim(), fields, action))
+        .filter(|t| !t.is_empty())
+        .collect();
+    if trailers.is_empty() {
+        return message;
+    }
+    format!("{}\n\n{}\n", message.trim_end(), trailers.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(delta: Option<(usize, usize)>) -> MessageFields<'static> {
+        static TAGS: [String; 0] = [];
+        MessageFields {
+            slug: "tea",
+            title: Some("# Tea & Toast"),
+            word_count: 420,
+            tags: &TAGS,
+            content_type: "post",
+            delta,
+        }
+    }
+
+    #[test]
+    fn defaults_match_the_old_messages_and_count_the_change() {
+        let commit = CommitConfig::default();
+        assert_eq!(publish(&commit, &fields(None)), "Publish: tea");
+        assert_eq!(
+            publish(&commit, &fields(Some((12, 3)))),
+            "Republish: tea (+9 words)"
+        );
+        assert_eq!(
+            publish(&commit, &fields(Some((0, 5)))),
+            "Republish: tea (-5 words)"
+        );
+        assert_eq!(unpublish(&commit, "tea"), "Unpublish: tea");
+        assert_eq!(batch(&commit, &["a", "b"]), "Publish: a, b");
+    }
+
+    #[test]
+    fn subjects_read_back_through_the_templates() {
+        let slugs = |commit: &CommitConfig, subject: &str| published_slugs(commit, subject);
+        let commit = CommitConfig::default();
+        assert_eq!(slugs(&commit, "Publish: tea"), ["tea"]);
+        assert_eq!(slugs(&commit, "Republish: tea (+9 words)"), ["tea"]);
+        assert_eq!(slugs(&commit, "Publish: a, b"), ["a", "b"]);
+        assert!(slugs(&commit, "Fix typo").is_empty());
+
+        let custom = CommitConfig {
+            publish_template: "post({content_type}): {title} [{slug}]\n\n{word_count} words".into(),
+            republish_template: "{title} updated".into(),
+            ..CommitConfig::default()
+        };
+        let subject = publish(&custom, &fields(None));
+        let subject = subject.lines().next().unwrap();
+        assert_eq!(subject, "post(post): Tea & Toast [tea]");
+        assert_eq!(slugs(&custom, subject), ["tea"]);
+        // No `{slug}` to read back; batches keep their own subject.
+        assert!(slugs(&custom, "Tea & Toast updated").is_empty());
+        assert_eq!(slugs(&custom, "Publish: a, b"), ["a", "b"]);
+    }
+
+    #[test]
+    fn templates_fill_tokens_and_append_trailers() {
+        let commit = CommitConfig {
+            publish_template: "{action} {content_type} \"{title}\" ({word_count} words) {nope}"
+                .into(),
+            trailers: vec!["Co-authored-by: Ed <ed@example.com>".into(), "  ".into()],
+            ..CommitConfig::default()
+        };
+        assert_eq!(
+            publish(&commit, &fields(None)),
+            "publish post \"Tea & Toast\" (420 words) {nope}\n\nCo-authored-by: Ed <ed@example.com>\n"
+        );
+    }
+
+    #[test]
+    fn blank_identities_fall_back_to_git_config() {
+        let commit = CommitConfig {
+            author: Some(GitIdentity {
+                name: "Dispatch".into(),
+                email: "bot@example.com".into(),
+            }),
+            committer: Some(GitIdentity {
+                name: String::new(),
+                email: String::new(),
+            }),
+            ..CommitConfig::default()
+        };
+        let who = authorship(&commit);
+        assert_eq!(
+            who.author,
+            Some(("Dispatch".into(), "bot@example.com".into()))
+        );
+        assert_eq!(who.committer, None);
+    }
+}
@@ -1,5 +1,5 @@
 use axum::{
-    extract::State,
+    extract::{Query, State},
     http::{HeaderMap, StatusCode},
     response::Html,
     routing::{delete, get, post},
@@ -49,9 +49,19 @@ pub async fn start_server() {
         .route("/", get(serve_ui))
         .route("/api/auth", post(api_auth))
         .route("/api/files", get(api_list_files))
+        .route("/api/search", get(api_search))
         .route("/api/files/{slug}/publish", post(api_publish))
+        .route("/api/files/{slug}/plan", get(api_plan_publish))
         .route("/api/files/{slug}/schedule", post(api_schedule))
         .route("/api/files/{slug}/schedule", delete(api_cancel_schedule))
+        .route(
+            "/api/files/{slug}/unpublish-schedule",
+            post(api_schedule_unpublish),
+        )
+        .route(
+            "/api/files/{slug}/unpublish-schedule",
+            delete(api_cancel_scheduled_unpublish),
+        )
         .layer(CorsLayer::permissive())
         .with_state(state);
 
@@ -111,6 +121,7 @@ struct CompanionFile {
     published_url: Option<String>,
     published_date: Option<u64>,
     publish_at: Option<String>,
+    unpublish_at: Option<String>,
     unlisted: bool,
     has_password: bool,
     modified: u64,
@@ -141,6 +152,7 @@ async fn api_list_files(
             published_url: f.published_url,
             published_date: f.published_date,
             publish_at: f.publish_at,
+            unpublish_at: f.unpublish_at,
             unlisted: f.unlisted,
             has_password: f.password.is_some(),
             modified: f.modified,
@@ -151,6 +163,48 @@ async fn api_list_files(
     Ok(Json(companion_files))
 }
 
+#[derive(Deserialize)]
+struct SearchParams {
+    q: Option<String>,
+    /// Comma-separated; all of them.
+    tags: Option<String>,
+    dir: Option<String>,
+    #[serde(rename = "type")]
+    content_type: Option<String>,
+    published: Option<bool>,
+    limit: Option<usize>,
+}
+
+async fn api_search(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(params): Query<SearchParams>,
+) -> Result<Json<crate::search::SearchResults>, StatusCode> {
+    if !check_auth(&headers, &state.pin) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    let query = crate::search::SearchQuery {
+        text: params.q.unwrap_or_default(),
+        tags: params
+            .tags
+            .iter()
+            .flat_map(|tags| tags.split(','))
+            .map(|tag| tag.trim().to_string())
+            .filter(|tag| !tag.is_empty())
+            .collect(),
+        dir: params.dir,
+        content_type: params.content_type,
+        published: params.published,
+        limit: params.limit,
+    };
+    let results = tokio::task::spawn_blocking(move || crate::search::search(&query))
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(results))
+}
+
 async fn api_publish(
     State(state): State<Arc<AppState>>,
     headers: HeaderMap,
@@ -169,7 +223,29 @@ async fn api_publish(
         .ok_or(StatusCode::NOT_FOUND)?;
 
     match crate::publish::publish_file(&file.path, &slug, None) {
-        Ok(url) => Ok(Json(serde_json::json!({ "url": url }))),
+        Ok(published) => Ok(Json(serde_json::json!(published))),
+        Err(e) => Ok(Json(serde_json::json!({ "error": e }))),
+    }
+}
+
+async fn api_plan_publish(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    axum::extract::Path(slug): axum::extract::Path<String>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    if !check_auth(&headers, &state.pin) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    let files =
+        crate::vault::get_recent_files(500).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let file = files
+        .iter()
+        .find(|f| f.filename.trim_end_matches(".md") == slug)
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    match crate::publish::plan_publish(&file.path, &slug, None) {
+        Ok(plan) => Ok(Json(serde_json::json!(plan))),
         Err(e) => Ok(Json(serde_json::json!({ "error": e }))),
     }
 }
@@ -196,7 +272,11 @@ async fn api_schedule(
         .find(|f| f.filename.trim_end_matches(".md") == slug)
         .ok_or(StatusCode::NOT_FOUND)?;
 
-    crate::vault::set_frontmatter_field(&file.path, "publish_at", &body.publish_at)
+    let publish_at = match crate::schedule::normalize_now(&body.publish_at) {
+        Ok(at) => at,
+        Err(e) => return Ok(Json(serde_json::json!({ "error": e }))),
+    };
+    crate::vault::set_frontmatter_field(&file.path, "publish_at", &publish_at)
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
     Ok(Json(serde_json::json!({ "ok": true })))
@@ -224,6 +304,60 @@ async fn api_cancel_schedule(
     Ok(Json(serde_json::json!({ "ok": true })))
 }
 
+#[derive(Deserialize)]
+struct UnpublishScheduleRequest {
+    unpublish_at: String,
+}
+
+async fn api_schedule_unpublish(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    axum::extract::Path(slug): axum::extract::Path<String>,
+    Json(body): Json<UnpublishScheduleRequest>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    if !check_auth(&headers, &state.pin) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    let files =
+        crate::vault::get_recent_files(500).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let file = files
+        .iter()
+        .find(|f| f.filename.trim_end_matches(".md") == slug)
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let unpublish_at = match crate::schedule::normalize_now(&body.unpublish_at) {
+        Ok(at) => at,
+        Err(e) => return Ok(Json(serde_json::json!({ "error": e }))),
+    };
+    crate::vault::set_frontmatter_field(&file.path, "unpublish_at", &unpublish_at)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(serde_json::json!({ "ok": true })))
+}
+
+async fn api_cancel_scheduled_unpublish(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    axum::extract::Path(slug): axum::extract::Path<String>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    if !check_auth(&headers, &state.pin) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    let files =
+        crate::vault::get_recent_files(500).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let file = files
+        .iter()
+        .find(|f| f.filename.trim_end_matches(".md") == slug)
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    crate::vault::remove_frontmatter_field(&file.path, "unpublish_at")
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(serde_json::json!({ "ok": true })))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -280,6 +414,7 @@ mod tests {
             published_url: None,
             published_date: None,
             publish_at: Some("2026-03-01T09:00:00Z".into()),
+            unpublish_at: None,
             unlisted: false,
             has_password: false,
             modified: 1000000,
@@ -288,5 +423,6 @@ mod tests {
         let json = serde_json::to_string(&file).unwrap();
         assert!(json.contains("\"slug\":\"path\""));
         assert!(json.contains("\"publish_at\":\"2026-03-01T09:00:00Z\""));
+        assert!(json.contains("\"unpublish_at\":null"));
     }
 }
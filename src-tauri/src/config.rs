@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::PathBuf;
 use std::sync::{OnceLock, RwLock};
@@ -20,6 +21,10 @@ pub struct AppConfig {
     pub analytics_url: Option<String>,
     #[serde(default)]
     pub mastodon_instance: Option<String>,
+    /// IANA zone (`Europe/London`) that local `publish_at` / `unpublish_at`
+    /// times are read in. None means the system timezone.
+    #[serde(default)]
+    pub timezone: Option<String>,
     #[serde(default)]
     pub media: MediaConfig,
     /// When true, every successful publish/republish also forwards webmentions
@@ -27,6 +32,8 @@ pub struct AppConfig {
     /// Defaults to false — turn it on in Settings → Connections.
     #[serde(default)]
     pub webmentions_bridgy_fed: bool,
+    #[serde(default)]
+    pub lint: LintConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Copy)]
@@ -101,8 +108,316 @@ pub struct PublishTarget {
     pub repo_path: String,
     pub domain: String,
     pub content_path_pattern: String,
+    /// The site path a post is served at, after `domain`: `{year}`,
+    /// `{month}` and `{slug}`, as filed by `content_path_pattern`. Empty
+    /// means `/blog/{year}/{slug}`.
+    #[serde(default)]
+    pub url_pattern: String,
     pub branch: String,
     pub is_default: bool,
+    /// Whether a publish pushes to `branch` or to a review branch.
+    #[serde(default)]
+    pub mode: PublishMode,
+    /// Which publish transforms run for this target. Missing on older
+    /// configs, where every step is on.
+    #[serde(default)]
+    pub transforms: TransformConfig,
+    #[serde(default)]
+    pub hooks: PublishHooks,
+    #[serde(default)]
+    pub verify: DeployVerify,
+    #[serde(default)]
+    pub commit: CommitConfig,
+    #[serde(default)]
+    pub redirects: RedirectsConfig,
+    #[serde(default)]
+    pub unpublish: UnpublishConfig,
+}
+
+/// How a publish reaches the target's `branch`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PublishMode {
+    /// Commit and push straight to `branch`.
+    #[default]
+    Direct,
+    /// Commit to a `dispatch/{slug}` branch and push that for review. The
+    /// post goes live when the branch is merged from Dispatch.
+    PullRequest,
+}
+
+/// Shell commands run in the target's repo around a publish. A failing
+/// pre-publish hook aborts the publish; post-publish hooks can't.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PublishHooks {
+    pub pre_publish: Vec<String>,
+    pub post_publish: Vec<String>,
+}
+
+/// Commit messages and authorship for a target's publish commits. Templates
+/// take `{title}`, `{slug}`, `{word_count}`, `{tags}`, `{content_type}` and
+/// `{action}`; the republish one also `{words_added}`, `{words_removed}` and
+/// `{word_delta}`. Journal backfill reads the slug back out of subjects
+/// made with these, so it needs `{slug}` in the first line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CommitConfig {
+    pub publish_template: String,
+    pub republish_template: String,
+    pub unpublish_template: String,
+    /// None = the repo's git config.
+    pub author: Option<GitIdentity>,
+    /// None = the repo's git config, like `git commit --author`.
+    pub committer: Option<GitIdentity>,
+    /// Appended after a blank line, e.g. `Co-authored-by: Name <email>`.
+    /// Tokens work here too.
+    pub trailers: Vec<String>,
+}
+
+impl Default for CommitConfig {
+    fn default() -> Self {
+        CommitConfig {
+            publish_template: "Publish: {slug}".into(),
+            republish_template: "Republish: {slug} ({word_delta} words)".into(),
+            unpublish_template: "Unpublish: {slug}".into(),
+            author: None,
+            committer: None,
+            trailers: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitIdentity {
+    pub name: String,
+    pub email: String,
+}
+
+/// The file a slug rename adds its old URL -> new URL redirect to.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RedirectsConfig {
+    pub format: RedirectFormat,
+    /// Repo-relative. Empty = the format's usual file at the repo root.
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RedirectFormat {
+    /// Renames write no redirect; the old URL 404s.
+    #[default]
+    None,
+    /// Netlify / Cloudflare Pages `_redirects`: `/old /new 301` lines.
+    Netlify,
+    /// The `redirects` array in `vercel.json`.
+    Vercel,
+    /// A flat `{"/old": "/new"}` object the site reads itself.
+    JsonMap,
+}
+
+/// What unpublishing a post does to its file in the target repo.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UnpublishConfig {
+    pub strategy: UnpublishStrategy,
+    /// Repo-relative folder `Archive` files posts under, in a folder per day.
+    pub archive_dir: String,
+}
+
+impl Default for UnpublishConfig {
+    fn default() -> Self {
+        UnpublishConfig {
+            strategy: UnpublishStrategy::default(),
+            archive_dir: "content/archive".into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnpublishStrategy {
+    /// Move the file to `content/drafts/`.
+    #[default]
+    MoveToDrafts,
+    /// Leave the file where it is with `draft: true` in its frontmatter.
+    MarkDraft,
+    /// Leave the file where it is with `unlisted: true`. The post is still
+    /// reachable by its URL, so it still shows as live.
+    MarkUnlisted,
+    /// Move the file to `archive_dir/{YYYY-MM-DD}/`.
+    Archive,
+    /// Delete the file and add a 410 Gone rule for its URL to the redirects
+    /// file (Netlify or JSON map).
+    Delete,
+}
+
+impl UnpublishStrategy {
+    /// How the journal records it.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            UnpublishStrategy::MoveToDrafts => "move_to_drafts",
+            UnpublishStrategy::MarkDraft => "mark_draft",
+            UnpublishStrategy::MarkUnlisted => "mark_unlisted",
+            UnpublishStrategy::Archive => "archive",
+            UnpublishStrategy::Delete => "delete",
+        }
+    }
+}
+
+/// Poll the post's URL after a push until the site has rebuilt with it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeployVerify {
+    pub enabled: bool,
+    /// Poll this origin instead of `domain` (e.g. a staging or local server).
+    /// Empty = the live domain.
+    pub base_url: String,
+    pub interval_secs: u64,
+    /// Give up and report the deploy as failed after this long.
+    pub timeout_secs: u64,
+}
+
+impl Default for DeployVerify {
+    fn default() -> Self {
+        DeployVerify {
+            enabled: false,
+            base_url: String::new(),
+            interval_secs: 10,
+            timeout_secs: 600,
+        }
+    }
+}
+
+/// Per-target switches for the vault -> repo transform pipeline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TransformConfig {
+    /// Rewrite `[[note]]` to site links (or plain text if unpublished).
+    pub wiki_links: bool,
+    /// Inline `![[note]]` and upload `![[image.png]]`.
+    pub embeds: bool,
+    /// Strip `%%comments%%` and `^block` ids.
+    pub comments: bool,
+    /// Strip `> [!private]` callouts.
+    pub private_blocks: bool,
+    /// Drop Obsidian- and Dispatch-only frontmatter keys.
+    pub frontmatter: bool,
+}
+
+impl Default for TransformConfig {
+    fn default() -> Self {
+        TransformConfig {
+            wiki_links: true,
+            embeds: true,
+            comments: true,
+            private_blocks: true,
+            frontmatter: true,
+        }
+    }
+}
+
+/// Which lint rules run, and at what level. A rule not named here keeps
+/// its default from `lint::RULES`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LintConfig {
+    /// Rule id (`long-link-text`) or group (`privacy`) -> level, for every
+    /// note.
+    pub rules: BTreeMap<String, LintLevel>,
+    /// Content type (`post`, `weeknote`) -> rule id or group -> level.
+    /// Wins over `rules`.
+    pub content_types: BTreeMap<String, BTreeMap<String, LintLevel>>,
+    /// Refuse to publish a note with error-level findings.
+    pub block_publish: bool,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        LintConfig {
+            rules: BTreeMap::new(),
+            content_types: BTreeMap::new(),
+            block_publish: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LintLevel {
+    Off,
+    Info,
+    Warn,
+    Error,
+}
+
+const DEFAULT_URL_PATTERN: &str = "/blog/{year}/{slug}";
+
+/// Values substituted into `content_path_pattern`.
+#[derive(Debug, Clone)]
+pub struct ContentPathFields<'a> {
+    /// Four-digit year, from the post's date.
+    pub year: &'a str,
+    /// Two-digit month, from the post's date.
+    pub month: &'a str,
+    pub slug: &'a str,
+    /// "post" or "weeknote".
+    pub content_type: &'a str,
+}
+
+impl PublishTarget {
+    /// The fixed part of `content_path_pattern` before the first token:
+    /// "content/blog/{year}" -> "content/blog".
+    pub fn content_base(&self) -> &str {
+        let pattern = self.content_path_pattern.trim_end_matches('/');
+        match pattern.find('{') {
+            Some(i) => pattern[..i].trim_end_matches('/'),
+            None => pattern,
+        }
+    }
+
+    /// Repo-relative path of a post's file. A pattern containing `{slug}`
+    /// names the file itself (`.md` is appended); otherwise it's the
+    /// directory and the file is `{slug}.md` inside it.
+    pub fn content_path(&self, fields: &ContentPathFields) -> String {
+        let pattern = self.content_path_pattern.trim_end_matches('/');
+        let pattern = if pattern.contains("{slug}") {
+            format!("{}.md", pattern)
+        } else {
+            format!("{}/{{slug}}.md", pattern)
+        };
+        pattern
+            .replace("{year}", fields.year)
+            .replace("{month}", fields.month)
+            .replace("{content_type}", fields.content_type)
+            .replace("{slug}", fields.slug)
+    }
+
+    /// Live URL of a post filed under `year` and `month`. A segment of
+    /// `url_pattern` whose token has no value (a post filed without a year)
+    /// is left out.
+    pub fn post_url(&self, year: Option<&str>, month: Option<&str>, slug: &str) -> String {
+        let pattern = match self.url_pattern.trim() {
+            "" => DEFAULT_URL_PATTERN,
+            pattern => pattern,
+        };
+        let fill = |segment: &str| {
+            let mut segment = segment.to_string();
+            for (token, value) in [("{year}", year), ("{month}", month), ("{slug}", Some(slug))] {
+                if segment.contains(token) {
+                    segment = segment.replace(token, value?);
+                }
+            }
+            Some(segment)
+        };
+        let path: Vec<String> = pattern
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .filter_map(fill)
+            .collect();
+        format!("{}/{}", self.domain, path.join("/"))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -138,8 +453,16 @@ impl Default for AppConfig {
                 repo_path: format!("{}/code/website2", home),
                 domain: "https://ejfox.com".into(),
                 content_path_pattern: "content/blog/{year}".into(),
+                url_pattern: String::new(),
                 branch: "main".into(),
                 is_default: true,
+                mode: PublishMode::default(),
+                transforms: TransformConfig::default(),
+                hooks: PublishHooks::default(),
+                verify: DeployVerify::default(),
+                commit: CommitConfig::default(),
+                redirects: RedirectsConfig::default(),
+                unpublish: UnpublishConfig::default(),
             }],
             editors: vec![
                 EditorConfig {
@@ -162,8 +485,10 @@ impl Default for AppConfig {
             cloudinary_cloud_name: None,
             analytics_url: None,
             mastodon_instance: None,
+            timezone: None,
             media: MediaConfig::default(),
             webmentions_bridgy_fed: false,
+            lint: LintConfig::default(),
         }
     }
 }
@@ -375,6 +700,19 @@ mod tests {
         assert!(config.media.r2.is_none());
     }
 
+    #[test]
+    fn test_target_without_transforms_runs_every_step() {
+        let json = r#"{
+            "name": "Site", "id": "site", "repo_path": "/tmp/site",
+            "domain": "https://example.com", "content_path_pattern": "content/blog/{year}",
+            "branch": "main", "is_default": true,
+            "transforms": { "comments": false }
+        }"#;
+        let target: PublishTarget = serde_json::from_str(json).unwrap();
+        assert!(!target.transforms.comments);
+        assert!(target.transforms.wiki_links && target.transforms.frontmatter);
+    }
+
     #[test]
     fn test_legacy_cloudinary_cloud_name_migrates_forward() {
         let mut config = AppConfig::default();
@@ -388,4 +726,57 @@ mod tests {
         // Idempotent.
         assert!(!migrate_legacy_cloudinary(&mut config));
     }
+
+    #[test]
+    fn test_content_path_pattern_tokens() {
+        let mut target = AppConfig::default().publish_targets[0].clone();
+        let fields = ContentPathFields {
+            year: "2025",
+            month: "12",
+            slug: "late-post",
+            content_type: "post",
+        };
+        assert_eq!(target.content_base(), "content/blog");
+        assert_eq!(
+            target.content_path(&fields),
+            "content/blog/2025/late-post.md"
+        );
+
+        target.content_path_pattern = "content/{content_type}/{year}/{month}/{slug}".into();
+        assert_eq!(target.content_base(), "content");
+        assert_eq!(
+            target.content_path(&fields),
+            "content/post/2025/12/late-post.md"
+        );
+
+        target.content_path_pattern = "content/posts/".into();
+        assert_eq!(target.content_base(), "content/posts");
+        assert_eq!(target.content_path(&fields), "content/posts/late-post.md");
+    }
+
+    #[test]
+    fn post_urls_follow_the_url_pattern() {
+        let mut target = AppConfig::default().publish_targets[0].clone();
+        target.domain = "https://example.com".into();
+        assert_eq!(
+            target.post_url(Some("2025"), Some("12"), "late-post"),
+            "https://example.com/blog/2025/late-post"
+        );
+        assert_eq!(
+            target.post_url(None, None, "late-post"),
+            "https://example.com/blog/late-post"
+        );
+
+        target.content_path_pattern = "content/posts/{year}/{month}/{slug}".into();
+        target.url_pattern = "/posts/{year}/{month}/{slug}/".into();
+        assert_eq!(
+            target.post_url(Some("2025"), Some("12"), "late-post"),
+            "https://example.com/posts/2025/12/late-post"
+        );
+        target.url_pattern = "{slug}.html".into();
+        assert_eq!(
+            target.post_url(Some("2025"), Some("12"), "late-post"),
+            "https://example.com/late-post.html"
+        );
+    }
 }
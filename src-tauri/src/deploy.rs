@@ -0,0 +1,267 @@
+//! Deploy verification after a push.
+//!
+//! A successful `git push` only means the site's build has started. When a
+//! target has `verify.enabled`, `watch` polls the post's URL in the
+//! background until it answers 200 with the post's title in the page, then
+//! emits `publish-live` — or `publish-deploy-failed` once the timeout runs
+//! out — and journals how long it took.
+
+use crate::config::PublishTarget;
+use reqwest::blocking::Client;
+use reqwest::header::CACHE_CONTROL;
+use reqwest::StatusCode;
+use serde::Serialize;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DeployCheck {
+    pub target_id: String,
+    pub slug: String,
+    pub title: Option<String>,
+    /// The post's public URL.
+    pub url: String,
+    /// What was actually polled; differs from `url` when the target has a
+    /// `verify.base_url`.
+    pub checked_url: String,
+    pub live: bool,
+    pub attempts: u32,
+    /// Push to live (or to giving up).
+    pub elapsed_ms: u64,
+    /// Status of the last response, if the server answered at all.
+    pub status: Option<u16>,
+    /// Why the last attempt didn't count. None once live.
+    pub error: Option<String>,
+}
+
+struct Poll {
+    live: bool,
+    attempts: u32,
+    elapsed_ms: u64,
+    status: Option<u16>,
+    error: Option<String>,
+}
+
+/// Verify a fresh publish in the background, if the target asks for it.
+/// Call right after the push so `elapsed_ms` measures the deploy.
+pub fn watch(
+    app: &AppHandle,
+    target: &PublishTarget,
+    source_path: &str,
+    slug: &str,
+    url: &str,
+    commit_sha: Option<&str>,
+) {
+    if !target.verify.enabled {
+        return;
+    }
+    let app = app.clone();
+    let target = target.clone();
+    let title = crate::vault::post_title(Path::new(source_path));
+    let (slug, url) = (slug.to_string(), url.to_string());
+    let commit_sha = commit_sha.map(String::from);
+
+    std::thread::spawn(move || {
+        let check = verify(&target, &slug, &url, title);
+        let (event, emitted) = if check.live {
+            ("live", "publish-live")
+        } else {
+            ("deploy_failed", "publish-deploy-failed")
+        };
+        if check.live {
+            log::warn!("{} is live after {}ms", check.checked_url, check.elapsed_ms);
+        } else {
+            log::warn!(
+                "{} not live after {}ms: {}",
+                check.checked_url,
+                check.elapsed_ms,
+                check.error.as_deref().unwrap_or("unknown error")
+            );
+        }
+        let _ = crate::journal::record_deploy(crate::journal::DeployRecord {
+            event,
+            slug: &slug,
+            title: check.title.as_deref(),
+            url: &url,
+            target_id: &target.id,
+            commit_sha: commit_sha.as_deref(),
+            time_to_live_ms: check.elapsed_ms,
+        });
+        let _ = app.emit(emitted, &check);
+    });
+}
+
+/// Poll until the post is live or the target's timeout passes. Blocks.
+pub fn verify(target: &PublishTarget, slug: &str, url: &str, title: Option<String>) -> DeployCheck {
+    let checked_url = checked_url(target, url);
+    let poll = poll(
+        &checked_url,
+        title.as_deref(),
+        Duration::from_secs(target.verify.interval_secs.max(1)),
+        Duration::from_secs(target.verify.timeout_secs),
+    );
+    DeployCheck {
+        target_id: target.id.clone(),
+        slug: slug.to_string(),
+        title,
+        url: url.to_string(),
+        checked_url,
+        live: poll.live,
+        attempts: poll.attempts,
+        elapsed_ms: poll.elapsed_ms,
+        status: poll.status,
+        error: poll.error,
+    }
+}
+
+/// `url` with the target's domain swapped for `verify.base_url`, if set.
+fn checked_url(target: &PublishTarget, url: &str) -> String {
+    let base = target.verify.base_url.trim().trim_end_matches('/');
+    if base.is_empty() {
+        return url.to_string();
+    }
+    match url.strip_prefix(target.domain.trim_end_matches('/')) {
+        Some(path) => format!("{}{}", base, path),
+        None => url.to_string(),
+    }
+}
+
+fn poll(url: &str, title: Option<&str>, interval: Duration, timeout: Duration) -> Poll {
+    let started = Instant::now();
+    let mut poll = Poll {
+        live: false,
+        attempts: 0,
+        elapsed_ms: 0,
+        status: None,
+        error: None,
+    };
+    let client = match Client::builder().timeout(Duration::from_secs(15)).build() {
+        Ok(c) => c,
+        Err(e) => {
+            poll.error = Some(format!("Failed to build HTTP client: {}", e));
+            return poll;
+        }
+    };
+
+    loop {
+        poll.attempts += 1;
+        match client.get(url).header(CACHE_CONTROL, "no-cache").send() {
+            Ok(resp) => {
+                let status = resp.status();
+                poll.status = Some(status.as_u16());
+                if status != StatusCode::OK {
+                    poll.error = Some(format!("HTTP {}", status.as_u16()));
+                } else {
+                    match resp.text() {
+                        Ok(body) if title.is_none_or(|t| page_has_title(&body, t)) => {
+                            poll.live = true;
+                            poll.error = None;
+                        }
+                        Ok(_) => {
+                            poll.error =
+                                Some("Page is up but doesn't show the post title yet".into())
+                        }
+                        Err(e) => poll.error = Some(format!("Failed to read page: {}", e)),
+                    }
+                }
+            }
+            Err(e) => {
+                poll.status = None;
+                poll.error = Some(e.to_string());
+            }
+        }
+        if poll.live || started.elapsed() + interval > timeout {
+            break;
+        }
+        std::thread::sleep(interval);
+    }
+    poll.elapsed_ms = started.elapsed().as_millis() as u64;
+    poll
+}
+
+/// Does the page show `title`? Matches through the usual HTML escaping and
+/// smart quotes, since the site renders the title rather than copying it.
+fn page_has_title(html: &str, title: &str) -> bool {
+    let title = title.trim().trim_start_matches('#').trim();
+    if title.is_empty() {
+        return true;
+    }
+    let normalize = |s: &str| {
+        s.replace("&amp;", "&")
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&quot;", "\"")
+            .replace("&#39;", "'")
+            .replace("&#x27;", "'")
+            .replace("&apos;", "'")
+            .replace(['\u{2018}', '\u{2019}'], "'")
+            .replace(['\u{201C}', '\u{201D}'], "\"")
+    };
+    normalize(html).contains(&normalize(title))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    /// Answer one request per canned response, then stop listening.
+    fn serve(responses: Vec<(u16, &'static str)>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for ((status, body), stream) in responses.into_iter().zip(listener.incoming()) {
+                let mut stream = stream.unwrap();
+                let _ = stream.read(&mut [0u8; 4096]);
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {} X\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    #[test]
+    fn polls_until_the_page_shows_the_title() {
+        let base = serve(vec![
+            (404, "not yet"),
+            (200, "<h1>Some other post</h1>"),
+            (200, "<title>Tea &amp; Toast</title>"),
+        ]);
+        let poll = poll(
+            &format!("{}/blog/2026/tea", base),
+            Some("# Tea & Toast"),
+            Duration::from_millis(10),
+            Duration::from_secs(10),
+        );
+        assert!(poll.live, "{:?}", poll.error);
+        assert_eq!(poll.attempts, 3);
+        assert_eq!(poll.status, Some(200));
+    }
+
+    #[test]
+    fn gives_up_at_the_timeout_and_checks_the_base_url() {
+        let base = serve(vec![(500, ""), (500, ""), (500, "")]);
+        let mut target = AppConfig::default().publish_targets[0].clone();
+        target.domain = "https://example.com/".into();
+        target.verify.base_url = format!("{}/", base);
+        let url = checked_url(&target, "https://example.com/blog/2026/tea");
+        assert_eq!(url, format!("{}/blog/2026/tea", base));
+
+        let poll = poll(
+            &url,
+            None,
+            Duration::from_millis(20),
+            Duration::from_millis(50),
+        );
+        assert!(!poll.live);
+        assert_eq!(poll.error.as_deref(), Some("HTTP 500"));
+    }
+}
@@ -0,0 +1,437 @@
+//! YAML frontmatter, read with a real YAML parser and edited in place.
+//!
+//! Notes are written by hand, so an edit rewrites only the lines of the key
+//! it touches: comments, blank lines, key order and the way every other
+//! value is written come back out as they went in. Obsidian is forgiving
+//! about YAML (`title: Tea: a history`), so a block that isn't valid as a
+//! whole is read key by key, and a value that still won't parse is kept as
+//! its text.
+
+use serde_yaml::{Mapping, Value};
+use std::fs;
+use std::ops::Range;
+
+/// A note's frontmatter block.
+#[derive(Debug, Clone, Default)]
+pub struct Frontmatter {
+    /// The `---` lines as written; None while the note has no block.
+    delimiters: Option<(String, String)>,
+    /// Everything between the delimiters, one line each, newlines included.
+    lines: Vec<String>,
+    values: Mapping,
+}
+
+/// Split a note into its leading `---` block, delimiters included, and the
+/// body after it.
+pub fn split(content: &str) -> (Option<&str>, &str) {
+    let mut offset = 0;
+    for (i, line) in content.split_inclusive('\n').enumerate() {
+        offset += line.len();
+        if line.trim_end() != "---" {
+            if i == 0 {
+                break;
+            }
+            continue;
+        }
+        if i > 0 {
+            return (Some(&content[..offset]), &content[offset..]);
+        }
+    }
+    (None, content)
+}
+
+/// A note's text without its frontmatter.
+pub fn body(content: &str) -> &str {
+    split(content).1
+}
+
+/// Edit a note's frontmatter, leaving its body alone. The file is only
+/// written if the edit changed it.
+pub fn edit_file(path: &str, edit: impl FnOnce(&mut Frontmatter)) -> Result<(), String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let (mut frontmatter, body) = Frontmatter::parse(&content);
+    if frontmatter.delimiters.is_none() && content.lines().next().map(str::trim_end) == Some("---")
+    {
+        return Err("Invalid frontmatter: no closing ---".into());
+    }
+    edit(&mut frontmatter);
+    let updated = format!("{}{}", frontmatter.block(), body);
+    if updated != content {
+        fs::write(path, updated).map_err(|e| format!("Failed to write file: {}", e))?;
+    }
+    Ok(())
+}
+
+impl Frontmatter {
+    /// Read a note's frontmatter; the second half is the body after it.
+    pub fn parse(content: &str) -> (Frontmatter, &str) {
+        let (block, body) = split(content);
+        let mut frontmatter = Frontmatter::default();
+        if let Some(block) = block {
+            let mut lines: Vec<String> = block.split_inclusive('\n').map(String::from).collect();
+            let close = lines.pop().unwrap_or_default();
+            let open = lines.remove(0);
+            frontmatter.delimiters = Some((open, close));
+            frontmatter.lines = lines;
+            frontmatter.reparse();
+        }
+        (frontmatter, body)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.values.get(key)
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.values.contains_key(key)
+    }
+
+    /// A single value as text (`2026-01-31`, `true`, `Tea: a history`).
+    /// None when the key is missing or empty, or holds a list or map.
+    pub fn get_str(&self, key: &str) -> Option<String> {
+        self.get(key).and_then(scalar)
+    }
+
+    /// `true` or `yes`.
+    pub fn get_bool(&self, key: &str) -> bool {
+        matches!(self.get_str(key).as_deref(), Some("true" | "yes"))
+    }
+
+    /// A list, written as a YAML list (`[a, b]` or `- a` lines) or as
+    /// comma-separated text.
+    pub fn get_list(&self, key: &str) -> Vec<String> {
+        let items = match self.get(key) {
+            Some(Value::Sequence(items)) => items.iter().filter_map(scalar).collect(),
+            Some(value) => scalar(value)
+                .map(|text| text.split(',').map(|s| s.trim().to_string()).collect())
+                .unwrap_or_default(),
+            None => Vec::new(),
+        };
+        items
+            .into_iter()
+            .filter(|s: &String| !s.is_empty())
+            .collect()
+    }
+
+    pub fn tags(&self) -> Vec<String> {
+        self.get_list("tags")
+    }
+
+    /// Set `key` to a single value, replacing however it was written. A new
+    /// key goes at the end of the block, and a note without one gets one.
+    pub fn set(&mut self, key: &str, value: &str) {
+        let line = format!("{}: {}{}", key, scalar_text(value, false), self.newline());
+        self.replace(key, vec![line]);
+    }
+
+    /// Set `key` to a list, in the style it's written in: `- item` lines
+    /// keep their indent, anything else is written inline (`[a, b]`).
+    pub fn set_list(&mut self, key: &str, items: &[String]) {
+        let newline = self.newline();
+        let indent = self.spans(key).first().and_then(|span| {
+            self.lines[span.start + 1..span.end]
+                .iter()
+                .find_map(|line| {
+                    let item = line.trim_start();
+                    item.starts_with('-')
+                        .then(|| line[..line.len() - item.len()].to_string())
+                })
+        });
+        let lines = match indent {
+            Some(indent) if !items.is_empty() => std::iter::once(format!("{}:{}", key, newline))
+                .chain(
+                    items
+                        .iter()
+                        .map(|item| format!("{}- {}{}", indent, scalar_text(item, false), newline)),
+                )
+                .collect(),
+            _ => {
+                let items: Vec<String> = items.iter().map(|i| scalar_text(i, true)).collect();
+                vec![format!("{}: [{}]{}", key, items.join(", "), newline)]
+            }
+        };
+        self.replace(key, lines);
+    }
+
+    /// Drop `key` and all its lines. Whether it was there.
+    pub fn remove(&mut self, key: &str) -> bool {
+        let spans = self.spans(key);
+        for span in spans.iter().rev() {
+            self.lines.drain(span.clone());
+        }
+        if !spans.is_empty() {
+            self.reparse();
+        }
+        !spans.is_empty()
+    }
+
+    /// The block as written, delimiters included; empty for a note without
+    /// one.
+    pub fn block(&self) -> String {
+        match &self.delimiters {
+            Some((open, close)) => format!("{}{}{}", open, self.lines.concat(), close),
+            None => String::new(),
+        }
+    }
+
+    fn replace(&mut self, key: &str, lines: Vec<String>) {
+        let spans = self.spans(key);
+        match spans.split_first() {
+            Some((first, duplicates)) => {
+                for span in duplicates.iter().rev() {
+                    self.lines.drain(span.clone());
+                }
+                self.lines.splice(first.clone(), lines);
+            }
+            None => {
+                self.delimiters
+                    .get_or_insert_with(|| ("---\n".into(), "---\n".into()));
+                let end = self
+                    .lines
+                    .iter()
+                    .rposition(|line| !line.trim().is_empty())
+                    .map_or(0, |i| i + 1);
+                self.lines.splice(end..end, lines);
+            }
+        }
+        self.reparse();
+    }
+
+    /// The line ranges `key` is written on (more than one if it's repeated).
+    fn spans(&self, key: &str) -> Vec<Range<usize>> {
+        entries(&self.lines)
+            .into_iter()
+            .filter(|(k, _)| k == key)
+            .map(|(_, span)| span)
+            .collect()
+    }
+
+    fn newline(&self) -> &'static str {
+        match &self.delimiters {
+            Some((open, _)) if open.ends_with("\r\n") => "\r\n",
+            _ => "\n",
+        }
+    }
+
+    fn reparse(&mut self) {
+        self.values = match serde_yaml::from_str::<Value>(&self.lines.concat()) {
+            Ok(Value::Mapping(values)) => values,
+            // Nothing but comments and blank lines.
+            Ok(Value::Null) => Mapping::new(),
+            _ => entries(&self.lines)
+                .into_iter()
+                .map(|(key, span)| (Value::String(key), entry_value(&self.lines[span])))
+                .collect(),
+        };
+    }
+}
+
+/// Top-level keys and the lines each is written on: the `key:` line, then
+/// any indented or `- item` lines under it. Blank lines between entries
+/// belong to neither.
+fn entries(lines: &[String]) -> Vec<(String, Range<usize>)> {
+    let mut entries = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let Some(key) = entry_key(&lines[i]) else {
+            i += 1;
+            continue;
+        };
+        let mut end = i + 1;
+        for (j, line) in lines.iter().enumerate().skip(i + 1) {
+            if line.trim().is_empty() {
+                continue;
+            }
+            if !line.starts_with([' ', '\t', '-']) {
+                break;
+            }
+            end = j + 1;
+        }
+        entries.push((key, i..end));
+        i = end;
+    }
+    entries
+}
+
+fn entry_key(line: &str) -> Option<String> {
+    if line.starts_with([' ', '\t', '#', '-']) {
+        return None;
+    }
+    let (key, _) = line.split_once(':')?;
+    let key = key.trim().trim_matches(|c| c == '"' || c == '\'');
+    (!key.is_empty()).then(|| key.to_string())
+}
+
+/// One entry's value, for a block that isn't valid YAML as a whole. An
+/// entry that isn't valid on its own either is read as text after the
+/// colon, or as its `- item` lines.
+fn entry_value(lines: &[String]) -> Value {
+    if let Ok(Value::Mapping(entry)) = serde_yaml::from_str::<Value>(&lines.concat()) {
+        if let Some((_, value)) = entry.into_iter().next() {
+            return value;
+        }
+    }
+    let unquote = |s: &str| Value::String(s.trim().trim_matches(['"', '\'']).to_string());
+    let text = lines[0]
+        .split_once(':')
+        .map_or("", |(_, value)| value.trim());
+    let items: Vec<Value> = lines[1..]
+        .iter()
+        .filter_map(|line| line.trim().strip_prefix("- ").map(unquote))
+        .collect();
+    if text.is_empty() && !items.is_empty() {
+        Value::Sequence(items)
+    } else {
+        unquote(text)
+    }
+}
+
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Bool(b) => Some(b.to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Tagged(tagged) => scalar(&tagged.value),
+        Value::Null | Value::Sequence(_) | Value::Mapping(_) => None,
+    }
+}
+
+/// `value` as written after `key: `: bare if YAML reads it back as the same
+/// text, double-quoted if not. Inside `[a, b]`, commas and brackets need
+/// quotes too.
+fn scalar_text(value: &str, in_list: bool) -> String {
+    let reads_back = || {
+        serde_yaml::from_str::<Value>(value)
+            .ok()
+            .as_ref()
+            .and_then(scalar)
+            .as_deref()
+            == Some(value)
+    };
+    let bare = !(in_list && value.contains([',', '[', ']', '{', '}'])) && reads_back();
+    if bare {
+        value.to_string()
+    } else {
+        serde_json::to_string(value).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTE: &str = "---
+# Written by hand
+title: \"Tea: a history\"
+date: 2026-01-31
+
+tags:
+  - tea
+  - history
+cover:
+  src: pot.jpg
+  alt: A teapot
+draft: false
+---
+# Tea
+";
+
+    #[test]
+    fn reads_what_a_line_splitter_cannot() {
+        let (frontmatter, body) = Frontmatter::parse(NOTE);
+        assert_eq!(body, "# Tea\n");
+        assert_eq!(
+            frontmatter.get_str("title").as_deref(),
+            Some("Tea: a history")
+        );
+        assert_eq!(frontmatter.get_str("date").as_deref(), Some("2026-01-31"));
+        assert_eq!(frontmatter.tags(), ["tea", "history"]);
+        assert!(!frontmatter.get_bool("draft"));
+        let cover = frontmatter
+            .get("cover")
+            .and_then(Value::as_mapping)
+            .unwrap();
+        assert_eq!(cover.get("alt").and_then(Value::as_str), Some("A teapot"));
+        assert_eq!(frontmatter.get_str("cover"), None);
+
+        // Not valid YAML as a whole: each key is still read.
+        let sloppy = "---\ntitle: Tea: a history\ntags: tea, history\nunlisted: yes\n---\n";
+        let (frontmatter, body) = Frontmatter::parse(sloppy);
+        assert_eq!(body, "");
+        assert_eq!(
+            frontmatter.get_str("title").as_deref(),
+            Some("Tea: a history")
+        );
+        assert_eq!(frontmatter.tags(), ["tea", "history"]);
+        assert!(frontmatter.get_bool("unlisted"));
+
+        let (frontmatter, body) = Frontmatter::parse("---\n# a rule, not frontmatter\n");
+        assert!(!frontmatter.contains("title"));
+        assert_eq!(body, "---\n# a rule, not frontmatter\n");
+    }
+
+    #[test]
+    fn edits_only_touch_their_own_lines() {
+        let (mut frontmatter, _) = Frontmatter::parse(NOTE);
+        frontmatter.set("draft", "true");
+        frontmatter.set_list("tags", &["tea".into(), "history".into(), "china".into()]);
+        frontmatter.set("publish_at", "2026-03-01T09:00:00Z");
+        assert!(frontmatter.remove("date"));
+        assert!(!frontmatter.remove("date"));
+        assert_eq!(
+            frontmatter.block(),
+            "---
+# Written by hand
+title: \"Tea: a history\"
+
+tags:
+  - tea
+  - history
+  - china
+cover:
+  src: pot.jpg
+  alt: A teapot
+draft: true
+publish_at: 2026-03-01T09:00:00Z
+---
+"
+        );
+        assert!(frontmatter.get_bool("draft"));
+        assert_eq!(frontmatter.tags().len(), 3);
+
+        let (mut frontmatter, _) = Frontmatter::parse("---\r\ntags: [a]\r\n---\r\nbody");
+        frontmatter.set_list("tags", &["a".into(), "b, c".into()]);
+        frontmatter.set("title", "Tea: a history");
+        assert_eq!(
+            frontmatter.block(),
+            "---\r\ntags: [a, \"b, c\"]\r\ntitle: \"Tea: a history\"\r\n---\r\n"
+        );
+
+        let (mut frontmatter, body) = Frontmatter::parse("No block yet\n");
+        frontmatter.set("draft", "true");
+        assert_eq!(
+            format!("{}{}", frontmatter.block(), body),
+            "---\ndraft: true\n---\nNo block yet\n"
+        );
+    }
+
+    #[test]
+    fn values_are_quoted_only_when_they_need_it() {
+        for bare in [
+            "tea",
+            "2026-01-31",
+            "2026-03-01T09:00:00-05:00",
+            "true",
+            "a b",
+        ] {
+            assert_eq!(scalar_text(bare, false), bare);
+        }
+        assert_eq!(scalar_text("Tea: a history", false), "\"Tea: a history\"");
+        assert_eq!(scalar_text("1.10", false), "\"1.10\"");
+        assert_eq!(scalar_text("null", false), "\"null\"");
+        assert_eq!(scalar_text("#tea", false), "\"#tea\"");
+        assert_eq!(scalar_text("", false), "\"\"");
+        assert_eq!(scalar_text("a, b", false), "a, b");
+        assert_eq!(scalar_text("a, b", true), "\"a, b\"");
+    }
+}
@@ -2,7 +2,6 @@ use crate::config;
 use chrono::Local;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
-use std::process::Command;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Gear {
@@ -261,13 +260,8 @@ pub struct GearPending {
 
 #[tauri::command]
 pub fn gear_pending_changes() -> Result<GearPending, String> {
-    let repo = repo_path()?;
-    let out = Command::new(crate::bin_paths::git())
-        .args(["diff", "--stat", "--", "data/gear.csv"])
-        .current_dir(&repo)
-        .output()
-        .map_err(|e| format!("git diff: {}", e))?;
-    let stat = String::from_utf8_lossy(&out.stdout).trim().to_string();
+    let repo = crate::git::Repo::open(repo_path()?)?;
+    let stat = repo.diff_stat(&["data/gear.csv"])?;
     Ok(GearPending {
         dirty: !stat.is_empty(),
         diff_stat: stat,
@@ -276,40 +270,14 @@ pub fn gear_pending_changes() -> Result<GearPending, String> {
 
 #[tauri::command]
 pub fn commit_gear_changes(message: Option<String>) -> Result<String, String> {
-    let repo = repo_path()?;
+    let repo_path = repo_path()?;
+    let _lock = crate::publish_lock::acquire(&repo_path.to_string_lossy(), "gear commit")?;
+    let repo = crate::git::Repo::open(repo_path)?;
     let msg = message.unwrap_or_else(|| format!("gear: hygiene pass {}", today()));
 
-    let add = Command::new(crate::bin_paths::git())
-        .args(["add", "data/gear.csv"])
-        .current_dir(&repo)
-        .output()
-        .map_err(|e| format!("git add: {}", e))?;
-    if !add.status.success() {
-        return Err(format!(
-            "git add failed: {}",
-            String::from_utf8_lossy(&add.stderr)
-        ));
+    repo.add(&["data/gear.csv"])?;
+    match repo.commit(&msg)? {
+        crate::git::CommitOutcome::Created(_) => Ok(msg),
+        crate::git::CommitOutcome::NothingToCommit => Ok("nothing to commit".into()),
     }
-
-    let staged = Command::new(crate::bin_paths::git())
-        .args(["diff", "--cached", "--stat", "--", "data/gear.csv"])
-        .current_dir(&repo)
-        .output()
-        .map_err(|e| format!("git diff cached: {}", e))?;
-    if String::from_utf8_lossy(&staged.stdout).trim().is_empty() {
-        return Ok("nothing to commit".into());
-    }
-
-    let commit = Command::new(crate::bin_paths::git())
-        .args(["commit", "-m", &msg])
-        .current_dir(&repo)
-        .output()
-        .map_err(|e| format!("git commit: {}", e))?;
-    if !commit.status.success() {
-        return Err(format!(
-            "git commit failed: {}",
-            String::from_utf8_lossy(&commit.stderr)
-        ));
-    }
-    Ok(msg)
 }
@@ -0,0 +1,1327 @@
+//! Git operations on the website repos, with typed results.
+//!
+//! Local work (status, staging, commits, reverts, history) runs in-process
+//! through libgit2, so nothing depends on the user's locale or git version
+//! and there is no stderr to pattern-match. Talking to the remote (pull and
+//! push) still goes through the `git` binary: that's the only way to honour
+//! the user's SSH agent, credential helpers and `~/.ssh/config` without
+//! reimplementing them. Those two calls are classified by exit code, repo
+//! state and `--porcelain` output rather than by their messages, with one
+//! exception: whether the remote could be reached at all is read from the
+//! network errors git relays from curl and ssh (run in the C locale).
+//!
+//! Commits made here don't run the repo's git hooks.
+
+use git2::build::{CheckoutBuilder, TreeUpdateBuilder};
+use git2::{
+    BranchType, DiffFindOptions, DiffOptions, ErrorCode, FileMode, IndexAddOption, Oid, Patch,
+    Repository, RepositoryState, ResetType, Signature, Sort, Status, StatusOptions,
+};
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// The remote review branches are pushed to and fetched from.
+const REMOTE: &str = "origin";
+
+#[derive(Debug)]
+pub enum GitError {
+    /// The path isn't inside a git work tree.
+    NotARepo(String),
+    /// The commit (or other revision) doesn't exist in this repo.
+    UnknownRevision(String),
+    /// Applying changes would conflict. Holds the paths involved.
+    Conflict(Vec<String>),
+    /// The remote couldn't be reached (offline, DNS, timeout). Holds git's
+    /// own message.
+    Unreachable(String),
+    /// `git pull --rebase` failed for a reason other than a conflict or the
+    /// network (auth, no upstream...). Holds git's own message.
+    Pull(String),
+    /// The remote refused the push (usually: someone else pushed first).
+    PushRejected(String),
+    /// The push failed for a reason other than the network (auth...).
+    Push(String),
+    /// `git fetch` failed for a reason other than the network (auth...).
+    Fetch(String),
+    /// The current branch has commits the revision doesn't, so it can't be
+    /// fast-forwarded to it. Holds the revision.
+    NotFastForward(String),
+    /// `user.name` / `user.email` aren't configured.
+    Identity(String),
+    /// Couldn't run the git binary at all.
+    Spawn(String),
+    /// Anything else libgit2 or the filesystem reported.
+    Other(String),
+}
+
+impl fmt::Display for GitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GitError::NotARepo(path) => write!(f, "Not a git repository: {}", path),
+            GitError::UnknownRevision(rev) => write!(f, "Commit {} is not in this repo", rev),
+            GitError::Conflict(paths) => write!(f, "Conflicts in {}", paths.join(", ")),
+            GitError::Unreachable(msg) => write!(f, "Couldn't reach the remote: {}", msg),
+            GitError::Pull(msg) => write!(f, "Git pull failed: {}", msg),
+            GitError::PushRejected(msg) => write!(f, "Push rejected by remote: {}", msg),
+            GitError::Push(msg) => write!(f, "Git push failed: {}", msg),
+            GitError::Fetch(msg) => write!(f, "Git fetch failed: {}", msg),
+            GitError::NotFastForward(rev) => write!(
+                f,
+                "Can't fast-forward to {}: the branch has moved on since it was made",
+                rev
+            ),
+            GitError::Identity(msg) => write!(
+                f,
+                "Git identity not set (git config user.name / user.email): {}",
+                msg
+            ),
+            GitError::Spawn(msg) => write!(f, "Failed to run git: {}", msg),
+            GitError::Other(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl GitError {
+    /// Talking to the remote failed in a way that can clear up on its own
+    /// (offline, someone else pushed first), so retrying makes sense. Auth
+    /// failures, a missing upstream and conflicts need a person.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, GitError::Unreachable(_) | GitError::PushRejected(_))
+    }
+}
+
+impl From<git2::Error> for GitError {
+    fn from(e: git2::Error) -> Self {
+        GitError::Other(e.message().to_string())
+    }
+}
+
+impl From<GitError> for String {
+    fn from(e: GitError) -> Self {
+        e.to_string()
+    }
+}
+
+/// One changed path, with the two-letter code `git status --porcelain` uses.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatusEntry {
+    pub code: String,
+    pub path: String,
+}
+
+impl fmt::Display for StatusEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.code, self.path)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RepoStatus {
+    /// `None` when HEAD is detached.
+    pub branch: Option<String>,
+    pub entries: Vec<StatusEntry>,
+    pub conflicts: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommitOutcome {
+    Created(String),
+    /// The index matched HEAD; no commit was made.
+    NothingToCommit,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PushOutcome {
+    Pushed,
+    UpToDate,
+}
+
+/// Who a commit is attributed to, as (name, email). Unset, the repo's
+/// `user.name` / `user.email` are used like a plain `git commit`; an author
+/// alone is like `git commit --author`, leaving the committer as configured.
+#[derive(Debug, Clone, Default)]
+pub struct Authorship {
+    pub author: Option<(String, String)>,
+    pub committer: Option<(String, String)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    pub sha: String,
+    /// Author date, RFC 3339 with the author's offset (like `%aI`).
+    pub author_time: String,
+    pub subject: String,
+}
+
+/// One commit's change to a file, from `Repo::file_history`.
+#[derive(Debug, Clone)]
+pub struct FileRevision {
+    pub sha: String,
+    /// Author date, RFC 3339 with the author's offset (like `%aI`).
+    pub author_time: String,
+    pub message: String,
+    /// Repo-relative path before the commit; None when it added the file.
+    pub old_path: Option<String>,
+    /// Repo-relative path after; None when it deleted the file.
+    pub new_path: Option<String>,
+    pub before: Option<String>,
+    pub after: Option<String>,
+    pub lines_added: usize,
+    pub lines_removed: usize,
+}
+
+pub struct Repo {
+    repo: Repository,
+    workdir: PathBuf,
+}
+
+impl Repo {
+    pub fn open(path: impl AsRef<Path>) -> Result<Repo, GitError> {
+        let path = path.as_ref();
+        let repo =
+            Repository::open(path).map_err(|_| GitError::NotARepo(path.display().to_string()))?;
+        let workdir = repo
+            .workdir()
+            .ok_or_else(|| GitError::NotARepo(format!("{} (bare)", path.display())))?
+            .to_path_buf();
+        Ok(Repo { repo, workdir })
+    }
+
+    pub fn workdir(&self) -> &Path {
+        &self.workdir
+    }
+
+    /// The `.git` directory (or a worktree's own git dir).
+    pub fn git_dir(&self) -> &Path {
+        self.repo.path()
+    }
+
+    /// Current branch name, `None` when detached. Works on an unborn branch.
+    pub fn branch(&self) -> Option<String> {
+        let head = self.repo.find_reference("HEAD").ok()?;
+        head.symbolic_target()
+            .map(|t| t.trim_start_matches("refs/heads/").to_string())
+    }
+
+    pub fn head(&self) -> Option<String> {
+        self.repo
+            .head()
+            .ok()
+            .and_then(|h| h.target())
+            .map(|oid| oid.to_string())
+    }
+
+    /// The commit SHA `rev` names, if it exists.
+    pub fn resolve(&self, rev: &str) -> Option<String> {
+        self.repo
+            .revparse_single(rev)
+            .and_then(|o| o.peel_to_commit())
+            .map(|c| c.id().to_string())
+            .ok()
+    }
+
+    /// Whether `ancestor` is `rev` itself or somewhere in its history.
+    pub fn contains(&self, rev: &str, ancestor: &str) -> bool {
+        match (self.resolve(rev), self.resolve(ancestor)) {
+            (Some(rev), Some(ancestor)) => {
+                let (Ok(rev), Ok(ancestor)) = (Oid::from_str(&rev), Oid::from_str(&ancestor))
+                else {
+                    return false;
+                };
+                rev == ancestor
+                    || self
+                        .repo
+                        .graph_descendant_of(rev, ancestor)
+                        .unwrap_or(false)
+            }
+            _ => false,
+        }
+    }
+
+    /// The origin remote as a browsable URL:
+    /// `git@github.com:me/site.git` -> `https://github.com/me/site`.
+    pub fn remote_web_url(&self) -> Option<String> {
+        let remote = self.repo.find_remote(REMOTE).ok()?;
+        web_url(remote.url()?)
+    }
+
+    pub fn status(&self) -> Result<RepoStatus, GitError> {
+        let mut opts = StatusOptions::new();
+        opts.include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .include_ignored(false);
+        let statuses = self.repo.statuses(Some(&mut opts))?;
+
+        let mut entries = Vec::new();
+        let mut conflicts = Vec::new();
+        for s in statuses.iter() {
+            let path = s.path().unwrap_or_default().to_string();
+            let flags = s.status();
+            if flags.contains(Status::CONFLICTED) {
+                conflicts.push(path.clone());
+            }
+            entries.push(StatusEntry {
+                code: porcelain_code(flags),
+                path,
+            });
+        }
+        Ok(RepoStatus {
+            branch: self.branch(),
+            entries,
+            conflicts,
+        })
+    }
+
+    /// Stage `paths` the way `git add -A <paths>` does: present files are
+    /// added, missing ones are removed from the index. Paths may be absolute
+    /// (inside the work tree) or relative to it.
+    pub fn add(&self, paths: &[&str]) -> Result<(), GitError> {
+        let mut index = self.repo.index()?;
+        index.read(false)?;
+        for p in paths {
+            let rel = self.relative(p)?;
+            if self.workdir.join(&rel).is_dir() {
+                index.add_all([&rel], IndexAddOption::DEFAULT, None)?;
+            } else if self.workdir.join(&rel).exists() {
+                index.add_path(&rel)?;
+            } else {
+                index.remove_path(&rel)?;
+            }
+        }
+        index.write()?;
+        Ok(())
+    }
+
+    /// Put the index entries for `paths` back to what HEAD has (`git reset -- <paths>`).
+    pub fn unstage(&self, paths: &[&str]) -> Result<(), GitError> {
+        let rels = paths
+            .iter()
+            .map(|p| self.relative(p))
+            .collect::<Result<Vec<_>, _>>()?;
+        let head = self.repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let target = head.as_ref().map(|c| c.as_object());
+        self.repo.reset_default(target, rels.iter())?;
+        Ok(())
+    }
+
+    /// Commit whatever is staged onto HEAD.
+    pub fn commit(&self, message: &str) -> Result<CommitOutcome, GitError> {
+        self.commit_as(message, &Authorship::default())
+    }
+
+    /// `commit`, attributed to `who`.
+    pub fn commit_as(&self, message: &str, who: &Authorship) -> Result<CommitOutcome, GitError> {
+        let mut index = self.repo.index()?;
+        index.read(false)?;
+        let tree_id = index.write_tree()?;
+        let parent = self.repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let unchanged = match &parent {
+            Some(p) => p.tree_id() == tree_id,
+            None => index.is_empty(),
+        };
+        if unchanged {
+            return Ok(CommitOutcome::NothingToCommit);
+        }
+
+        let (author, committer) = self.signatures(who)?;
+        let tree = self.repo.find_tree(tree_id)?;
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        let oid = self
+            .repo
+            .commit(Some("HEAD"), &author, &committer, message, &tree, &parents)?;
+        Ok(CommitOutcome::Created(oid.to_string()))
+    }
+
+    /// Commit `contents` at `path` on top of `parent` and point
+    /// `refs/heads/<branch>` at the result, creating or moving it. HEAD, the
+    /// index and the work tree are left alone. `NothingToCommit` (and no
+    /// branch change) when `parent` already has exactly that file.
+    pub fn commit_file_to_branch(
+        &self,
+        branch: &str,
+        parent: &str,
+        path: &str,
+        contents: &[u8],
+        message: &str,
+        who: &Authorship,
+    ) -> Result<CommitOutcome, GitError> {
+        let parent = self
+            .repo
+            .revparse_single(parent)
+            .and_then(|o| o.peel_to_commit())
+            .map_err(|_| GitError::UnknownRevision(parent.to_string()))?;
+        let rel = self.relative(path)?;
+        let blob = self.repo.blob(contents)?;
+        let mut update = TreeUpdateBuilder::new();
+        update.upsert(&rel, blob, FileMode::Blob);
+        let tree_id = update.create_updated(&self.repo, &parent.tree()?)?;
+        if tree_id == parent.tree_id() {
+            return Ok(CommitOutcome::NothingToCommit);
+        }
+
+        let (author, committer) = self.signatures(who)?;
+        let tree = self.repo.find_tree(tree_id)?;
+        let oid = self
+            .repo
+            .commit(None, &author, &committer, message, &tree, &[&parent])?;
+        self.repo
+            .reference(&format!("refs/heads/{}", branch), oid, true, message)?;
+        Ok(CommitOutcome::Created(oid.to_string()))
+    }
+
+    /// Author and committer signatures for `who`, stamped now.
+    fn signatures(
+        &self,
+        who: &Authorship,
+    ) -> Result<(Signature<'static>, Signature<'static>), GitError> {
+        let identity_error = |e: git2::Error| GitError::Identity(e.message().to_string());
+        let configured = || self.repo.signature().map_err(identity_error);
+        let named =
+            |(name, email): &(String, String)| Signature::now(name, email).map_err(identity_error);
+        let author = match &who.author {
+            Some(person) => named(person)?,
+            None => configured()?,
+        };
+        let committer = match &who.committer {
+            Some(person) => named(person)?,
+            // No user.name at all: an overridden author can stand in.
+            None => configured().or_else(|e| match &who.author {
+                Some(person) => named(person),
+                None => Err(e),
+            })?,
+        };
+        Ok((author, committer))
+    }
+
+    /// Move the current branch forward to `rev` and check it out, like
+    /// `git merge --ff-only`. Does nothing if the branch already contains
+    /// `rev`; refuses if a file it changes has local edits.
+    pub fn fast_forward(&self, rev: &str) -> Result<(), GitError> {
+        let target = self
+            .repo
+            .revparse_single(rev)
+            .and_then(|o| o.peel_to_commit())
+            .map_err(|_| GitError::UnknownRevision(rev.to_string()))?;
+        let head = self.repo.head()?.peel_to_commit()?;
+        if head.id() == target.id() || self.repo.graph_descendant_of(head.id(), target.id())? {
+            return Ok(());
+        }
+        if !self.repo.graph_descendant_of(target.id(), head.id())? {
+            return Err(GitError::NotFastForward(rev.to_string()));
+        }
+        let mut checkout = CheckoutBuilder::new();
+        checkout.safe();
+        self.repo
+            .checkout_tree(target.as_object(), Some(&mut checkout))
+            .map_err(|e| match e.code() {
+                ErrorCode::Conflict => GitError::Conflict(Vec::new()),
+                _ => e.into(),
+            })?;
+        self.repo
+            .head()?
+            .set_target(target.id(), &format!("fast-forward to {}", rev))?;
+        Ok(())
+    }
+
+    /// Delete local branch `branch`. Not finding it is fine.
+    pub fn delete_branch(&self, branch: &str) -> Result<(), GitError> {
+        match self.repo.find_branch(branch, BranchType::Local) {
+            Ok(mut b) => Ok(b.delete()?),
+            Err(e) if e.code() == ErrorCode::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Move HEAD back to `rev`, keeping the index and work tree (`git reset --soft`).
+    pub fn reset_soft(&self, rev: &str) -> Result<(), GitError> {
+        let obj = self
+            .repo
+            .revparse_single(rev)
+            .map_err(|_| GitError::UnknownRevision(rev.to_string()))?;
+        self.repo.reset(&obj, ResetType::Soft, None)?;
+        Ok(())
+    }
+
+    /// Commit the inverse of `rev` on top of HEAD, like `git revert --no-edit`.
+    /// Nothing is touched if the revert would conflict or if a file it needs
+    /// to change has local edits. `NothingToCommit` means the changes are
+    /// already gone.
+    pub fn revert(&self, rev: &str) -> Result<CommitOutcome, GitError> {
+        self.revert_as(rev, &Authorship::default(), str::to_string)
+    }
+
+    /// `revert`, attributed to `who`. `message` gets git's own
+    /// (`Revert "…"`, `This reverts commit …`) and returns the one to use.
+    pub fn revert_as(
+        &self,
+        rev: &str,
+        who: &Authorship,
+        message: impl FnOnce(&str) -> String,
+    ) -> Result<CommitOutcome, GitError> {
+        let oid = Oid::from_str(rev).map_err(|_| GitError::UnknownRevision(rev.to_string()))?;
+        let commit = self
+            .repo
+            .find_commit(oid)
+            .map_err(|_| GitError::UnknownRevision(rev.to_string()))?;
+        let head = self.repo.head()?.peel_to_commit()?;
+
+        let mut reverted = self.repo.revert_commit(&commit, &head, 0, None)?;
+        if reverted.has_conflicts() {
+            let mut paths = Vec::new();
+            for c in reverted.conflicts()? {
+                let c = c?;
+                if let Some(entry) = c.our.or(c.their).or(c.ancestor) {
+                    paths.push(String::from_utf8_lossy(&entry.path).to_string());
+                }
+            }
+            return Err(GitError::Conflict(paths));
+        }
+        let tree_id = reverted.write_tree_to(&self.repo)?;
+        if tree_id == head.tree_id() {
+            return Ok(CommitOutcome::NothingToCommit);
+        }
+        let tree = self.repo.find_tree(tree_id)?;
+
+        // Paths the revert changes; refuse if any has uncommitted edits.
+        let diff = self
+            .repo
+            .diff_tree_to_tree(Some(&head.tree()?), Some(&tree), None)?;
+        let mut touched: Vec<PathBuf> = Vec::new();
+        for delta in diff.deltas() {
+            for f in [delta.old_file(), delta.new_file()] {
+                if let Some(p) = f.path() {
+                    if !touched.iter().any(|t| t == p) {
+                        touched.push(p.to_path_buf());
+                    }
+                }
+            }
+        }
+        let dirty: Vec<String> = self
+            .status()?
+            .entries
+            .into_iter()
+            .filter(|e| touched.iter().any(|t| Path::new(&e.path) == t))
+            .map(|e| e.path)
+            .collect();
+        if !dirty.is_empty() {
+            return Err(GitError::Conflict(dirty));
+        }
+
+        let (author, committer) = self.signatures(who)?;
+        let message = message(&format!(
+            "Revert \"{}\"\n\nThis reverts commit {}.\n",
+            commit.summary().unwrap_or_default(),
+            oid
+        ));
+        let new_oid =
+            self.repo
+                .commit(Some("HEAD"), &author, &committer, &message, &tree, &[&head])?;
+
+        // Bring the work tree and index in line for just the touched paths.
+        let mut index = self.repo.index()?;
+        index.read(false)?;
+        for rel in &touched {
+            let abs = self.workdir.join(rel);
+            match tree.get_path(rel) {
+                Ok(entry) => {
+                    let blob = self.repo.find_blob(entry.id())?;
+                    if let Some(parent) = abs.parent() {
+                        std::fs::create_dir_all(parent)
+                            .map_err(|e| GitError::Other(e.to_string()))?;
+                    }
+                    std::fs::write(&abs, blob.content())
+                        .map_err(|e| GitError::Other(e.to_string()))?;
+                    index.add_path(rel)?;
+                }
+                Err(_) => {
+                    let _ = std::fs::remove_file(&abs);
+                    index.remove_path(rel)?;
+                }
+            }
+        }
+        index.write()?;
+
+        Ok(CommitOutcome::Created(new_oid.to_string()))
+    }
+
+    /// Work-tree paths `rev` changed against its first parent.
+    pub fn commit_paths(&self, rev: &str) -> Result<Vec<PathBuf>, GitError> {
+        let commit = self
+            .repo
+            .revparse_single(rev)
+            .and_then(|obj| obj.peel_to_commit())
+            .map_err(|_| GitError::UnknownRevision(rev.to_string()))?;
+        let parent = commit.parents().next().map(|p| p.tree()).transpose()?;
+        let diff = self
+            .repo
+            .diff_tree_to_tree(parent.as_ref(), Some(&commit.tree()?), None)?;
+        let mut paths: Vec<PathBuf> = Vec::new();
+        for delta in diff.deltas() {
+            for f in [delta.old_file(), delta.new_file()] {
+                if let Some(p) = f.path().map(|p| self.workdir.join(p)) {
+                    if !paths.contains(&p) {
+                        paths.push(p);
+                    }
+                }
+            }
+        }
+        Ok(paths)
+    }
+
+    /// `git diff --stat` of the work tree against the index for `paths`.
+    /// Empty when there are no unstaged changes.
+    pub fn diff_stat(&self, paths: &[&str]) -> Result<String, GitError> {
+        let mut opts = DiffOptions::new();
+        for p in paths {
+            opts.pathspec(self.relative(p)?);
+        }
+        let diff = self.repo.diff_index_to_workdir(None, Some(&mut opts))?;
+        let stats = diff.stats()?;
+        if stats.files_changed() == 0 {
+            return Ok(String::new());
+        }
+        let buf = stats.to_buf(git2::DiffStatsFormat::FULL, 80)?;
+        Ok(buf.as_str().unwrap_or_default().trim_end().to_string())
+    }
+
+    /// Every commit reachable from any ref, newest first (`git log --all`).
+    pub fn log_all(&self) -> Result<Vec<LogEntry>, GitError> {
+        let mut walk = self.repo.revwalk()?;
+        walk.set_sorting(Sort::TIME)?;
+        match walk.push_glob("*") {
+            Ok(()) => {}
+            // Fresh repo with no commits on any ref.
+            Err(e) if e.code() == ErrorCode::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        }
+        let _ = walk.push_head();
+
+        let mut entries = Vec::new();
+        for oid in walk {
+            let commit = self.repo.find_commit(oid?)?;
+            entries.push(LogEntry {
+                sha: commit.id().to_string(),
+                author_time: time_string(commit.author().when()),
+                subject: commit.summary().unwrap_or_default().to_string(),
+            });
+        }
+        Ok(entries)
+    }
+
+    /// Commits on HEAD that added, changed, moved or deleted a file whose
+    /// path `is_file` accepts, newest first. Renames are followed back like
+    /// `git log --follow`: once a commit moved a file onto a matching path,
+    /// older commits to the path it came from are included too. Merges are
+    /// compared with their first parent.
+    pub fn file_history(
+        &self,
+        is_file: impl Fn(&str) -> bool,
+    ) -> Result<Vec<FileRevision>, GitError> {
+        let mut walk = self.repo.revwalk()?;
+        walk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+        match walk.push_head() {
+            Ok(()) => {}
+            Err(e) if e.code() == ErrorCode::UnbornBranch || e.code() == ErrorCode::NotFound => {
+                return Ok(Vec::new())
+            }
+            Err(e) => return Err(e.into()),
+        }
+
+        let mut followed: HashSet<String> = HashSet::new();
+        let mut revisions = Vec::new();
+        for oid in walk {
+            let commit = self.repo.find_commit(oid?)?;
+            let parent_tree = match commit.parent(0) {
+                Ok(parent) => Some(parent.tree()?),
+                Err(_) => None,
+            };
+            let mut diff =
+                self.repo
+                    .diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), None)?;
+            diff.find_similar(Some(DiffFindOptions::new().renames(true)))?;
+
+            for (idx, delta) in diff.deltas().enumerate() {
+                let path = |file: git2::DiffFile<'_>| {
+                    (!file.id().is_zero())
+                        .then(|| file.path().map(|p| p.to_string_lossy().to_string()))
+                        .flatten()
+                };
+                let (old_path, new_path) = (path(delta.old_file()), path(delta.new_file()));
+                let tracked = |p: &Option<String>| {
+                    p.as_deref()
+                        .is_some_and(|p| is_file(p) || followed.contains(p))
+                };
+                if !tracked(&new_path) && !tracked(&old_path) {
+                    continue;
+                }
+                if let (Some(old), true) = (&old_path, tracked(&new_path)) {
+                    followed.insert(old.clone());
+                }
+
+                let (_, lines_added, lines_removed) = match Patch::from_diff(&diff, idx)? {
+                    Some(patch) => patch.line_stats()?,
+                    None => (0, 0, 0),
+                };
+                let blob = |file: git2::DiffFile<'_>| {
+                    let blob = self.repo.find_blob(file.id()).ok()?;
+                    Some(String::from_utf8_lossy(blob.content()).to_string())
+                };
+                revisions.push(FileRevision {
+                    sha: commit.id().to_string(),
+                    author_time: time_string(commit.author().when()),
+                    message: commit.message().unwrap_or_default().trim_end().to_string(),
+                    before: old_path.as_ref().and_then(|_| blob(delta.old_file())),
+                    after: new_path.as_ref().and_then(|_| blob(delta.new_file())),
+                    old_path,
+                    new_path,
+                    lines_added,
+                    lines_removed,
+                });
+            }
+        }
+        Ok(revisions)
+    }
+
+    /// Where commit `original` ended up after a pull rebased it: itself if
+    /// HEAD still contains it, else the recent commit on HEAD with the same
+    /// author time and subject. None if it isn't on the branch at all.
+    pub fn find_rebased(&self, original: &str) -> Option<String> {
+        let oid = Oid::from_str(original).ok()?;
+        let head = self.repo.head().ok()?.target()?;
+        if head == oid || self.repo.graph_descendant_of(head, oid).unwrap_or(false) {
+            return Some(original.to_string());
+        }
+        let commit = self.repo.find_commit(oid).ok()?;
+        let when = commit.author().when().seconds();
+        let subject = commit.summary()?.to_string();
+
+        let mut walk = self.repo.revwalk().ok()?;
+        walk.push(head).ok()?;
+        walk.take(500)
+            .filter_map(Result::ok)
+            .find(|candidate| {
+                self.repo.find_commit(*candidate).is_ok_and(|c| {
+                    c.author().when().seconds() == when && c.summary() == Some(subject.as_str())
+                })
+            })
+            .map(|oid| oid.to_string())
+    }
+
+    /// `git pull --rebase --autostash`. A conflicting rebase is aborted
+    /// before returning, leaving the repo as it was.
+    pub fn pull_rebase(&self) -> Result<(), GitError> {
+        let out = self.cli(&["pull", "--rebase", "--autostash"])?;
+        if out.status.success() {
+            return Ok(());
+        }
+        let msg = output_text(&out);
+        if self.rebase_in_progress() {
+            let conflicts = self.status().map(|s| s.conflicts).unwrap_or_default();
+            let _ = self.cli(&["rebase", "--abort"]);
+            log::warn!("Pull conflicted, rebase aborted: {}", msg);
+            return Err(GitError::Conflict(conflicts));
+        }
+        Err(remote_error(msg, GitError::Pull))
+    }
+
+    /// `git fetch --prune origin`: every remote branch, with the ones deleted
+    /// on the remote dropped.
+    pub fn fetch(&self) -> Result<(), GitError> {
+        let out = self.cli(&["fetch", "--prune", REMOTE])?;
+        if out.status.success() {
+            Ok(())
+        } else {
+            Err(remote_error(output_text(&out), GitError::Fetch))
+        }
+    }
+
+    pub fn push(&self) -> Result<PushOutcome, GitError> {
+        self.push_with(&["push", "--porcelain"])
+    }
+
+    /// Push local `branch` to the branch of the same name on origin. `force`
+    /// replaces it even if it has moved on, as long as it's still where the
+    /// last fetch saw it.
+    pub fn push_branch(&self, branch: &str, force: bool) -> Result<PushOutcome, GitError> {
+        let refspec = format!("refs/heads/{0}:refs/heads/{0}", branch);
+        let mut args = vec!["push", "--porcelain"];
+        if force {
+            args.push("--force-with-lease");
+        }
+        args.extend([REMOTE, refspec.as_str()]);
+        self.push_with(&args)
+    }
+
+    /// Delete `branch` on origin.
+    pub fn delete_remote_branch(&self, branch: &str) -> Result<(), GitError> {
+        let refspec = format!(":refs/heads/{}", branch);
+        self.push_with(&["push", "--porcelain", REMOTE, refspec.as_str()])
+            .map(|_| ())
+    }
+
+    fn push_with(&self, args: &[&str]) -> Result<PushOutcome, GitError> {
+        let out = self.cli(args)?;
+        let stdout = String::from_utf8_lossy(&out.stdout);
+        let flags = push_ref_flags(&stdout);
+        if let Some(line) = stdout.lines().find(|l| l.starts_with('!')) {
+            return Err(GitError::PushRejected(line[1..].trim().to_string()));
+        }
+        if !out.status.success() {
+            return Err(remote_error(output_text(&out), GitError::Push));
+        }
+        if !flags.is_empty() && flags.iter().all(|f| *f == '=') {
+            Ok(PushOutcome::UpToDate)
+        } else {
+            Ok(PushOutcome::Pushed)
+        }
+    }
+
+    fn rebase_in_progress(&self) -> bool {
+        matches!(
+            self.repo.state(),
+            RepositoryState::Rebase
+                | RepositoryState::RebaseInteractive
+                | RepositoryState::RebaseMerge
+                | RepositoryState::ApplyMailbox
+                | RepositoryState::ApplyMailboxOrRebase
+        )
+    }
+
+    fn cli(&self, args: &[&str]) -> Result<Output, GitError> {
+        let out = Command::new(crate::bin_paths::git())
+            .args(args)
+            .env("LC_ALL", "C")
+            .current_dir(&self.workdir)
+            .output()
+            .map_err(|e| GitError::Spawn(e.to_string()))?;
+        // The binary may have rewritten the index behind libgit2's cached copy
+        // (a stale conflicted index makes later resets refuse to run).
+        if let Ok(mut index) = self.repo.index() {
+            let _ = index.read(true);
+        }
+        Ok(out)
+    }
+
+    /// `p` relative to the work tree. Absolute paths are resolved through
+    /// symlinks first (macOS temp and iCloud paths are full of them).
+    fn relative(&self, p: &str) -> Result<PathBuf, GitError> {
+        let path = Path::new(p);
+        if path.is_relative() {
+            return Ok(path.to_path_buf());
+        }
+        if let Ok(rel) = path.strip_prefix(&self.workdir) {
+            return Ok(rel.to_path_buf());
+        }
+        let root = self
+            .workdir
+            .canonicalize()
+            .map_err(|e| GitError::Other(e.to_string()))?;
+        // The file itself may already be gone (unpublish), so resolve its parent.
+        let resolved = match (path.parent(), path.file_name()) {
+            (Some(parent), Some(name)) => parent
+                .canonicalize()
+                .map(|dir| dir.join(name))
+                .unwrap_or_else(|_| path.to_path_buf()),
+            _ => path.to_path_buf(),
+        };
+        resolved
+            .strip_prefix(&root)
+            .map(Path::to_path_buf)
+            .map_err(|_| GitError::Other(format!("{} is outside {}", p, root.display())))
+    }
+}
+
+/// A commit time as RFC 3339 in its own offset.
+fn time_string(when: git2::Time) -> String {
+    chrono::FixedOffset::east_opt(when.offset_minutes() * 60)
+        .and_then(|tz| {
+            chrono::DateTime::from_timestamp(when.seconds(), 0)
+                .map(|dt| dt.with_timezone(&tz).to_rfc3339())
+        })
+        .unwrap_or_default()
+}
+
+fn porcelain_code(s: Status) -> String {
+    if s.contains(Status::CONFLICTED) {
+        return "UU".into();
+    }
+    if s.contains(Status::WT_NEW) {
+        return "??".into();
+    }
+    let x = if s.contains(Status::INDEX_NEW) {
+        'A'
+    } else if s.contains(Status::INDEX_MODIFIED) {
+        'M'
+    } else if s.contains(Status::INDEX_DELETED) {
+        'D'
+    } else if s.contains(Status::INDEX_RENAMED) {
+        'R'
+    } else if s.contains(Status::INDEX_TYPECHANGE) {
+        'T'
+    } else {
+        ' '
+    };
+    let y = if s.contains(Status::WT_MODIFIED) {
+        'M'
+    } else if s.contains(Status::WT_DELETED) {
+        'D'
+    } else if s.contains(Status::WT_RENAMED) {
+        'R'
+    } else if s.contains(Status::WT_TYPECHANGE) {
+        'T'
+    } else {
+        ' '
+    };
+    format!("{}{}", x, y)
+}
+
+/// The per-ref flag characters from `git push --porcelain` output
+/// (`' '` fast-forward, `'+'` forced, `'-'` deleted, `'*'` new ref,
+/// `'!'` rejected, `'='` up to date).
+fn push_ref_flags(stdout: &str) -> Vec<char> {
+    stdout
+        .lines()
+        .filter(|l| l.contains("refs/") && l.chars().nth(1) == Some('\t'))
+        .filter_map(|l| l.chars().next())
+        .collect()
+}
+
+/// A remote URL as the https page for the repo. None for local paths and
+/// anything else that isn't a hosted remote.
+fn web_url(remote_url: &str) -> Option<String> {
+    let url = remote_url.trim().trim_end_matches('/');
+    let url = url.strip_suffix(".git").unwrap_or(url);
+    let (scheme, rest) = match url.split_once("://") {
+        Some((scheme, rest)) => (scheme, rest),
+        // scp-like `[user@]host:path`
+        None => {
+            let (host, path) = url.split_once(':')?;
+            if host.contains('/') {
+                return None;
+            }
+            return web_url(&format!("ssh://{}/{}", host, path));
+        }
+    };
+    let (host, path) = rest.split_once('/')?;
+    let host = host.rsplit('@').next()?;
+    let path = path.trim_start_matches('/');
+    if host.is_empty() || path.is_empty() {
+        return None;
+    }
+    match scheme {
+        "http" | "https" => Some(format!("{}://{}/{}", scheme, host, path)),
+        // The web side is https, without the ssh port.
+        "ssh" | "git" => Some(format!("https://{}/{}", host.split(':').next()?, path)),
+        _ => None,
+    }
+}
+
+/// How curl and ssh (as relayed by git) say the remote host couldn't be
+/// reached. Auth failures and missing repos read differently.
+const UNREACHABLE: &[&str] = &[
+    "Could not resolve host",
+    "Could not resolve hostname",
+    "Failed to connect to",
+    "Connection refused",
+    "Connection timed out",
+    "Operation timed out",
+    "Network is unreachable",
+    "No route to host",
+    "Connection reset by peer",
+    "Temporary failure in name resolution",
+];
+
+/// A failed pull, push or fetch: `Unreachable` if the network was the
+/// problem, else `other(msg)`.
+fn remote_error(msg: String, other: fn(String) -> GitError) -> GitError {
+    if UNREACHABLE.iter().any(|sign| msg.contains(sign)) {
+        GitError::Unreachable(msg)
+    } else {
+        other(msg)
+    }
+}
+
+fn output_text(out: &Output) -> String {
+    let stderr = String::from_utf8_lossy(&out.stderr).trim().to_string();
+    let stdout = String::from_utf8_lossy(&out.stdout).trim().to_string();
+    match (stdout.is_empty(), stderr.is_empty()) {
+        (true, _) => stderr,
+        (false, true) => stdout,
+        (false, false) => format!("{}\n{}", stdout, stderr),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static SCRATCH: AtomicUsize = AtomicUsize::new(0);
+
+    /// A fresh directory under the system temp dir.
+    pub(crate) fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "dispatch-git-{}-{}-{}",
+            name,
+            std::process::id(),
+            SCRATCH.fetch_add(1, Ordering::SeqCst)
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Write `body` to `rel` under `root`, creating folders on the way.
+    pub(crate) fn write(root: impl AsRef<Path>, rel: &str, body: &str) -> PathBuf {
+        let path = root.as_ref().join(rel);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, body).unwrap();
+        path
+    }
+
+    fn sh(dir: &Path, args: &[&str]) {
+        let out = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(
+            out.status.success(),
+            "git {:?}: {}",
+            args,
+            output_text(&out)
+        );
+    }
+
+    /// Nothing listens on port 1, so a remote here can't be reached.
+    pub(crate) const UNREACHABLE_URL: &str = "http://127.0.0.1:1/site.git";
+
+    /// Point the clone's origin at `url`.
+    pub(crate) fn set_origin(clone: &Path, url: &str) {
+        sh(clone, &["remote", "set-url", "origin", url]);
+    }
+
+    /// A bare "remote" with one commit on main, and a clone of it with an
+    /// identity configured. Returns (remote, clone).
+    pub(crate) fn remote_and_clone(name: &str) -> (PathBuf, PathBuf) {
+        let root = scratch(name);
+        let remote = root.join("remote.git");
+        let seed = root.join("seed");
+        sh(&root, &["init", "--bare", "-b", "main", "remote.git"]);
+        sh(&root, &["init", "-b", "main", "seed"]);
+        std::fs::write(seed.join("README.md"), "site\n").unwrap();
+        sh(
+            &seed,
+            &["-c", "user.name=t", "-c", "user.email=t@t", "add", "."],
+        );
+        sh(
+            &seed,
+            &[
+                "-c",
+                "user.name=t",
+                "-c",
+                "user.email=t@t",
+                "commit",
+                "-m",
+                "init",
+            ],
+        );
+        sh(&seed, &["push", remote.to_str().unwrap(), "main"]);
+        sh(&root, &["clone", "-q", "remote.git", "site"]);
+        let site = root.join("site");
+        sh(&site, &["config", "user.name", "Dispatch Test"]);
+        sh(&site, &["config", "user.email", "test@example.com"]);
+        (remote, site)
+    }
+
+    /// Another clone of `remote` that pushes one commit, so `remote` moves ahead.
+    pub(crate) fn push_from_elsewhere(remote: &Path, file: &str) {
+        let other = scratch("other");
+        sh(&other, &["clone", "-q", remote.to_str().unwrap(), "c"]);
+        let c = other.join("c");
+        std::fs::create_dir_all(c.join(file).parent().unwrap()).unwrap();
+        std::fs::write(c.join(file), "theirs\n").unwrap();
+        sh(&c, &["add", "."]);
+        sh(
+            &c,
+            &[
+                "-c",
+                "user.name=o",
+                "-c",
+                "user.email=o@o",
+                "commit",
+                "-m",
+                "other",
+            ],
+        );
+        sh(&c, &["push", "-q"]);
+    }
+
+    #[test]
+    fn commit_push_and_nothing_to_commit() {
+        let (_remote, site) = remote_and_clone("commit");
+        let repo = Repo::open(&site).unwrap();
+        assert_eq!(repo.branch().as_deref(), Some("main"));
+
+        std::fs::create_dir_all(site.join("content/blog/2026")).unwrap();
+        std::fs::write(site.join("content/blog/2026/a.md"), "# a\n").unwrap();
+        let status = repo.status().unwrap();
+        assert_eq!(status.entries.len(), 1);
+        assert_eq!(status.entries[0].code, "??");
+
+        repo.add(&[site.join("content/blog/2026/a.md").to_str().unwrap()])
+            .unwrap();
+        let sha = match repo.commit("Publish: a").unwrap() {
+            CommitOutcome::Created(sha) => sha,
+            other => panic!("expected a commit, got {:?}", other),
+        };
+        assert_eq!(repo.head().as_deref(), Some(sha.as_str()));
+        assert_eq!(
+            repo.commit("Publish: a").unwrap(),
+            CommitOutcome::NothingToCommit
+        );
+        assert!(repo.status().unwrap().entries.is_empty());
+
+        assert_eq!(repo.push().unwrap(), PushOutcome::Pushed);
+        assert_eq!(repo.push().unwrap(), PushOutcome::UpToDate);
+
+        let log = repo.log_all().unwrap();
+        assert_eq!(log[0].subject, "Publish: a");
+        assert!(chrono::DateTime::parse_from_rfc3339(&log[0].author_time).is_ok());
+    }
+
+    #[test]
+    fn push_is_rejected_until_pulled() {
+        let (remote, site) = remote_and_clone("reject");
+        let repo = Repo::open(&site).unwrap();
+        push_from_elsewhere(&remote, "other.md");
+
+        std::fs::write(site.join("mine.md"), "mine\n").unwrap();
+        repo.add(&["mine.md"]).unwrap();
+        repo.commit("Publish: mine").unwrap();
+        let local = repo.head().unwrap();
+        assert_eq!(repo.find_rebased(&local), Some(local.clone()));
+
+        assert!(matches!(repo.push(), Err(GitError::PushRejected(_))));
+        repo.pull_rebase().unwrap();
+        assert!(site.join("other.md").exists());
+        assert_eq!(repo.push().unwrap(), PushOutcome::Pushed);
+
+        // The rebase gave the commit a new SHA; it can still be followed.
+        let pushed = repo.head().unwrap();
+        assert_ne!(pushed, local);
+        assert_eq!(repo.find_rebased(&local), Some(pushed));
+    }
+
+    #[test]
+    fn only_an_unreachable_remote_is_worth_retrying() {
+        let (_remote, site) = remote_and_clone("unreachable");
+        let repo = Repo::open(&site).unwrap();
+
+        set_origin(&site, UNREACHABLE_URL);
+        let err = repo.pull_rebase().unwrap_err();
+        assert!(matches!(err, GitError::Unreachable(_)), "{:?}", err);
+        assert!(err.is_transient());
+
+        // A remote that isn't there won't appear by waiting.
+        let missing = site.with_file_name("missing.git");
+        set_origin(&site, missing.to_str().unwrap());
+        let err = repo.pull_rebase().unwrap_err();
+        assert!(matches!(err, GitError::Pull(_)), "{:?}", err);
+        assert!(!err.is_transient());
+        assert!(!GitError::Conflict(vec!["a.md".into()]).is_transient());
+    }
+
+    #[test]
+    fn conflicting_pull_is_aborted() {
+        let (remote, site) = remote_and_clone("conflict");
+        let repo = Repo::open(&site).unwrap();
+        push_from_elsewhere(&remote, "same.md");
+
+        std::fs::write(site.join("same.md"), "mine\n").unwrap();
+        repo.add(&["same.md"]).unwrap();
+        let before = repo.head();
+        repo.commit("Publish: same").unwrap();
+        let mine = repo.head();
+        assert_ne!(before, mine);
+
+        assert!(matches!(repo.pull_rebase(), Err(GitError::Conflict(_))));
+        assert!(!repo.rebase_in_progress());
+        assert_eq!(repo.head(), mine);
+    }
+
+    #[test]
+    fn revert_restores_previous_contents() {
+        let (_remote, site) = remote_and_clone("revert");
+        let repo = Repo::open(&site).unwrap();
+        let post = site.join("post.md");
+
+        std::fs::write(&post, "v1\n").unwrap();
+        repo.add(&["post.md"]).unwrap();
+        let CommitOutcome::Created(first) = repo.commit("Publish: post").unwrap() else {
+            panic!("no commit")
+        };
+        std::fs::write(&post, "v2\n").unwrap();
+        repo.add(&["post.md"]).unwrap();
+        let CommitOutcome::Created(second) = repo.commit("Publish: post").unwrap() else {
+            panic!("no commit")
+        };
+
+        // Reverting the republish puts v1 back.
+        assert!(matches!(
+            repo.revert(&second),
+            Ok(CommitOutcome::Created(_))
+        ));
+        assert_eq!(std::fs::read_to_string(&post).unwrap(), "v1\n");
+        assert!(repo.status().unwrap().entries.is_empty());
+        assert_eq!(
+            repo.revert(&second).unwrap(),
+            CommitOutcome::NothingToCommit
+        );
+
+        // Reverting the first publish removes the file.
+        repo.revert(&first).unwrap();
+        assert!(!post.exists());
+        assert!(repo.status().unwrap().entries.is_empty());
+
+        let log = repo.log_all().unwrap();
+        assert_eq!(log[0].subject, "Revert \"Publish: post\"");
+        assert!(matches!(
+            repo.revert("0000000000000000000000000000000000000000"),
+            Err(GitError::UnknownRevision(_))
+        ));
+    }
+
+    #[test]
+    fn reset_soft_and_unstage_undo_a_commit() {
+        let (_remote, site) = remote_and_clone("reset");
+        let repo = Repo::open(&site).unwrap();
+        let start = repo.head().unwrap();
+
+        std::fs::write(site.join("x.md"), "x\n").unwrap();
+        repo.add(&["x.md"]).unwrap();
+        repo.commit("Publish: x").unwrap();
+
+        repo.reset_soft(&start).unwrap();
+        assert_eq!(repo.head().as_deref(), Some(start.as_str()));
+        assert_eq!(repo.status().unwrap().entries[0].code, "A ");
+        repo.unstage(&["x.md"]).unwrap();
+        assert_eq!(repo.status().unwrap().entries[0].code, "??");
+    }
+
+    #[test]
+    fn push_flags_are_parsed_from_porcelain() {
+        let out = "To ../remote.git\n=\trefs/heads/main:refs/heads/main\t[up to date]\nDone\n";
+        assert_eq!(push_ref_flags(out), vec!['=']);
+        let out = "To x\n \trefs/heads/main:refs/heads/main\tabc..def\nDone\n";
+        assert_eq!(push_ref_flags(out), vec![' ']);
+    }
+
+    #[test]
+    fn branch_commit_leaves_the_checkout_alone_until_fast_forwarded() {
+        let (_remote, site) = remote_and_clone("branch");
+        let repo = Repo::open(&site).unwrap();
+        let start = repo.head().unwrap();
+
+        let sha = match repo
+            .commit_file_to_branch(
+                "dispatch/a",
+                "origin/main",
+                "content/a.md",
+                b"# a\n",
+                "a",
+                &Authorship::default(),
+            )
+            .unwrap()
+        {
+            CommitOutcome::Created(sha) => sha,
+            other => panic!("expected a commit, got {:?}", other),
+        };
+        assert_eq!(repo.head().as_deref(), Some(start.as_str()));
+        assert!(!site.join("content/a.md").exists());
+        assert!(repo.status().unwrap().entries.is_empty());
+        assert!(repo.contains("dispatch/a", "main"));
+        assert_eq!(
+            repo.commit_file_to_branch(
+                "dispatch/a",
+                "dispatch/a",
+                "content/a.md",
+                b"# a\n",
+                "a",
+                &Authorship::default()
+            )
+            .unwrap(),
+            CommitOutcome::NothingToCommit
+        );
+
+        assert_eq!(
+            repo.push_branch("dispatch/a", false).unwrap(),
+            PushOutcome::Pushed
+        );
+        repo.fetch().unwrap();
+        assert_eq!(repo.resolve("origin/dispatch/a"), Some(sha.clone()));
+
+        repo.fast_forward("dispatch/a").unwrap();
+        assert_eq!(repo.head(), Some(sha));
+        assert_eq!(
+            std::fs::read_to_string(site.join("content/a.md")).unwrap(),
+            "# a\n"
+        );
+        assert!(repo.status().unwrap().entries.is_empty());
+
+        repo.delete_branch("dispatch/a").unwrap();
+        repo.delete_remote_branch("dispatch/a").unwrap();
+        repo.fetch().unwrap();
+        assert_eq!(repo.resolve("origin/dispatch/a"), None);
+    }
+
+    #[test]
+    fn fast_forward_refuses_a_diverged_branch() {
+        let (_remote, site) = remote_and_clone("diverged");
+        let repo = Repo::open(&site).unwrap();
+        repo.commit_file_to_branch(
+            "dispatch/b",
+            "main",
+            "b.md",
+            b"b\n",
+            "b",
+            &Authorship::default(),
+        )
+        .unwrap();
+        std::fs::write(site.join("c.md"), "c\n").unwrap();
+        repo.add(&["c.md"]).unwrap();
+        repo.commit("c").unwrap();
+        assert!(matches!(
+            repo.fast_forward("dispatch/b"),
+            Err(GitError::NotFastForward(_))
+        ));
+    }
+
+    #[test]
+    fn remote_urls_become_web_urls() {
+        for (remote, web) in [
+            (
+                "git@github.com:ej/site.git",
+                Some("https://github.com/ej/site"),
+            ),
+            (
+                "ssh://git@gitlab.com:2222/ej/site.git",
+                Some("https://gitlab.com/ej/site"),
+            ),
+            (
+                "https://tok@github.com/ej/site/",
+                Some("https://github.com/ej/site"),
+            ),
+            (
+                "http://git.local:3000/ej/site",
+                Some("http://git.local:3000/ej/site"),
+            ),
+            ("/tmp/remote.git", None),
+            ("file:///tmp/remote.git", None),
+        ] {
+            assert_eq!(web_url(remote).as_deref(), web, "{}", remote);
+        }
+    }
+}
@@ -0,0 +1,238 @@
+//! Per-target shell hooks around a publish.
+//!
+//! `pre_publish` commands run before anything in the repo changes, and the
+//! first one to exit non-zero aborts the publish. `post_publish` commands run
+//! once the push has landed; they all run, and none of them can fail the
+//! publish. Every hook runs through `sh -c` in the target's repo with
+//! DISPATCH_* variables describing the post.
+
+use crate::config::PublishTarget;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Instant;
+
+/// Keep the last this-many bytes of each stream — enough for a link
+/// checker's report, not a whole build log.
+const MAX_OUTPUT: usize = 16 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookStage {
+    PrePublish,
+    PostPublish,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HookRun {
+    pub stage: HookStage,
+    pub command: String,
+    /// None when the command couldn't start or was killed by a signal.
+    pub exit_code: Option<i32>,
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+    pub duration_ms: u64,
+}
+
+/// What the hooks are told about the publish.
+#[derive(Debug, Clone, Copy)]
+pub struct HookEnv<'a> {
+    pub target_id: &'a str,
+    pub slug: &'a str,
+    pub url: &'a str,
+    pub source_path: &'a str,
+    pub dest_path: &'a str,
+    /// Only known to post-publish hooks, and only if something was committed.
+    pub commit_sha: Option<&'a str>,
+}
+
+/// Run the target's pre-publish hooks. Err carries the failing hook's output.
+pub fn pre_publish(target: &PublishTarget, env: &HookEnv) -> Result<Vec<HookRun>, String> {
+    let runs = run(
+        HookStage::PrePublish,
+        &target.hooks.pre_publish,
+        &target.repo_path,
+        env,
+    );
+    match runs.last() {
+        Some(failed) if !failed.success => Err(failure_message(failed)),
+        _ => Ok(runs),
+    }
+}
+
+/// Run the target's post-publish hooks. Failures are logged and returned,
+/// never raised — the post is already live.
+pub fn post_publish(target: &PublishTarget, env: &HookEnv) -> Vec<HookRun> {
+    let runs = run(
+        HookStage::PostPublish,
+        &target.hooks.post_publish,
+        &target.repo_path,
+        env,
+    );
+    for failed in runs.iter().filter(|r| !r.success) {
+        log::warn!("{}", failure_message(failed));
+    }
+    runs
+}
+
+fn run(stage: HookStage, commands: &[String], cwd: &str, env: &HookEnv) -> Vec<HookRun> {
+    let mut runs = Vec::new();
+    for command in commands.iter().filter(|c| !c.trim().is_empty()) {
+        log::warn!("Running {:?} hook: {}", stage, command);
+        let run = run_one(stage, command, cwd, env);
+        let failed = !run.success;
+        runs.push(run);
+        if failed && stage == HookStage::PrePublish {
+            break;
+        }
+    }
+    runs
+}
+
+fn run_one(stage: HookStage, command: &str, cwd: &str, env: &HookEnv) -> HookRun {
+    let started = Instant::now();
+    let output = Command::new("/bin/sh")
+        .args(["-c", command])
+        .current_dir(cwd)
+        .env("PATH", hook_path())
+        .env(
+            "DISPATCH_HOOK",
+            match stage {
+                HookStage::PrePublish => "pre_publish",
+                HookStage::PostPublish => "post_publish",
+            },
+        )
+        .env("DISPATCH_TARGET_ID", env.target_id)
+        .env("DISPATCH_SLUG", env.slug)
+        .env("DISPATCH_URL", env.url)
+        .env("DISPATCH_SOURCE_PATH", env.source_path)
+        .env("DISPATCH_DEST_PATH", env.dest_path)
+        .env("DISPATCH_COMMIT_SHA", env.commit_sha.unwrap_or_default())
+        .output();
+    let duration_ms = started.elapsed().as_millis() as u64;
+    match output {
+        Ok(out) => HookRun {
+            stage,
+            command: command.to_string(),
+            exit_code: out.status.code(),
+            success: out.status.success(),
+            stdout: tail(&String::from_utf8_lossy(&out.stdout)),
+            stderr: tail(&String::from_utf8_lossy(&out.stderr)),
+            duration_ms,
+        },
+        Err(e) => HookRun {
+            stage,
+            command: command.to_string(),
+            exit_code: None,
+            success: false,
+            stdout: String::new(),
+            stderr: format!("Failed to start hook: {}", e),
+            duration_ms,
+        },
+    }
+}
+
+/// The app's PATH with the resolved node and git directories in front, so
+/// `npm run ...` works from a GUI launch the same as from a terminal.
+fn hook_path() -> String {
+    let mut dirs: Vec<PathBuf> = [crate::bin_paths::node(), crate::bin_paths::git()]
+        .iter()
+        .filter_map(|bin| bin.parent().map(Path::to_path_buf))
+        .collect();
+    if let Some(path) = std::env::var_os("PATH") {
+        dirs.extend(std::env::split_paths(&path));
+    }
+    std::env::join_paths(dirs)
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+fn tail(s: &str) -> String {
+    if s.len() <= MAX_OUTPUT {
+        return s.to_string();
+    }
+    let mut start = s.len() - MAX_OUTPUT;
+    while !s.is_char_boundary(start) {
+        start += 1;
+    }
+    format!("…{}", &s[start..])
+}
+
+fn failure_message(run: &HookRun) -> String {
+    let status = run
+        .exit_code
+        .map(|c| format!("exit {}", c))
+        .unwrap_or_else(|| "did not run".into());
+    let output = if run.stderr.trim().is_empty() {
+        run.stdout.trim()
+    } else {
+        run.stderr.trim()
+    };
+    let stage = match run.stage {
+        HookStage::PrePublish => "Pre-publish",
+        HookStage::PostPublish => "Post-publish",
+    };
+    format!(
+        "{} hook `{}` failed ({}): {}",
+        stage, run.command, status, output
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+
+    fn target(pre: &[&str], post: &[&str]) -> PublishTarget {
+        let mut target = AppConfig::default().publish_targets[0].clone();
+        target.repo_path = std::env::temp_dir().to_string_lossy().to_string();
+        target.hooks.pre_publish = pre.iter().map(|s| s.to_string()).collect();
+        target.hooks.post_publish = post.iter().map(|s| s.to_string()).collect();
+        target
+    }
+
+    fn env() -> HookEnv<'static> {
+        HookEnv {
+            target_id: "website",
+            slug: "hello",
+            url: "https://example.com/blog/2026/hello",
+            source_path: "/vault/blog/hello.md",
+            dest_path: "/site/content/blog/2026/hello.md",
+            commit_sha: None,
+        }
+    }
+
+    #[test]
+    fn pre_hooks_see_the_post_and_stop_at_the_first_failure() {
+        let ok = target(&["echo \"$DISPATCH_SLUG $DISPATCH_URL\""], &[]);
+        let runs = pre_publish(&ok, &env()).unwrap();
+        assert_eq!(
+            runs[0].stdout.trim(),
+            "hello https://example.com/blog/2026/hello"
+        );
+
+        let failing = target(&["echo broken links >&2; exit 3", "touch never-runs"], &[]);
+        let err = pre_publish(&failing, &env()).unwrap_err();
+        assert!(
+            err.contains("exit 3") && err.contains("broken links"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn post_hooks_all_run_and_get_the_commit() {
+        let t = target(&[], &["exit 1", "echo $DISPATCH_COMMIT_SHA"]);
+        let runs = post_publish(
+            &t,
+            &HookEnv {
+                commit_sha: Some("abc123"),
+                ..env()
+            },
+        );
+        assert_eq!(runs.len(), 2);
+        assert!(!runs[0].success);
+        assert_eq!(runs[1].stdout.trim(), "abc123");
+    }
+}
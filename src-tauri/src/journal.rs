@@ -1,4 +1,4 @@
-use chrono::{Datelike, Local, NaiveDate, Timelike, Utc};
+use chrono::{DateTime, Datelike, Local, NaiveDate, Timelike, Utc};
 use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
@@ -21,6 +21,12 @@ pub struct JournalEntry {
     pub url: Option<String>,
     pub target_id: Option<String>,
     pub visibility: String, // "public", "unlisted", "protected"
+    /// Website repo commit this event produced (publish/republish/revert/unpublish).
+    pub commit_sha: Option<String>,
+    /// Push to page-live time, on "live" and "deploy_failed" events.
+    pub time_to_live_ms: Option<i64>,
+    /// How an "unpublish" took the post down (`move_to_drafts`, `archive`, ...).
+    pub unpublish_strategy: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -176,7 +182,10 @@ fn init_schema(conn: &Connection) -> Result<(), String> {
             content_type TEXT NOT NULL DEFAULT 'post',
             url         TEXT,
             target_id   TEXT,
-            visibility  TEXT NOT NULL DEFAULT 'public'
+            visibility  TEXT NOT NULL DEFAULT 'public',
+            commit_sha  TEXT,
+            time_to_live_ms INTEGER,
+            unpublish_strategy TEXT
         );
 
         CREATE INDEX IF NOT EXISTS idx_events_local_date ON events(local_date);
@@ -184,7 +193,32 @@ fn init_schema(conn: &Connection) -> Result<(), String> {
         CREATE INDEX IF NOT EXISTS idx_events_slug ON events(slug);
         ",
     )
-    .map_err(|e| format!("Failed to create journal schema: {}", e))
+    .map_err(|e| format!("Failed to create journal schema: {}", e))?;
+
+    // Journals created before these were recorded lack the columns.
+    add_column_if_missing(conn, "commit_sha", "TEXT")?;
+    add_column_if_missing(conn, "time_to_live_ms", "INTEGER")?;
+    add_column_if_missing(conn, "unpublish_strategy", "TEXT")?;
+    Ok(())
+}
+
+fn add_column_if_missing(conn: &Connection, column: &str, sql_type: &str) -> Result<(), String> {
+    let exists: bool = conn
+        .query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('events') WHERE name = ?1",
+            params![column],
+            |r| r.get::<_, i64>(0),
+        )
+        .map(|n| n > 0)
+        .map_err(|e| format!("Failed to inspect journal schema: {}", e))?;
+    if !exists {
+        conn.execute(
+            &format!("ALTER TABLE events ADD COLUMN {} {}", column, sql_type),
+            [],
+        )
+        .map_err(|e| format!("Failed to migrate journal schema: {}", e))?;
+    }
+    Ok(())
 }
 
 // ---------------------------------------------------------------------------
@@ -201,9 +235,78 @@ pub struct EventRecord<'a> {
     pub url: Option<&'a str>,
     pub target_id: Option<&'a str>,
     pub visibility: &'a str,
+    pub commit_sha: Option<&'a str>,
+    pub unpublish_strategy: Option<&'a str>,
 }
 
 pub fn record_event(rec: EventRecord<'_>) -> Result<i64, String> {
+    insert_event(rec)
+}
+
+/// A post-push deploy check.
+pub struct DeployRecord<'a> {
+    /// "live" or "deploy_failed".
+    pub event: &'a str,
+    pub slug: &'a str,
+    pub title: Option<&'a str>,
+    pub url: &'a str,
+    pub target_id: &'a str,
+    pub commit_sha: Option<&'a str>,
+    /// How long the site took (or how long we waited).
+    pub time_to_live_ms: u64,
+}
+
+pub fn record_deploy(rec: DeployRecord<'_>) -> Result<i64, String> {
+    let db = get_db()?.lock().map_err(|e| format!("DB lock: {}", e))?;
+    insert_deploy(&db, &rec, Local::now()).map_err(|e| format!("Failed to record event: {}", e))
+}
+
+/// The deploy row takes word count, tags, content type and visibility from
+/// the publish it checks, so stats over live posts see the real post. With
+/// no matching publish on record, those columns keep their defaults.
+fn insert_deploy(
+    db: &Connection,
+    rec: &DeployRecord<'_>,
+    local: DateTime<Local>,
+) -> rusqlite::Result<i64> {
+    let timestamp = local.with_timezone(&Utc).to_rfc3339();
+    let local_date = local.format("%Y-%m-%d").to_string();
+    let local_hour = local.hour() as i32;
+    let day_of_week = local.weekday().num_days_from_monday() as i32;
+    let params = params![
+        timestamp,
+        local_date,
+        local_hour,
+        day_of_week,
+        rec.event,
+        rec.slug,
+        rec.title,
+        rec.url,
+        rec.target_id,
+        rec.commit_sha,
+        rec.time_to_live_ms as i64,
+    ];
+
+    let copied = db.execute(
+        "INSERT INTO events (timestamp, local_date, local_hour, day_of_week, event, slug, title, word_count, tags, content_type, url, target_id, visibility, commit_sha, time_to_live_ms)
+         SELECT ?1, ?2, ?3, ?4, ?5, ?6, COALESCE(?7, title), word_count, tags, content_type, ?8, ?9, visibility, ?10, ?11
+         FROM events
+         WHERE event IN ('publish', 'republish') AND slug = ?6 AND target_id = ?9
+           AND (?10 IS NULL OR commit_sha = ?10)
+         ORDER BY id DESC LIMIT 1",
+        params,
+    )?;
+    if copied == 0 {
+        db.execute(
+            "INSERT INTO events (timestamp, local_date, local_hour, day_of_week, event, slug, title, url, target_id, commit_sha, time_to_live_ms)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params,
+        )?;
+    }
+    Ok(db.last_insert_rowid())
+}
+
+fn insert_event(rec: EventRecord<'_>) -> Result<i64, String> {
     let now = Utc::now();
     let local = Local::now();
     let timestamp = now.to_rfc3339();
@@ -214,8 +317,8 @@ pub fn record_event(rec: EventRecord<'_>) -> Result<i64, String> {
 
     let db = get_db()?.lock().map_err(|e| format!("DB lock: {}", e))?;
     db.execute(
-        "INSERT INTO events (timestamp, local_date, local_hour, day_of_week, event, slug, title, word_count, tags, content_type, url, target_id, visibility)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+        "INSERT INTO events (timestamp, local_date, local_hour, day_of_week, event, slug, title, word_count, tags, content_type, url, target_id, visibility, commit_sha, unpublish_strategy)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
         params![
             timestamp,
             local_date,
@@ -230,6 +333,8 @@ pub fn record_event(rec: EventRecord<'_>) -> Result<i64, String> {
             rec.url,
             rec.target_id,
             rec.visibility,
+            rec.commit_sha,
+            rec.unpublish_strategy,
         ],
     )
     .map_err(|e| format!("Failed to record event: {}", e))?;
@@ -241,31 +346,89 @@ pub fn record_event(rec: EventRecord<'_>) -> Result<i64, String> {
 // Read entries
 // ---------------------------------------------------------------------------
 
+const ENTRY_COLUMNS: &str = "id, timestamp, event, slug, title, word_count, tags, content_type, url, target_id, visibility, commit_sha, time_to_live_ms, unpublish_strategy";
+
+fn entry_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<JournalEntry> {
+    Ok(JournalEntry {
+        id: row.get(0)?,
+        timestamp: row.get(1)?,
+        event: row.get(2)?,
+        slug: row.get(3)?,
+        title: row.get(4)?,
+        word_count: row.get(5)?,
+        tags: row.get(6)?,
+        content_type: row.get(7)?,
+        url: row.get(8)?,
+        target_id: row.get(9)?,
+        visibility: row.get(10)?,
+        commit_sha: row.get(11)?,
+        time_to_live_ms: row.get(12)?,
+        unpublish_strategy: row.get(13)?,
+    })
+}
+
 pub fn get_recent_entries(limit: usize) -> Result<Vec<JournalEntry>, String> {
     let db = get_db()?.lock().map_err(|e| format!("DB lock: {}", e))?;
     let mut stmt = db
-        .prepare(
-            "SELECT id, timestamp, event, slug, title, word_count, tags, content_type, url, target_id, visibility
-             FROM events ORDER BY id DESC LIMIT ?1",
-        )
+        .prepare(&format!(
+            "SELECT {} FROM events ORDER BY id DESC LIMIT ?1",
+            ENTRY_COLUMNS
+        ))
         .map_err(|e| format!("Query error: {}", e))?;
 
     let rows = stmt
-        .query_map(params![limit as i64], |row| {
-            Ok(JournalEntry {
-                id: row.get(0)?,
-                timestamp: row.get(1)?,
-                event: row.get(2)?,
-                slug: row.get(3)?,
-                title: row.get(4)?,
-                word_count: row.get(5)?,
-                tags: row.get(6)?,
-                content_type: row.get(7)?,
-                url: row.get(8)?,
-                target_id: row.get(9)?,
-                visibility: row.get(10)?,
-            })
-        })
+        .query_map(params![limit as i64], entry_from_row)
+        .map_err(|e| format!("Query error: {}", e))?;
+
+    let mut entries = Vec::new();
+    for row in rows {
+        entries.push(row.map_err(|e| format!("Row error: {}", e))?);
+    }
+    Ok(entries)
+}
+
+pub fn get_entry(id: i64) -> Result<JournalEntry, String> {
+    let db = get_db()?.lock().map_err(|e| format!("DB lock: {}", e))?;
+    db.query_row(
+        &format!("SELECT {} FROM events WHERE id = ?1", ENTRY_COLUMNS),
+        params![id],
+        entry_from_row,
+    )
+    .map_err(|e| format!("Journal event {} not found: {}", id, e))
+}
+
+/// Every publish/republish event produced by `commit_sha` — more than one
+/// when the commit came from a batch publish.
+pub fn entries_for_commit(commit_sha: &str) -> Result<Vec<JournalEntry>, String> {
+    let db = get_db()?.lock().map_err(|e| format!("DB lock: {}", e))?;
+    let mut stmt = db
+        .prepare(&format!(
+            "SELECT {} FROM events WHERE commit_sha = ?1 AND event IN ('publish', 'republish') ORDER BY id",
+            ENTRY_COLUMNS
+        ))
+        .map_err(|e| format!("Query error: {}", e))?;
+    let rows = stmt
+        .query_map(params![commit_sha], entry_from_row)
+        .map_err(|e| format!("Query error: {}", e))?;
+
+    let mut entries = Vec::new();
+    for row in rows {
+        entries.push(row.map_err(|e| format!("Row error: {}", e))?);
+    }
+    Ok(entries)
+}
+
+/// Every event recorded for `slug`, oldest first.
+pub fn entries_for_slug(slug: &str) -> Result<Vec<JournalEntry>, String> {
+    let db = get_db()?.lock().map_err(|e| format!("DB lock: {}", e))?;
+    let mut stmt = db
+        .prepare(&format!(
+            "SELECT {} FROM events WHERE slug = ?1 ORDER BY id",
+            ENTRY_COLUMNS
+        ))
+        .map_err(|e| format!("Query error: {}", e))?;
+    let rows = stmt
+        .query_map(params![slug], entry_from_row)
         .map_err(|e| format!("Query error: {}", e))?;
 
     let mut entries = Vec::new();
@@ -1382,7 +1545,11 @@ fn format_words_friendly(words: i64) -> String {
 // Backfill from git history (Phase 6 — called once if DB is empty)
 // ---------------------------------------------------------------------------
 
-pub fn backfill_from_git(repo_path: &str, domain: &str) -> Result<u32, String> {
+pub fn backfill_from_git(
+    repo_path: &str,
+    domain: &str,
+    commit: &crate::config::CommitConfig,
+) -> Result<u32, String> {
     // Most posts are added via `yarn blog:process` without a dedicated
     // "Publish:" commit, so parsing git log alone yields almost nothing.
     // The processed manifest is the authoritative list of what's actually
@@ -1390,23 +1557,31 @@ pub fn backfill_from_git(repo_path: &str, domain: &str) -> Result<u32, String> {
     // the events table.
     let mut inserted = backfill_from_manifest(repo_path, domain).unwrap_or(0);
 
-    let db = get_db()?.lock().map_err(|e| format!("DB lock: {}", e))?;
-
-    // Also pick up any explicit "Publish: SLUG" commits not represented in
-    // the manifest (e.g. unlisted posts processed outside the main flow).
-    let output = std::process::Command::new(crate::bin_paths::git())
-        .args(["log", "--oneline", "--format=%aI %s", "--all"])
-        .current_dir(repo_path)
-        .output()
+    // Also pick up any publish commits not represented in the manifest
+    // (e.g. unlisted posts processed outside the main flow), as the
+    // target's templates write them.
+    let log = crate::git::Repo::open(repo_path)
+        .and_then(|repo| repo.log_all())
         .map_err(|e| format!("git log failed: {}", e))?;
+    let db = get_db()?.lock().map_err(|e| format!("DB lock: {}", e))?;
+    inserted += backfill_commits(&db, &log, domain, commit);
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-
-    for line in stdout.lines().rev() {
-        if let Some(cap) = crate::patterns::GIT_PUBLISH_LOG.captures(line) {
-            let ts = &cap[1];
-            let slug = cap[2].trim();
+    Ok(inserted)
+}
 
+/// Publish events for the commits in `log` (newest first) whose subjects
+/// name slugs the journal has no publish for, each with its commit SHA so
+/// it can be reverted.
+fn backfill_commits(
+    db: &Connection,
+    log: &[crate::git::LogEntry],
+    domain: &str,
+    commit: &crate::config::CommitConfig,
+) -> u32 {
+    let mut inserted = 0;
+    for entry in log.iter().rev() {
+        let ts = entry.author_time.as_str();
+        for slug in crate::commit_message::published_slugs(commit, &entry.subject) {
             let exists: i64 = db
                 .query_row(
                     "SELECT COUNT(*) FROM events WHERE slug = ?1 AND event = 'publish'",
@@ -1426,16 +1601,16 @@ pub fn backfill_from_git(repo_path: &str, domain: &str) -> Result<u32, String> {
                 let url = format!("{}/blog/{}/{}", domain, local.year(), slug);
 
                 let _ = db.execute(
-                    "INSERT INTO events (timestamp, local_date, local_hour, day_of_week, event, slug, title, word_count, tags, content_type, url, target_id, visibility)
-                     VALUES (?1, ?2, ?3, ?4, 'publish', ?5, NULL, 0, '', 'post', ?6, NULL, 'public')",
-                    params![ts, local_date, local_hour, day_of_week, slug, url],
+                    "INSERT INTO events (timestamp, local_date, local_hour, day_of_week, event, slug, title, word_count, tags, content_type, url, target_id, visibility, commit_sha)
+                     VALUES (?1, ?2, ?3, ?4, 'publish', ?5, NULL, 0, '', 'post', ?6, NULL, 'public', ?7)",
+                    params![ts, local_date, local_hour, day_of_week, slug, url, entry.sha],
                 );
                 inserted += 1;
             }
         }
     }
 
-    Ok(inserted)
+    inserted
 }
 
 /// Backfill publish events from website2's processed manifest. This is the
@@ -1518,3 +1693,129 @@ fn backfill_from_manifest(repo_path: &str, domain: &str) -> Result<u32, String>
 
     Ok(inserted)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn init_schema_adds_missing_columns_to_old_journals() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp TEXT NOT NULL, local_date TEXT NOT NULL,
+                local_hour INTEGER NOT NULL, day_of_week INTEGER NOT NULL,
+                event TEXT NOT NULL, slug TEXT NOT NULL, title TEXT,
+                word_count INTEGER NOT NULL DEFAULT 0, tags TEXT NOT NULL DEFAULT '',
+                content_type TEXT NOT NULL DEFAULT 'post', url TEXT, target_id TEXT,
+                visibility TEXT NOT NULL DEFAULT 'public'
+            );",
+        )
+        .unwrap();
+
+        init_schema(&conn).unwrap();
+        // Running it again on an already-migrated DB is a no-op.
+        init_schema(&conn).unwrap();
+
+        let n: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('events')
+                 WHERE name IN ('commit_sha', 'time_to_live_ms', 'unpublish_strategy')",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(n, 3);
+    }
+
+    #[test]
+    fn backfilled_publishes_keep_their_commit() {
+        let conn = Connection::open_in_memory().unwrap();
+        init_schema(&conn).unwrap();
+        let entry = |sha: &str, subject: &str| crate::git::LogEntry {
+            sha: sha.into(),
+            author_time: "2026-03-01T09:00:00-05:00".into(),
+            subject: subject.into(),
+        };
+        let log = [
+            entry("c3", "Publish: tea"),
+            entry("c2", "Fix typo"),
+            entry("c1", "Publish: a, b"),
+        ];
+        let commit = crate::config::CommitConfig::default();
+        assert_eq!(
+            backfill_commits(&conn, &log, "https://example.com", &commit),
+            3
+        );
+        // Already there: nothing new.
+        assert_eq!(
+            backfill_commits(&conn, &log, "https://example.com", &commit),
+            0
+        );
+
+        let shas: Vec<(String, String)> = conn
+            .prepare("SELECT slug, commit_sha FROM events ORDER BY id")
+            .unwrap()
+            .query_map([], |r| Ok((r.get(0)?, r.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let expected = [("a", "c1"), ("b", "c1"), ("tea", "c3")];
+        assert_eq!(
+            shas,
+            expected.map(|(slug, sha)| (slug.to_string(), sha.to_string()))
+        );
+    }
+
+    #[test]
+    fn deploy_rows_describe_the_publish_they_check() {
+        let conn = Connection::open_in_memory().unwrap();
+        init_schema(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO events (timestamp, local_date, local_hour, day_of_week, event, slug, title, word_count, tags, content_type, url, target_id, visibility, commit_sha)
+             VALUES ('t', 'd', 9, 0, 'publish', 'tea', 'Tea', 420, 'drinks', 'note', 'https://example.com/tea', 'blog', 'unlisted', 'c1')",
+            [],
+        )
+        .unwrap();
+        let deploy = |slug, commit_sha| DeployRecord {
+            event: "live",
+            slug,
+            title: None,
+            url: "https://example.com/tea",
+            target_id: "blog",
+            commit_sha,
+            time_to_live_ms: 4200,
+        };
+        let now = Local::now();
+        insert_deploy(&conn, &deploy("tea", Some("c1")), now).unwrap();
+        // Nothing to copy from: the columns keep their defaults.
+        insert_deploy(&conn, &deploy("tea", Some("c9")), now).unwrap();
+
+        let rows: Vec<(Option<String>, i64, String, String, i64)> = conn
+            .prepare(
+                "SELECT title, word_count, content_type, visibility, time_to_live_ms
+                 FROM events WHERE event = 'live' ORDER BY id",
+            )
+            .unwrap()
+            .query_map([], |r| {
+                Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?, r.get(4)?))
+            })
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            rows,
+            [
+                (
+                    Some("Tea".into()),
+                    420,
+                    "note".into(),
+                    "unlisted".into(),
+                    4200
+                ),
+                (None, 0, "post".into(), "public".into(), 4200),
+            ]
+        );
+    }
+}
@@ -18,25 +18,43 @@ mod analytics; // Umami analytics integration
 mod asset_usage; // Tracks which Cloudinary images are used in which posts
 mod bin_paths; // Login-shell-resolved paths to node/git
 mod cloudinary; // Uploads images/videos to Cloudinary CDN
+mod collisions; // Slug collisions between the vault and a publish target's repo
+mod commit_message; // Publish commit messages and authors from per-target templates
 mod companion; // Companion web UI server for mobile access
 pub mod config; // App configuration (vault path, publish targets, editors)
+mod deploy; // Polls a post's URL after push until the site build has it live
 mod dock_menu; // Dock right-click menu (macOS, via objc_sys class_addMethod)
+mod frontmatter; // Round-trip YAML frontmatter: typed reads, single-key edits that keep the rest as written
 mod gear;
+mod git; // libgit2-backed repo operations with typed results (pull/push via the git CLI)
+mod hooks; // Per-target shell commands run before and after a publish
 mod journal; // Publishing journal, streaks, milestones
+mod link_graph; // Backlinks, outlinks and the vault's link graph, resolved from the vault index
+mod lint; // Lint rules with ids, severities and spans, leveled per content type from config
 mod mac_native; // NSWindow proxy icon + dirty-dot via objc2 (macOS-only)
 mod media; // Multi-destination upload orchestrator (Cloudinary / R2 / both)
 mod menu; // Application menu bar builder
-mod obsidian; // Talks to Obsidian's Local REST API for backlinks
+mod obsidian; // Checks whether Obsidian's Local REST API is running
 mod open; // Open files in Obsidian, editors, terminal
 mod patterns; // Shared compiled regex patterns (LazyLock statics)
+mod post_history; // A post's commits in the target repo, merged with its journal events
 mod preview; // Manages a local Node.js server for previewing posts
 mod publish; // Handles git operations to publish posts to your website
 mod publish_diff; // Computes a line-level diff between vault source and the published copy
+mod publish_lock; // One git operation at a time per target repo (in-process queue + lock file)
+mod push_queue; // Publish commits waiting to reach the remote, retried in the background
 mod r2; // Cloudflare R2 (S3-compatible) uploader with hand-rolled SigV4
+mod redirects; // Old URL -> new URL entries in a target's _redirects / vercel.json / JSON map
+mod rename; // Renames a live post's slug across the target repo and the vault
+mod schedule; // Reads publish_at / unpublish_at times (local, date-only, relative)
+mod schedule_index; // Scheduled notes kept current by the vault watcher, so the checker can sleep
+mod search; // Full-text search (SQLite FTS5) over vault notes and the targets' published posts
 mod sketchybar_cache; // Snapshot JSON for sketchybar / ambient surfaces
 mod syndication; // Post-publish social distribution (Mastodon, etc.)
 mod syndication_queue; // Scheduled syndication queue with background sender
+mod transform; // Vault -> repo rewrites run on publish (wiki-links, embeds, private blocks)
 mod vault; // Scans your Obsidian vault for markdown files
+mod vault_index; // Persistent SQLite index of vault notes, updated incrementally by the watcher
 mod vault_pulse; // Read-only vault intelligence (never publishes)
 mod vault_watcher; // fs::notify-driven auto-refresh on vault changes
 mod webmention; // IndieWeb webmention sending // Gear inventory hygiene (Last_Used, Location, Scan_3D_URL)
@@ -81,6 +99,7 @@ pub struct MarkdownFile {
     pub is_safe: bool, // True if no warnings (safe to publish)
 
     pub warnings: Vec<String>, // List of issues: ["No date", "Has TODOs"]
+    pub lint: Vec<lint::Finding>, // The findings behind them, with rule ids and line numbers
 
     pub published_url: Option<String>, // URL if already published: "https://ejfox.com/blog/..."
     pub published_date: Option<u64>,   // When it was published (timestamp)
@@ -92,6 +111,7 @@ pub struct MarkdownFile {
 
     // Scheduling
     pub publish_at: Option<String>, // ISO 8601 datetime for scheduled publishing
+    pub unpublish_at: Option<String>, // ISO 8601 datetime to take a live post down
 
     // Content type: "post" or "weeknote"
     pub content_type: String,
@@ -171,7 +191,67 @@ fn append_to_file(path: String, content: String) -> Result<(), String> {
     Ok(())
 }
 
-// Publish a markdown file to the website (copy + git commit + push).
+// Record a publish/republish in the journal from the source file's metadata.
+// Returns the post title (or filename) so callers can use it for
+// notifications without re-reading the file.
+fn record_publish_event(
+    source_path: &str,
+    slug: &str,
+    url: &str,
+    is_republish: bool,
+    target_id: Option<&str>,
+    commit_sha: Option<&str>,
+) -> Option<String> {
+    let files = vault::get_recent_files(9999).ok()?;
+    let file = files.iter().find(|f| f.path == source_path)?;
+    let event = if is_republish { "republish" } else { "publish" };
+    let visibility = if file.password.is_some() {
+        "protected"
+    } else if file.unlisted {
+        "unlisted"
+    } else {
+        "public"
+    };
+    let _ = journal::record_event(journal::EventRecord {
+        event,
+        slug,
+        title: file.title.as_deref(),
+        word_count: file.word_count,
+        tags: &file.tags,
+        content_type: &file.content_type,
+        url: Some(url),
+        target_id,
+        visibility,
+        commit_sha,
+        unpublish_strategy: None,
+    });
+    file.title.clone().or_else(|| Some(file.filename.clone()))
+}
+
+// Journal an unpublish from one target (None = the default), with how it was
+// taken down.
+pub(crate) fn record_unpublish_event(
+    slug: &str,
+    target_id: Option<&str>,
+    unpublished: &publish::Unpublished,
+) {
+    let _ = journal::record_event(journal::EventRecord {
+        event: "unpublish",
+        slug,
+        title: None,
+        word_count: 0,
+        tags: &[],
+        content_type: "post",
+        url: Some(&unpublished.url),
+        target_id,
+        visibility: "public",
+        commit_sha: unpublished.commit_sha.as_deref(),
+        unpublish_strategy: Some(unpublished.strategy.as_str()),
+    });
+}
+
+// Publish a markdown file to the website (copy + git commit + push). Returns
+// the URL, commit SHA and any pre/post-publish hook output.
 // Takes the AppHandle so we can fire a native macOS notification post-success
 // — when Dispatch is in the background, the user gets a system banner with
 // the post title and a "View" interaction.
@@ -181,43 +261,38 @@ fn publish_file(
     source_path: String,
     slug: String,
     target_id: Option<String>,
-) -> Result<String, String> {
+) -> Result<publish::Published, String> {
     // Check if this is a republish (file already exists in website repo)
     let target = config::resolve_target(target_id.as_deref())?;
     let is_republish = vault::find_published_info_for_target(&target, &slug)
         .0
         .is_some();
 
-    let url = publish::publish_file(&source_path, &slug, target_id.as_deref())?;
-
-    // Record in journal — read source file metadata, capture the title for
-    // the notification body so we don't re-read the file later.
-    let mut notif_title: Option<String> = None;
-    if let Ok(files) = vault::get_recent_files(9999) {
-        if let Some(file) = files.iter().find(|f| f.path == source_path) {
-            notif_title = file.title.clone().or_else(|| Some(file.filename.clone()));
-            let event = if is_republish { "republish" } else { "publish" };
-            let visibility = if file.password.is_some() {
-                "protected"
-            } else if file.unlisted {
-                "unlisted"
-            } else {
-                "public"
-            };
-            let _ = journal::record_event(journal::EventRecord {
-                event,
-                slug: &slug,
-                title: file.title.as_deref(),
-                word_count: file.word_count,
-                tags: &file.tags,
-                content_type: &file.content_type,
-                url: Some(&url),
-                target_id: target_id.as_deref(),
-                visibility,
-            });
-        }
+    let published = publish::publish_file(&source_path, &slug, target_id.as_deref())?;
+    let url = &published.url;
+    // Committed but not pushed yet: the push queue journals it once it lands.
+    // A review branch is journaled by merge_publish_branch.
+    if published.pending_push.is_some() || published.branch.is_some() {
+        return Ok(published);
     }
 
+    let notif_title = record_publish_event(
+        &source_path,
+        &slug,
+        url,
+        is_republish,
+        target_id.as_deref(),
+        published.commit_sha.as_deref(),
+    );
+    deploy::watch(
+        &app,
+        &target,
+        &source_path,
+        &slug,
+        url,
+        published.commit_sha.as_deref(),
+    );
+
     // Native macOS Notification Center banner. Stays in the user's
     // notification history; the body URL is logged but the notification
     // click action returns focus to Dispatch (the only "app" target).
@@ -240,30 +315,273 @@ fn publish_file(
     // next tick (~120s by default).
     std::thread::spawn(sketchybar_cache::update);
 
-    Ok(url)
+    Ok(published)
 }
 
-// Unpublish a file (move from blog/ to drafts/ in the website repo)
+// Publish one file to several targets (the post's frontmatter `targets:`
+// when none are picked). One journal event per target that went live.
 #[tauri::command]
-fn unpublish_file(slug: String, target_id: Option<String>) -> Result<(), String> {
-    publish::unpublish_file(&slug, target_id.as_deref())?;
+fn publish_file_to_targets(
+    app: tauri::AppHandle,
+    source_path: String,
+    slug: String,
+    target_ids: Vec<String>,
+) -> Result<Vec<publish::TargetOutcome>, String> {
+    let outcomes = publish::publish_file_to_targets(&source_path, &slug, &target_ids)?;
+
+    // Queued pushes are journaled by the push queue once they land, review
+    // branches when they're merged.
+    let is_live =
+        |o: &&publish::TargetOutcome| o.success && o.pending_push.is_none() && o.branch.is_none();
+    let mut notif_title = None;
+    for o in outcomes.iter().filter(is_live) {
+        notif_title = record_publish_event(
+            &source_path,
+            &slug,
+            o.url.as_deref().unwrap_or_default(),
+            o.is_republish,
+            Some(o.target_id.as_str()),
+            o.commit_sha.as_deref(),
+        );
+        if let Ok(target) = config::resolve_target(Some(&o.target_id)) {
+            deploy::watch(
+                &app,
+                &target,
+                &source_path,
+                &slug,
+                o.url.as_deref().unwrap_or_default(),
+                o.commit_sha.as_deref(),
+            );
+        }
+    }
+
+    let live = outcomes.iter().filter(is_live).count();
+    if live > 0 {
+        use tauri_plugin_notification::NotificationExt;
+        let title_str = notif_title
+            .as_deref()
+            .map(|t| t.trim().trim_start_matches('#').trim().to_string())
+            .unwrap_or_else(|| slug.clone());
+        let _ = app
+            .notification()
+            .builder()
+            .title("Published")
+            .body(format!(
+                "\u{2018}{}\u{2019} is live on {} of {} targets",
+                title_str,
+                live,
+                outcomes.len()
+            ))
+            .show();
+        std::thread::spawn(sketchybar_cache::update);
+    }
+
+    Ok(outcomes)
+}
+
+// Merge a post's review branch (pull-request targets) into the target branch
+// and push. That's when the post goes live, so this is where it's journaled.
+#[tauri::command]
+fn merge_publish_branch(
+    app: tauri::AppHandle,
+    source_path: String,
+    slug: String,
+    target_id: Option<String>,
+) -> Result<publish::Published, String> {
+    let target = config::resolve_target(target_id.as_deref())?;
+    let is_republish = vault::find_published_info_for_target(&target, &slug)
+        .0
+        .is_some();
+    let published = publish::merge_publish_branch(&source_path, &slug, target_id.as_deref())?;
+    record_publish_event(
+        &source_path,
+        &slug,
+        &published.url,
+        is_republish,
+        Some(target.id.as_str()),
+        published.commit_sha.as_deref(),
+    );
+    deploy::watch(
+        &app,
+        &target,
+        &source_path,
+        &slug,
+        &published.url,
+        published.commit_sha.as_deref(),
+    );
+    std::thread::spawn(sketchybar_cache::update);
+    Ok(published)
+}
+
+// Target ids a post asks for in its frontmatter (`targets:`), so the publish
+// dialog can pre-tick them. Empty when it doesn't say.
+#[tauri::command]
+fn get_post_targets(source_path: String) -> Vec<String> {
+    vault::post_targets(&source_path)
+}
+
+// Publish several posts as one commit and one push. Rolls the repo back if
+// any copy or pre-flight check fails; journal events are still per post.
+#[tauri::command]
+fn publish_batch(
+    app: tauri::AppHandle,
+    items: Vec<publish::BatchItem>,
+    target_id: Option<String>,
+) -> Result<Vec<publish::BatchPublished>, String> {
+    let published = publish::publish_batch(&items, target_id.as_deref())?;
+    let target = config::resolve_target(target_id.as_deref())?;
+    for p in published.iter().filter(|p| p.pending_push.is_none()) {
+        record_publish_event(
+            &p.source_path,
+            &p.slug,
+            &p.url,
+            p.is_republish,
+            target_id.as_deref(),
+            p.commit_sha.as_deref(),
+        );
+        deploy::watch(
+            &app,
+            &target,
+            &p.source_path,
+            &p.slug,
+            &p.url,
+            p.commit_sha.as_deref(),
+        );
+    }
+    std::thread::spawn(sketchybar_cache::update);
+    Ok(published)
+}
+
+// Dry run of publish_file: where the file would land, the git commands,
+// commit message, final URL and open warnings. Touches nothing.
+#[tauri::command]
+fn plan_publish(
+    source_path: String,
+    slug: String,
+    target_id: Option<String>,
+) -> Result<publish::PublishPlan, String> {
+    publish::plan_publish(&source_path, &slug, target_id.as_deref())
+}
+
+// Unpublish a file the way the target is set up to (move to drafts, mark it
+// draft/unlisted, archive or delete it in the website repo)
+#[tauri::command]
+fn unpublish_file(slug: String, target_id: Option<String>) -> Result<publish::Unpublished, String> {
+    let unpublished = publish::unpublish_file(&slug, target_id.as_deref())?;
+    record_unpublish_event(&slug, target_id.as_deref(), &unpublished);
+    std::thread::spawn(sketchybar_cache::update);
+
+    Ok(unpublished)
+}
+
+// Change a live post's slug: one commit moves the file and adds a redirect
+// from the old URL, then the vault note is renamed and links to it rewritten.
+#[tauri::command]
+fn rename_published_slug(
+    old: String,
+    new: String,
+    target_id: Option<String>,
+) -> Result<rename::Renamed, String> {
+    let renamed = rename::rename_published_slug(&old, &new, target_id.as_deref())?;
 
-    // Record in journal
     let _ = journal::record_event(journal::EventRecord {
-        event: "unpublish",
-        slug: &slug,
+        event: "rename",
+        slug: &new,
         title: None,
         word_count: 0,
         tags: &[],
         content_type: "post",
-        url: None,
+        url: Some(&renamed.url),
         target_id: target_id.as_deref(),
         visibility: "public",
+        commit_sha: renamed.commit_sha.as_deref(),
+        unpublish_strategy: None,
     });
 
     std::thread::spawn(sketchybar_cache::update);
 
-    Ok(())
+    Ok(renamed)
+}
+
+// Undo a publish journal event: `git revert` the commit it recorded and push.
+// The vault file is left alone. A batch commit can only be reverted whole, so
+// one that published other posts too is refused unless `whole_batch` says
+// the caller has confirmed that (see `get_publish_batch`); each of them then
+// gets its own "revert" event. A revert that couldn't push is queued like a
+// publish, but journaled now.
+#[tauri::command]
+fn revert_publish(event_id: i64, whole_batch: Option<bool>) -> Result<publish::Reverted, String> {
+    let entry = journal::get_entry(event_id)?;
+    if entry.event != "publish" && entry.event != "republish" {
+        return Err(format!("Can't revert a \"{}\" event", entry.event));
+    }
+    let commit_sha = entry.commit_sha.clone().ok_or_else(|| {
+        "No commit recorded for this publish (older entry, or nothing changed)".to_string()
+    })?;
+
+    let mut batch = journal::entries_for_commit(&commit_sha).unwrap_or_default();
+    if batch.len() > 1 && whole_batch != Some(true) {
+        let slugs: Vec<&str> = batch.iter().map(|e| e.slug.as_str()).collect();
+        return Err(format!(
+            "{} went out in a batch of {} posts ({}); reverting its commit takes them all down",
+            entry.slug,
+            batch.len(),
+            slugs.join(", ")
+        ));
+    }
+    if batch.is_empty() {
+        batch.push(entry.clone());
+    }
+
+    let reverted = publish::revert_publish(
+        &commit_sha,
+        entry.target_id.as_deref(),
+        &entry.slug,
+        entry.url.as_deref().unwrap_or_default(),
+    )?;
+
+    for e in &batch {
+        let tags: Vec<String> = e
+            .tags
+            .split(',')
+            .filter(|t| !t.is_empty())
+            .map(String::from)
+            .collect();
+        let _ = journal::record_event(journal::EventRecord {
+            event: "revert",
+            slug: &e.slug,
+            title: e.title.as_deref(),
+            word_count: 0,
+            tags: &tags,
+            content_type: &e.content_type,
+            url: e.url.as_deref(),
+            target_id: e.target_id.as_deref(),
+            visibility: &e.visibility,
+            commit_sha: Some(&reverted.commit_sha),
+            unpublish_strategy: None,
+        });
+    }
+
+    std::thread::spawn(sketchybar_cache::update);
+
+    Ok(reverted)
+}
+
+// The publish events that share an event's commit: more than one means it
+// was a batch, and reverting it would take all of them down.
+#[tauri::command]
+fn get_publish_batch(event_id: i64) -> Result<Vec<journal::JournalEntry>, String> {
+    match journal::get_entry(event_id)?.commit_sha {
+        Some(sha) => journal::entries_for_commit(&sha),
+        None => Ok(Vec::new()),
+    }
+}
+
+// Slug problems between the vault and a target repo: the same slug live in
+// two folders, live posts with no vault note, and ambiguous vault matches.
+#[tauri::command]
+fn scan_slug_collisions(target_id: Option<String>) -> Result<Vec<collisions::Collision>, String> {
+    collisions::scan_target(target_id.as_deref())
 }
 
 // Get the current git status of the website repo
@@ -272,18 +590,79 @@ fn get_git_status(target_id: Option<String>) -> publish::GitStatus {
     publish::get_git_status(target_id.as_deref())
 }
 
+// Publishes committed but not yet pushed (offline, rejected), oldest first.
+#[tauri::command]
+fn get_pending_pushes(target_id: Option<String>) -> Result<Vec<push_queue::PendingPush>, String> {
+    push_queue::get_pending(target_id.as_deref())
+}
+
+// Retry every pending push now instead of waiting out the backoff. Returns
+// how many landed; each one is journaled as it does.
+#[tauri::command]
+fn retry_pending_pushes(app: tauri::AppHandle) -> Result<usize, String> {
+    push_queue::retry_all(&app)
+}
+
 // Add a tag to a markdown file's frontmatter
 #[tauri::command]
 fn add_tag_to_file(path: String, tag: String) -> Result<(), String> {
     vault::add_tag_to_file(&path, &tag)
 }
 
-// Get backlinks (other files that link to this one) via Obsidian's API
-// `async` means this function can pause while waiting for network requests
+// Lint one note: each finding's rule, severity, span and fix, at the
+// levels the config gives its content type
+#[tauri::command]
+fn lint_file(path: String) -> Result<Vec<lint::Finding>, String> {
+    lint::lint_file(&path, &config::get()?.lint)
+}
+
+// Apply a note's lint fixes, for `rules` or every rule if empty. Returns
+// how many were applied.
+#[tauri::command]
+fn fix_lint(path: String, rules: Vec<String>) -> Result<usize, String> {
+    lint::fix_file(&path, &rules, &config::get()?.lint)
+}
+
+// Get backlinks (other notes that link to this one) from the vault's own
+// link graph. The first call of a session may walk the vault, so these run
+// off the main thread.
+#[tauri::command]
+async fn get_backlinks(path: String) -> Result<Vec<link_graph::Backlink>, String> {
+    tokio::task::spawn_blocking(move || link_graph::backlinks(&path))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+// Every link in a note, with the note each one resolves to
+#[tauri::command]
+async fn get_outlinks(path: String) -> Result<Vec<link_graph::Outlink>, String> {
+    tokio::task::spawn_blocking(move || link_graph::outlinks(&path))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+// Notes with no links in or out
+#[tauri::command]
+async fn get_orphan_notes() -> Result<Vec<link_graph::GraphNode>, String> {
+    tokio::task::spawn_blocking(link_graph::orphans)
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+// The whole link graph: nodes, edges and links to notes that don't exist
 #[tauri::command]
-async fn get_backlinks(filename: String) -> Result<Vec<obsidian::Backlink>, String> {
-    // .await pauses until the async operation completes
-    obsidian::get_backlinks(&filename).await
+async fn get_link_graph() -> Result<link_graph::LinkGraph, String> {
+    tokio::task::spawn_blocking(link_graph::graph)
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+// Save the link graph as JSON
+#[tauri::command]
+async fn export_link_graph(dest: String) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || link_graph::export(&dest))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
 }
 
 // Check if Obsidian's Local REST API is running
@@ -336,7 +715,17 @@ fn get_app_config() -> Result<config::AppConfig, String> {
 
 #[tauri::command]
 fn save_app_config(config_data: config::AppConfig) -> Result<(), String> {
-    config::update(config_data)
+    config::update(config_data)?;
+    // The vault, its publishable dirs or the timezone may have changed
+    if let Err(e) = schedule_index::rebuild() {
+        log::warn!("Couldn't re-index scheduled posts: {}", e);
+    }
+    std::thread::spawn(|| {
+        if let Err(e) = vault_index::sync() {
+            log::warn!("Couldn't re-index the vault: {}", e);
+        }
+    });
+    Ok(())
 }
 
 #[tauri::command]
@@ -524,6 +913,7 @@ async fn get_top_posts(
 
 #[tauri::command]
 fn schedule_publish(path: String, publish_at: String) -> Result<(), String> {
+    let publish_at = schedule::normalize_now(&publish_at)?;
     vault::set_frontmatter_field(&path, "publish_at", &publish_at)
 }
 
@@ -532,6 +922,18 @@ fn cancel_schedule(path: String) -> Result<(), String> {
     vault::remove_frontmatter_field(&path, "publish_at")
 }
 
+// Take a live post down at `unpublish_at` (see run_schedule_checker).
+#[tauri::command]
+fn schedule_unpublish(path: String, unpublish_at: String) -> Result<(), String> {
+    let unpublish_at = schedule::normalize_now(&unpublish_at)?;
+    vault::set_frontmatter_field(&path, "unpublish_at", &unpublish_at)
+}
+
+#[tauri::command]
+fn cancel_scheduled_unpublish(path: String) -> Result<(), String> {
+    vault::remove_frontmatter_field(&path, "unpublish_at")
+}
+
 #[tauri::command]
 fn set_frontmatter(path: String, key: String, value: String) -> Result<(), String> {
     vault::set_frontmatter_field(&path, &key, &value)
@@ -582,7 +984,7 @@ fn get_journal_heatmap(days: Option<u32>) -> Result<Vec<(String, u32)>, String>
 #[tauri::command]
 fn backfill_journal() -> Result<u32, String> {
     let target = config::default_target()?;
-    journal::backfill_from_git(&target.repo_path, &target.domain)
+    journal::backfill_from_git(&target.repo_path, &target.domain, &target.commit)
 }
 
 // --- VAULT PULSE ---
@@ -666,6 +1068,29 @@ fn get_publish_diff(file_path: String) -> Result<publish_diff::PublishDiff, Stri
     publish_diff::compute_publish_diff(&file_path)
 }
 
+// Every version of a post the target repo has had, across year folders and
+// renames, with the journal's events for it. Walks the repo's whole log, so
+// it runs off the main thread.
+#[tauri::command]
+async fn get_post_history(
+    slug: String,
+    target_id: Option<String>,
+) -> Result<Vec<post_history::HistoryEntry>, String> {
+    tokio::task::spawn_blocking(move || post_history::get_post_history(&slug, target_id.as_deref()))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+// Full-text search over the vault and the targets' published posts. The
+// first search of a session may walk the vault, so it runs off the main
+// thread.
+#[tauri::command]
+async fn search_notes(query: search::SearchQuery) -> Result<search::SearchResults, String> {
+    tokio::task::spawn_blocking(move || search::search(&query))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
 // Upload multiple files to Cloudinary
 #[tauri::command]
 async fn cloudinary_upload_batch(
@@ -1023,9 +1448,15 @@ pub fn run() {
     // --- START PREVIEW SERVER ---
     preview::init_server();
 
-    // --- WARM SKETCHYBAR CACHE ---
-    // Off-thread so a slow first-vault-scan doesn't block app launch.
-    std::thread::spawn(sketchybar_cache::update);
+    // --- WARM VAULT INDEX + SKETCHYBAR CACHE ---
+    // Off-thread so a slow first-vault-scan doesn't block app launch. The
+    // cache reads the index, so it waits for the sync.
+    std::thread::spawn(|| {
+        if let Err(e) = vault_index::sync() {
+            log::warn!("Couldn't index the vault: {}", e);
+        }
+        sketchybar_cache::update();
+    });
 
     // --- BUILD AND RUN THE APP ---
     // (Open Recent commands defined below the run block.)
@@ -1064,6 +1495,12 @@ pub fn run() {
                 publish::run_schedule_checker(handle).await;
             });
 
+            // Retry publishes that committed but couldn't push
+            let push_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                push_queue::run_push_queue(push_handle).await;
+            });
+
             // Start syndication scheduler background task
             let syndication_handle = app.handle().clone();
             tauri::async_runtime::spawn(async move {
@@ -1109,10 +1546,27 @@ pub fn run() {
             get_file_content,
             append_to_file,
             publish_file,
+            publish_file_to_targets,
+            merge_publish_branch,
+            get_post_targets,
+            plan_publish,
+            publish_batch,
             unpublish_file,
+            rename_published_slug,
+            revert_publish,
+            get_publish_batch,
+            scan_slug_collisions,
             get_git_status,
+            get_pending_pushes,
+            retry_pending_pushes,
             add_tag_to_file,
+            lint_file,
+            fix_lint,
             get_backlinks,
+            get_outlinks,
+            get_orphan_notes,
+            get_link_graph,
+            export_link_graph,
             check_obsidian_api,
             open_in_obsidian,
             open_in_app,
@@ -1129,6 +1583,8 @@ pub fn run() {
             media_upload,
             check_media_status,
             get_publish_diff,
+            get_post_history,
+            search_notes,
             get_local_media,
             fix_local_media,
             apply_media_fixes,
@@ -1137,6 +1593,8 @@ pub fn run() {
             get_post_assets,
             schedule_publish,
             cancel_schedule,
+            schedule_unpublish,
+            cancel_scheduled_unpublish,
             set_frontmatter,
             send_webmentions,
             get_journal_stats,
@@ -0,0 +1,441 @@
+//! The vault's link graph, built from the links the vault index reads out
+//! of each note, so backlinks don't need Obsidian running.
+//!
+//! Link targets resolve the way Obsidian resolves them: a wiki-link names
+//! a note by its filename, or by enough of its path to tell it apart, and
+//! the closest match wins when several notes share a name. A markdown link
+//! is a path relative to the linking note first. Links to URLs and to
+//! files that aren't notes are left out; links to notes that don't exist
+//! are listed as unresolved.
+
+use crate::vault_index::{Link, Note};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Backlink {
+    pub path: String,
+    pub title: Option<String>,
+    /// The line of the first link from that note.
+    pub context: String,
+    pub line: usize,
+}
+
+/// A link out of a note, with where it goes.
+#[derive(Debug, Clone, Serialize)]
+pub struct Outlink {
+    #[serde(flatten)]
+    pub link: Link,
+    /// The note it resolves to; None for a URL, a file that isn't a note,
+    /// or a note that doesn't exist.
+    pub resolved: Option<String>,
+    /// A link to a note that doesn't exist.
+    pub unresolved: bool,
+}
+
+/// A link to a note that doesn't exist.
+#[derive(Debug, Clone, Serialize)]
+pub struct UnresolvedLink {
+    /// The note the link is in.
+    pub source: String,
+    #[serde(flatten)]
+    pub link: Link,
+}
+
+/// The whole graph, in the nodes-and-edges shape graph tools read.
+#[derive(Debug, Clone, Serialize)]
+pub struct LinkGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+    pub unresolved: Vec<UnresolvedLink>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphNode {
+    /// The note's path.
+    pub id: String,
+    pub title: String,
+    pub slug: String,
+    pub tags: Vec<String>,
+    pub published: bool,
+    /// Other notes linking here.
+    pub links_in: usize,
+    /// Other notes linked from here.
+    pub links_out: usize,
+}
+
+/// Every link from one note to another, counted once.
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphEdge {
+    pub source: String,
+    pub target: String,
+    /// How many links there are.
+    pub count: usize,
+    /// Whether any of them is an embed.
+    pub embed: bool,
+}
+
+/// The graph of the vault as the index has it now.
+pub fn graph() -> Result<LinkGraph, String> {
+    let vault_path = crate::config::get()?.vault.path;
+    Ok(build(&vault_path, &crate::vault_index::notes()?).graph)
+}
+
+/// Notes linking to the one at `path`, one entry per note.
+pub fn backlinks(path: &str) -> Result<Vec<Backlink>, String> {
+    let vault_path = crate::config::get()?.vault.path;
+    Ok(build(&vault_path, &crate::vault_index::notes()?).backlinks(path))
+}
+
+/// Every link in the note at `path`, in the order they're written.
+pub fn outlinks(path: &str) -> Result<Vec<Outlink>, String> {
+    let vault_path = crate::config::get()?.vault.path;
+    Ok(build(&vault_path, &crate::vault_index::notes()?).outlinks(path))
+}
+
+/// Notes nothing links to that don't link to another note either. A link
+/// to a note that doesn't exist doesn't count.
+pub fn orphans() -> Result<Vec<GraphNode>, String> {
+    Ok(graph()?
+        .nodes
+        .into_iter()
+        .filter(|node| node.links_in == 0 && node.links_out == 0)
+        .collect())
+}
+
+/// Write the graph to `dest` as JSON.
+pub fn export(dest: &str) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(&graph()?)
+        .map_err(|e| format!("Failed to serialize link graph: {}", e))?;
+    std::fs::write(dest, json).map_err(|e| format!("Failed to write {}: {}", dest, e))
+}
+
+/// The graph, and each link with where it resolved.
+struct Built<'a> {
+    links: Vec<(&'a Note, &'a Link, Resolved<'a>)>,
+    graph: LinkGraph,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Resolved<'a> {
+    Note(&'a str),
+    /// A URL, or a file that isn't a note.
+    Elsewhere,
+    Missing,
+}
+
+impl Built<'_> {
+    fn backlinks(&self, path: &str) -> Vec<Backlink> {
+        let mut backlinks: Vec<Backlink> = Vec::new();
+        for (source, link, resolved) in &self.links {
+            if *resolved != Resolved::Note(path) || source.path == path {
+                continue;
+            }
+            if backlinks.iter().any(|b| b.path == source.path) {
+                continue;
+            }
+            backlinks.push(Backlink {
+                path: source.path.clone(),
+                title: Some(title(source)),
+                context: link.context.clone(),
+                line: link.line,
+            });
+        }
+        backlinks.sort_by(|a, b| a.title.cmp(&b.title));
+        backlinks
+    }
+
+    fn outlinks(&self, path: &str) -> Vec<Outlink> {
+        self.links
+            .iter()
+            .filter(|(source, _, _)| source.path == path)
+            .map(|(_, link, resolved)| Outlink {
+                link: (*link).clone(),
+                resolved: match resolved {
+                    Resolved::Note(target) => Some(target.to_string()),
+                    _ => None,
+                },
+                unresolved: *resolved == Resolved::Missing,
+            })
+            .collect()
+    }
+}
+
+fn build<'a>(vault_path: &'a str, notes: &'a [Note]) -> Built<'a> {
+    let resolver = Resolver::new(vault_path, notes);
+    let mut links = Vec::new();
+    let mut edges: BTreeMap<(&str, &str), GraphEdge> = BTreeMap::new();
+    let mut unresolved = Vec::new();
+    for note in notes {
+        for link in &note.links {
+            let resolved = resolver.resolve(note, link);
+            match resolved {
+                Resolved::Note(target) if target != note.path => {
+                    let edge = edges
+                        .entry((note.path.as_str(), target))
+                        .or_insert_with(|| GraphEdge {
+                            source: note.path.clone(),
+                            target: target.to_string(),
+                            count: 0,
+                            embed: false,
+                        });
+                    edge.count += 1;
+                    edge.embed |= link.embed;
+                }
+                Resolved::Missing => unresolved.push(UnresolvedLink {
+                    source: note.path.clone(),
+                    link: link.clone(),
+                }),
+                _ => {}
+            }
+            links.push((note, link, resolved));
+        }
+    }
+
+    let (mut links_in, mut links_out) = (HashMap::new(), HashMap::new());
+    for (source, target) in edges.keys() {
+        *links_out.entry(*source).or_insert(0) += 1;
+        *links_in.entry(*target).or_insert(0) += 1;
+    }
+    let mut nodes: Vec<GraphNode> = notes
+        .iter()
+        .map(|note| GraphNode {
+            id: note.path.clone(),
+            title: title(note),
+            slug: note.slug.clone(),
+            tags: note.tags.clone(),
+            published: note.published.is_some(),
+            links_in: links_in.get(note.path.as_str()).copied().unwrap_or(0),
+            links_out: links_out.get(note.path.as_str()).copied().unwrap_or(0),
+        })
+        .collect();
+    nodes.sort_by(|a, b| a.id.cmp(&b.id));
+    Built {
+        links,
+        graph: LinkGraph {
+            nodes,
+            edges: edges.into_values().collect(),
+            unresolved,
+        },
+    }
+}
+
+fn title(note: &Note) -> String {
+    note.title
+        .clone()
+        .or_else(|| note.heading.clone())
+        .unwrap_or_else(|| note.slug.clone())
+}
+
+/// Finds the note a link names.
+struct Resolver<'a> {
+    vault_path: &'a str,
+    /// Vault-relative path without `.md`, lowercased → the note's path.
+    by_rel: HashMap<String, &'a str>,
+    /// Filename without `.md`, lowercased → vault-relative paths of the
+    /// notes with it, lowercased.
+    by_name: HashMap<String, Vec<String>>,
+}
+
+impl<'a> Resolver<'a> {
+    fn new(vault_path: &'a str, notes: &'a [Note]) -> Self {
+        let mut by_rel = HashMap::new();
+        let mut by_name: HashMap<String, Vec<String>> = HashMap::new();
+        for note in notes {
+            let rel = note_key(relative(vault_path, &note.path));
+            let name = rel.rsplit('/').next().unwrap_or(&rel).to_string();
+            by_name.entry(name).or_default().push(rel.clone());
+            by_rel.insert(rel, note.path.as_str());
+        }
+        Resolver {
+            vault_path,
+            by_rel,
+            by_name,
+        }
+    }
+
+    fn resolve(&self, from: &'a Note, link: &Link) -> Resolved<'a> {
+        let target = link.target.trim();
+        if target.is_empty() {
+            return Resolved::Note(from.path.as_str());
+        }
+        // Only a known attachment type counts as a file: note names can have
+        // dots in them too (`Dr. Who`, `2024.01.05`).
+        let is_url = target.contains("://") || target.starts_with("mailto:");
+        if is_url || crate::transform::is_attachment(target) {
+            return Resolved::Elsewhere;
+        }
+
+        let key = note_key(target.trim_start_matches('/'));
+        let from_rel = relative(self.vault_path, &from.path);
+        let from_dir = from_rel.rsplit_once('/').map_or("", |(dir, _)| dir);
+        if link.kind == "markdown" && !target.starts_with('/') {
+            let beside = normalize(&format!("{}/{}", from_dir, target));
+            if let Some(&path) = self.by_rel.get(&note_key(&beside)) {
+                return Resolved::Note(path);
+            }
+        }
+        if let Some(&path) = self.by_rel.get(&key) {
+            return Resolved::Note(path);
+        }
+
+        // A name, or the end of a path: the note nearest the link wins,
+        // then the shortest path.
+        let name = key.rsplit('/').next().unwrap_or(&key);
+        let from_dir = from_dir.to_lowercase();
+        self.by_name
+            .get(name)
+            .into_iter()
+            .flatten()
+            .filter(|rel| *rel == &key || rel.ends_with(&format!("/{}", key)))
+            .min_by_key(|rel| {
+                let shared = rel
+                    .split('/')
+                    .zip(from_dir.split('/'))
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                (std::cmp::Reverse(shared), rel.len(), rel.to_string())
+            })
+            .and_then(|rel| self.by_rel.get(rel).copied())
+            .map_or(Resolved::Missing, Resolved::Note)
+    }
+}
+
+fn relative<'p>(vault_path: &str, path: &'p str) -> &'p str {
+    path.strip_prefix(vault_path)
+        .unwrap_or(path)
+        .trim_start_matches('/')
+}
+
+/// Lowercased, without `.md`.
+fn note_key(path: &str) -> String {
+    let path = path.to_lowercase();
+    path.strip_suffix(".md").unwrap_or(&path).to_string()
+}
+
+/// Resolve `.` and `..` segments.
+fn normalize(path: &str) -> String {
+    let mut parts: Vec<&str> = Vec::new();
+    for part in path.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            _ => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(path: &str, links: &[(&str, &str)]) -> Note {
+        Note {
+            path: format!("/vault/{}", path),
+            slug: path
+                .rsplit('/')
+                .next()
+                .unwrap()
+                .trim_end_matches(".md")
+                .into(),
+            links: links
+                .iter()
+                .enumerate()
+                .map(|(i, (kind, target))| Link {
+                    target: target.to_string(),
+                    anchor: None,
+                    alias: None,
+                    embed: false,
+                    kind: kind.to_string(),
+                    line: i + 1,
+                    context: format!("line {}", i + 1),
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn links_resolve_like_obsidian() {
+        let notes = [
+            note(
+                "blog/tea.md",
+                &[
+                    ("wiki", "Coffee"),
+                    ("wiki", "coffee"),
+                    ("wiki", "Kettle"),
+                    ("markdown", "../drafts/cups.md"),
+                    ("markdown", "https://example.com"),
+                    ("wiki", "diagram.pdf"),
+                    ("wiki", "Teapot"),
+                    ("wiki", "Dr. Who"),
+                    ("markdown", "../daily/2024.01.05.md"),
+                ],
+            ),
+            note("blog/coffee.md", &[("wiki", "tea"), ("wiki", "")]),
+            note("archive/coffee.md", &[]),
+            note("gear/kettle.md", &[("wiki", "blog/tea")]),
+            note("drafts/cups.md", &[]),
+            note("drafts/alone.md", &[("wiki", "Nowhere")]),
+            note("drafts/quiet.md", &[]),
+            note("people/Dr. Who.md", &[]),
+            note("daily/2024.01.05.md", &[]),
+        ];
+        let built = build("/vault", &notes);
+
+        // The coffee beside tea wins over the archived one.
+        let outlinks = built.outlinks("/vault/blog/tea.md");
+        let resolved: Vec<Option<&str>> = outlinks.iter().map(|o| o.resolved.as_deref()).collect();
+        assert_eq!(
+            resolved,
+            [
+                Some("/vault/blog/coffee.md"),
+                Some("/vault/blog/coffee.md"),
+                Some("/vault/gear/kettle.md"),
+                Some("/vault/drafts/cups.md"),
+                None,
+                None,
+                None,
+                Some("/vault/people/Dr. Who.md"),
+                Some("/vault/daily/2024.01.05.md"),
+            ]
+        );
+        assert!(!outlinks[4].unresolved && !outlinks[5].unresolved);
+        assert!(outlinks[6].unresolved);
+
+        let backlinks = built.backlinks("/vault/blog/tea.md");
+        let from: Vec<(&str, usize)> = backlinks
+            .iter()
+            .map(|b| (b.path.as_str(), b.line))
+            .collect();
+        assert_eq!(
+            from,
+            [("/vault/blog/coffee.md", 1), ("/vault/gear/kettle.md", 1)]
+        );
+        assert_eq!(built.backlinks("/vault/blog/coffee.md").len(), 1);
+
+        let graph = &built.graph;
+        let coffee = graph
+            .edges
+            .iter()
+            .find(|e| e.target == "/vault/blog/coffee.md")
+            .unwrap();
+        assert_eq!(coffee.count, 2);
+        let missing: Vec<&str> = graph
+            .unresolved
+            .iter()
+            .map(|u| u.link.target.as_str())
+            .collect();
+        assert_eq!(missing, ["Teapot", "Nowhere"]);
+        let orphans: Vec<&str> = graph
+            .nodes
+            .iter()
+            .filter(|n| n.links_in == 0 && n.links_out == 0)
+            .map(|n| n.slug.as_str())
+            .collect();
+        assert_eq!(orphans, ["coffee", "alone", "quiet"]);
+    }
+}
@@ -0,0 +1,628 @@
+//! Lint rules for notes: what the file list's warnings and the publish
+//! checks are made of.
+//!
+//! Every rule has an id (`todo`, `privacy/email`) and a default level.
+//! `LintConfig` turns a rule off or re-levels it, for every note or per
+//! content type. `check` runs every rule over a note's text, and is what
+//! the vault index stores; `leveled` applies the config when the findings
+//! are read, so changing a level doesn't mean reading the vault again.
+//!
+//! A few rules look at what the vault knows about a note rather than its
+//! text: whether it changed since it was published (`modified`) and its
+//! schedule (`schedule/…`). The vault adds those findings through `finding`.
+
+use crate::config::{LintConfig, LintLevel};
+use crate::frontmatter::Frontmatter;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warn,
+    Error,
+}
+
+/// One thing a rule found, and where.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Finding {
+    pub rule: String,
+    pub severity: Severity,
+    pub message: String,
+    /// 1-based, in the whole file (frontmatter included). Columns count
+    /// characters; the end is exclusive.
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+    pub fix: Option<Fix>,
+}
+
+/// Replace the bytes `start..end` of the file with `replacement`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fix {
+    pub label: String,
+    pub start: usize,
+    pub end: usize,
+    pub replacement: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Rule {
+    pub id: &'static str,
+    /// The warning the file list shows when the rule finds anything.
+    pub label: &'static str,
+    pub level: LintLevel,
+    /// Off outside week notes unless config turns it on.
+    pub weeknote_only: bool,
+}
+
+const fn rule(id: &'static str, label: &'static str, weeknote_only: bool) -> Rule {
+    Rule {
+        id,
+        label,
+        level: LintLevel::Warn,
+        weeknote_only,
+    }
+}
+
+/// Every rule, in the order their warnings are listed.
+pub const RULES: &[Rule] = &[
+    rule("modified", "Modified since publish", false),
+    rule("no-date", "No date", false),
+    rule("todo", "Has TODOs", false),
+    rule("local-images", "Local images", false),
+    rule("privacy/phone", "Phone number detected", true),
+    rule("privacy/email", "Email address detected", true),
+    rule("privacy/money", "Financial amount detected", true),
+    rule("privacy/ssn", "Possible SSN detected", true),
+    rule("privacy/address", "Street address detected", true),
+    rule("privacy/person", "Named person reference", true),
+    rule("privacy/health", "Health/medical info", true),
+    rule("broken-link", "Broken link", false),
+    rule("missing-alt", "Missing alt text", false),
+    rule("local-media", "Local media", false),
+    rule("local-video", "Local video", false),
+    rule("long-link-text", "Long link text", false),
+    rule("schedule/unreadable", "Unreadable schedule", false),
+    rule("schedule/unpinned", "Unpinned schedule", false),
+    rule("schedule/overdue", "Schedule overdue", false),
+];
+
+// ---------------------------------------------------------------------------
+// Public API
+// ---------------------------------------------------------------------------
+
+/// Every rule's findings in `content`, at their default levels, in file
+/// order.
+pub fn check(content: &str) -> Vec<Finding> {
+    let (frontmatter, body) = Frontmatter::parse(content);
+    let mut lint = Lint {
+        content,
+        offset: content.len() - body.len(),
+        findings: Vec::new(),
+    };
+    lint.dates(&frontmatter);
+    lint.todos(body);
+    lint.local_images(body);
+    lint.privacy(body);
+    lint.broken_links(body);
+    lint.alt_text(body);
+    lint.local_media(body);
+    lint.long_link_text(body, 4);
+    let mut findings = lint.findings;
+    findings.sort_by_key(|f| (f.line, f.column));
+    findings
+}
+
+/// `findings` at the levels `config` gives them for `content_type`, less
+/// the rules it turns off.
+pub fn leveled(findings: &[Finding], content_type: &str, config: &LintConfig) -> Vec<Finding> {
+    findings
+        .iter()
+        .filter_map(|finding| {
+            let severity = severity(level(&finding.rule, content_type, config))?;
+            Some(Finding {
+                severity,
+                ..finding.clone()
+            })
+        })
+        .collect()
+}
+
+/// A note's findings at its configured levels, read from disk.
+pub fn lint_file(path: &str, config: &LintConfig) -> Result<Vec<Finding>, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let content_type = crate::vault::content_type_for_path(path);
+    Ok(leveled(&check(&content), content_type, config))
+}
+
+/// The file list's one-line warnings: the label of each rule with a
+/// finding at warn or above.
+pub fn warnings(findings: &[Finding]) -> Vec<String> {
+    RULES
+        .iter()
+        .filter(|rule| {
+            findings
+                .iter()
+                .any(|f| f.rule == rule.id && f.severity >= Severity::Warn)
+        })
+        .map(|rule| rule.label.to_string())
+        .collect()
+}
+
+/// A finding at file offsets `start..end` of `content`, at the rule's
+/// default level.
+pub fn finding(content: &str, id: &str, start: usize, end: usize, message: String) -> Finding {
+    let (line, column) = position(content, start);
+    let (end_line, end_column) = position(content, end);
+    let level = RULES.iter().find(|r| r.id == id).map(|r| r.level);
+    Finding {
+        rule: id.to_string(),
+        severity: level.and_then(severity).unwrap_or(Severity::Warn),
+        message,
+        line,
+        column,
+        end_line,
+        end_column,
+        fix: None,
+    }
+}
+
+/// Err listing the error-level findings, when `config` blocks publishing
+/// on them.
+pub fn check_publish(findings: &[Finding], config: &LintConfig) -> Result<(), String> {
+    let errors: Vec<String> = findings
+        .iter()
+        .filter(|f| f.severity == Severity::Error)
+        .map(|f| format!("{} (line {})", f.message, f.line))
+        .collect();
+    if !config.block_publish || errors.is_empty() {
+        return Ok(());
+    }
+    Err(format!(
+        "Lint errors block publishing: {}",
+        errors.join("; ")
+    ))
+}
+
+/// `check_publish` for a note on disk.
+pub fn check_publish_file(path: &str, config: &LintConfig) -> Result<(), String> {
+    check_publish(&lint_file(path, config)?, config)
+}
+
+/// Apply the fixes for `rules` (every rule when empty) to the note at
+/// `path`. Returns how many were applied.
+pub fn fix_file(path: &str, rules: &[String], config: &LintConfig) -> Result<usize, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let content_type = crate::vault::content_type_for_path(path);
+    let findings: Vec<Finding> = leveled(&check(&content), content_type, config)
+        .into_iter()
+        .filter(|f| rules.is_empty() || rules.contains(&f.rule))
+        .collect();
+    let (fixed, count) = apply_fixes(&content, &findings);
+    if count > 0 {
+        fs::write(path, fixed).map_err(|e| format!("Failed to write file: {}", e))?;
+    }
+    Ok(count)
+}
+
+/// `content` with the fixes among `findings` applied, and how many. A fix
+/// overlapping one already applied is skipped.
+pub fn apply_fixes(content: &str, findings: &[Finding]) -> (String, usize) {
+    let mut fixes: Vec<&Fix> = findings.iter().filter_map(|f| f.fix.as_ref()).collect();
+    // Last first, so the offsets of the rest still hold.
+    fixes.sort_by_key(|fix| std::cmp::Reverse(fix.start));
+    let mut fixed = content.to_string();
+    let mut count = 0;
+    let mut limit = content.len();
+    for fix in fixes {
+        if fix.end > limit || fix.start > fix.end {
+            continue;
+        }
+        fixed.replace_range(fix.start..fix.end, &fix.replacement);
+        limit = fix.start;
+        count += 1;
+    }
+    (fixed, count)
+}
+
+// ---------------------------------------------------------------------------
+// Levels
+// ---------------------------------------------------------------------------
+
+/// The content type's setting for the rule or its group, else the global
+/// one, else the rule's default.
+fn level(id: &str, content_type: &str, config: &LintConfig) -> LintLevel {
+    let group = id.split_once('/').map(|(group, _)| group);
+    let lookup = |levels: &BTreeMap<String, LintLevel>| {
+        levels
+            .get(id)
+            .or_else(|| group.and_then(|g| levels.get(g)))
+            .copied()
+    };
+    if let Some(level) = config.content_types.get(content_type).and_then(lookup) {
+        return level;
+    }
+    if let Some(level) = lookup(&config.rules) {
+        return level;
+    }
+    match RULES.iter().find(|r| r.id == id) {
+        Some(rule) if rule.weeknote_only && content_type != "weeknote" => LintLevel::Off,
+        Some(rule) => rule.level,
+        // A rule an older build stored that's since been dropped.
+        None => LintLevel::Off,
+    }
+}
+
+fn severity(level: LintLevel) -> Option<Severity> {
+    match level {
+        LintLevel::Off => None,
+        LintLevel::Info => Some(Severity::Info),
+        LintLevel::Warn => Some(Severity::Warn),
+        LintLevel::Error => Some(Severity::Error),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Rules
+// ---------------------------------------------------------------------------
+
+struct Lint<'a> {
+    content: &'a str,
+    /// Where the body starts in `content`; the rules' offsets are into the
+    /// body.
+    offset: usize,
+    findings: Vec<Finding>,
+}
+
+impl Lint<'_> {
+    fn push(&mut self, id: &str, start: usize, end: usize, message: impl Into<String>) {
+        self.push_fix(id, start, end, message, None);
+    }
+
+    fn push_fix(
+        &mut self,
+        id: &str,
+        start: usize,
+        end: usize,
+        message: impl Into<String>,
+        fix: Option<(&str, &str)>,
+    ) {
+        let offset = self.offset;
+        self.finding(id, offset + start, offset + end, message.into(), fix);
+    }
+
+    /// Record a finding at file offsets `start..end`.
+    fn finding(
+        &mut self,
+        id: &str,
+        start: usize,
+        end: usize,
+        message: String,
+        fix: Option<(&str, &str)>,
+    ) {
+        self.findings.push(Finding {
+            fix: fix.map(|(label, replacement)| Fix {
+                label: label.to_string(),
+                start,
+                end,
+                replacement: replacement.to_string(),
+            }),
+            ..finding(self.content, id, start, end, message)
+        });
+    }
+
+    // Title is optional - the website derives it from the filename if
+    // missing. Date is still required for sorting and display.
+    fn dates(&mut self, frontmatter: &Frontmatter) {
+        if !frontmatter.contains("date") {
+            // The opening `---`, or the top of the file.
+            let end = if self.offset > 0 { 3 } else { 0 };
+            self.finding("no-date", 0, end, "No date in the frontmatter".into(), None);
+        }
+    }
+
+    fn todos(&mut self, body: &str) {
+        for marker in ["TODO", "FIXME"] {
+            for (i, _) in body.match_indices(marker) {
+                self.push("todo", i, i + marker.len(), format!("{} left in", marker));
+            }
+        }
+    }
+
+    fn local_images(&mut self, body: &str) {
+        for pattern in ["](./", "](/attachments"] {
+            for (i, _) in body.match_indices(pattern) {
+                let end = link_end(body, i + pattern.len());
+                self.push("local-images", i, end, "Image points at a vault file");
+            }
+        }
+    }
+
+    /// Personal details that week notes shouldn't publish by accident.
+    fn privacy(&mut self, body: &str) {
+        use crate::patterns::{
+            EMAIL_ADDRESS, HEALTH_MEDICAL, MONEY_AMOUNT, PEOPLE_REFERENCE, PHONE_NUMBER, SSN,
+            STREET_ADDRESS,
+        };
+        let patterns = [
+            ("privacy/phone", &PHONE_NUMBER, "Phone number"),
+            ("privacy/email", &EMAIL_ADDRESS, "Email address"),
+            ("privacy/money", &MONEY_AMOUNT, "Financial amount"),
+            ("privacy/ssn", &SSN, "Possible SSN"),
+            ("privacy/address", &STREET_ADDRESS, "Street address"),
+            ("privacy/person", &PEOPLE_REFERENCE, "Named person"),
+            ("privacy/health", &HEALTH_MEDICAL, "Health/medical info"),
+        ];
+        for (id, pattern, message) in patterns {
+            for m in pattern.find_iter(body) {
+                self.push(id, m.start(), m.end(), message);
+            }
+        }
+    }
+
+    fn broken_links(&mut self, body: &str) {
+        let patterns = [
+            ("]()", "Empty link", None),
+            ("](#)", "Empty anchor", None),
+            ("](http)", "Incomplete URL", None),
+            (
+                "[[]]",
+                "Empty wikilink",
+                Some(("Remove the empty wikilink", "")),
+            ),
+        ];
+        for (pattern, message, fix) in patterns {
+            for (i, _) in body.match_indices(pattern) {
+                self.push_fix("broken-link", i, i + pattern.len(), message, fix);
+            }
+        }
+    }
+
+    fn alt_text(&mut self, body: &str) {
+        for caps in crate::patterns::MD_IMAGE_ALT_CHECK.captures_iter(body) {
+            let image = caps.get(0).expect("whole match");
+            let alt = caps.get(1).map(|m| m.as_str()).unwrap_or("");
+            if !crate::vault::is_junk_alt(alt) {
+                continue;
+            }
+            let message = match alt.trim() {
+                "" => "Image has no alt text".to_string(),
+                alt => format!("Alt text \"{}\" doesn't describe the image", alt),
+            };
+            self.push("missing-alt", image.start(), image.end(), message);
+        }
+    }
+
+    /// Images and video that may not have been uploaded. Anything on
+    /// Cloudinary is taken to be, and a note that links out over https is
+    /// given the benefit of the doubt for `<img>` and `<video>`.
+    fn local_media(&mut self, body: &str) {
+        let hosted = body.contains("cloudinary");
+        let patterns = [
+            ("![]()", "Empty image", true),
+            ("src=\"\"", "Empty image src", true),
+            ("src=''", "Empty image src", false),
+            (".png)", "Image may be a local file", false),
+            (".jpg)", "Image may be a local file", false),
+            (".jpeg)", "Image may be a local file", false),
+            (".gif)", "Image may be a local file", false),
+            (
+                "](attachments/",
+                "Image is in the attachments folder",
+                false,
+            ),
+            (
+                "](Attachments/",
+                "Image is in the attachments folder",
+                false,
+            ),
+            ("](assets/", "Image is in a local folder", false),
+            ("](images/", "Image is in a local folder", false),
+        ];
+        // A vault image is already `local-images`.
+        let local_images = self.findings.iter().any(|f| f.rule == "local-images");
+        let before = self.findings.len();
+        for (pattern, message, even_if_hosted) in patterns {
+            if local_images || (hosted && !even_if_hosted) {
+                continue;
+            }
+            for (i, _) in body.match_indices(pattern) {
+                self.push("local-media", i, i + pattern.len(), message);
+            }
+        }
+
+        let links_out = hosted || body.contains("https://");
+        if !links_out && self.findings.len() == before {
+            for (i, _) in body.match_indices("<img") {
+                self.push("local-media", i, i + 4, "<img> without a hosted src");
+            }
+        }
+        if !links_out {
+            for pattern in ["<video", ".mp4)", ".webm)"] {
+                for (i, _) in body.match_indices(pattern) {
+                    let message = "Video may be a local file";
+                    self.push("local-video", i, i + pattern.len(), message);
+                }
+            }
+        }
+    }
+
+    fn long_link_text(&mut self, body: &str, max_words: usize) {
+        for caps in crate::patterns::MD_LINK.captures_iter(body) {
+            if caps.get(1).is_some() {
+                continue;
+            }
+            let words = caps
+                .get(2)
+                .map_or(0, |m| m.as_str().split_whitespace().count());
+            if words > max_words {
+                let link = caps.get(0).expect("whole match");
+                let message = format!("Link text is {} words", words);
+                self.push("long-link-text", link.start(), link.end(), message);
+            }
+        }
+    }
+}
+
+/// The `)` closing a link whose destination starts at `from`, else the
+/// end of the line.
+fn link_end(body: &str, from: usize) -> usize {
+    let rest = &body[from..];
+    match rest.find([')', '\n']) {
+        Some(i) if rest[i..].starts_with(')') => from + i + 1,
+        Some(i) => from + i,
+        None => body.len(),
+    }
+}
+
+/// 1-based line and character column of byte `offset`.
+fn position(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTE: &str = "---\ntitle: Tea\n---\n# Tea\n\nTODO: steep times. ![](leaf.png)\n\nSee [[]] and [a link with far too many words](https://example.com).\n";
+
+    #[test]
+    fn findings_have_rule_ids_spans_and_fixes() {
+        let findings = check(NOTE);
+        let rules: Vec<(&str, usize, usize)> = findings
+            .iter()
+            .map(|f| (f.rule.as_str(), f.line, f.column))
+            .collect();
+        assert_eq!(
+            rules,
+            [
+                ("no-date", 1, 1),
+                ("todo", 6, 1),
+                ("missing-alt", 6, 20),
+                ("local-media", 6, 28),
+                ("broken-link", 8, 5),
+                ("long-link-text", 8, 14),
+            ]
+        );
+        let todo = &findings[1];
+        assert_eq!((todo.end_line, todo.end_column), (6, 5));
+        assert_eq!(
+            warnings(&findings),
+            [
+                "No date",
+                "Has TODOs",
+                "Broken link",
+                "Missing alt text",
+                "Local media",
+                "Long link text"
+            ]
+        );
+
+        let (fixed, count) = apply_fixes(NOTE, &findings);
+        assert_eq!(count, 1);
+        assert!(fixed.contains("See  and [a link"));
+    }
+
+    #[test]
+    fn config_relevels_rules_per_content_type() {
+        let mut config = LintConfig::default();
+        config.rules.insert("todo".into(), LintLevel::Error);
+        config.rules.insert("long-link-text".into(), LintLevel::Off);
+        config
+            .content_types
+            .insert("post".into(), [("privacy".into(), LintLevel::Info)].into());
+        let text = "---\ndate: 2026-01-01\n---\nTODO email jo@example.com\n";
+
+        let post = leveled(&check(text), "post", &config);
+        let levels: Vec<(&str, Severity)> =
+            post.iter().map(|f| (f.rule.as_str(), f.severity)).collect();
+        assert_eq!(
+            levels,
+            [("todo", Severity::Error), ("privacy/email", Severity::Info)]
+        );
+        // Info findings don't make the file list's warnings.
+        assert_eq!(warnings(&post), ["Has TODOs"]);
+        assert_eq!(
+            check_publish(&post, &config).unwrap_err(),
+            "Lint errors block publishing: TODO left in (line 4)"
+        );
+        config.block_publish = false;
+        assert!(check_publish(&post, &config).is_ok());
+
+        // Privacy rules are week notes' by default.
+        let none = LintConfig::default();
+        assert_eq!(leveled(&check(text), "post", &none).len(), 1);
+        assert_eq!(
+            warnings(&leveled(&check(text), "weeknote", &none)),
+            ["Has TODOs", "Email address detected"]
+        );
+    }
+
+    #[test]
+    fn privacy_rules() {
+        let privacy = |text: &str| -> Vec<String> {
+            let findings: Vec<Finding> = leveled(&check(text), "weeknote", &LintConfig::default())
+                .into_iter()
+                .filter(|f| f.rule.starts_with("privacy/"))
+                .collect();
+            warnings(&findings)
+        };
+
+        // Phone number
+        let warnings = privacy("Called them at 555-123-4567 today");
+        assert!(
+            warnings.iter().any(|w| w.contains("Phone")),
+            "Should detect phone: {:?}",
+            warnings
+        );
+
+        // Email
+        let warnings = privacy("Emailed john@example.com about the project");
+        assert!(
+            warnings.iter().any(|w| w.contains("Email")),
+            "Should detect email: {:?}",
+            warnings
+        );
+
+        // Money
+        let warnings = privacy("Got paid $12,500 for the project");
+        assert!(
+            warnings.iter().any(|w| w.contains("Financial")),
+            "Should detect money: {:?}",
+            warnings
+        );
+
+        // Named person
+        let warnings = privacy("Met with Sarah Johnson for coffee");
+        assert!(
+            warnings.iter().any(|w| w.contains("Named person")),
+            "Should detect person: {:?}",
+            warnings
+        );
+
+        // Health
+        let warnings = privacy("Had my therapy session on Tuesday");
+        assert!(
+            warnings.iter().any(|w| w.contains("Health")),
+            "Should detect health: {:?}",
+            warnings
+        );
+
+        // Clean content
+        let warnings = privacy("This week I worked on the website redesign and wrote some code.");
+        assert!(warnings.is_empty(), "Should be clean: {:?}", warnings);
+    }
+}
@@ -135,6 +135,34 @@ pub async fn upload(file_path: &str, folder: Option<&str>) -> MediaUploadResult
     }
 }
 
+/// `upload` for sync callers such as the publish transform. Runs on its own
+/// thread and runtime, so it's safe to call from inside an async task too.
+pub fn upload_blocking(file_path: &str, folder: Option<&str>) -> MediaUploadResult {
+    std::thread::scope(|s| {
+        s.spawn(|| {
+            match tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+            {
+                Ok(rt) => rt.block_on(upload(file_path, folder)),
+                Err(e) => MediaUploadResult {
+                    success: false,
+                    asset: None,
+                    mirrors: Vec::new(),
+                    error: Some(format!("Failed to start upload runtime: {}", e)),
+                },
+            }
+        })
+        .join()
+        .unwrap_or_else(|_| MediaUploadResult {
+            success: false,
+            asset: None,
+            mirrors: Vec::new(),
+            error: Some("Upload thread panicked".into()),
+        })
+    })
+}
+
 /// Cheap probe of both destinations (in parallel) so the Settings UI can
 /// render green/red dots and the user knows what's reachable before they drop.
 pub async fn status() -> MediaStatus {
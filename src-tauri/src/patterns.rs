@@ -162,6 +162,29 @@ pub static HEALTH_MEDICAL: LazyLock<Regex> = LazyLock::new(|| {
 // Git log parsing (journal.rs)
 // ---------------------------------------------------------------------------
 
-/// Matches git log lines: "TIMESTAMP Publish: SLUG"
-pub static GIT_PUBLISH_LOG: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r"^(\S+)\s+Publish:\s+(.+)$").expect("valid regex"));
+/// Matches batch publish commit subjects: "Publish: SLUG, SLUG". Single
+/// publishes follow the target's templates; see `commit_message::published_slugs`.
+pub static GIT_PUBLISH_SUBJECT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^Publish:\s+(.+)$").expect("valid regex"));
+
+// ---------------------------------------------------------------------------
+// Obsidian syntax (transform.rs)
+// ---------------------------------------------------------------------------
+
+/// Matches wiki-links and embeds: [[note]], [[note#heading|text]], ![[image.png]]
+/// Group 1 = "!" if embed, Group 2 = target, Group 3 = "#heading", Group 4 = alias
+pub static WIKI_LINK: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(!?)\[\[([^\[\]|#]*)(#[^\[\]|]*)?(?:\|([^\[\]]*))?\]\]").expect("valid regex")
+});
+
+/// Matches Obsidian comments: %%hidden%%, possibly spanning lines.
+pub static OBSIDIAN_COMMENT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)%%.*?%%").expect("valid regex"));
+
+/// Matches the opening line of a private callout: > [!private] Title
+pub static PRIVATE_CALLOUT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)^\s*>\s*\[!private\][+-]?").expect("valid regex"));
+
+/// Matches block reference ids at the end of a line: "text ^abc123"
+pub static BLOCK_ID: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m) \^[A-Za-z0-9-]+$").expect("valid regex"));
@@ -0,0 +1,258 @@
+//! A post's history in a target repo: every commit that changed its file,
+//! found by walking the repo's log across year folders and renames, with the
+//! journal's events for the post alongside.
+//!
+//! The repo is the record of what was live, so the history holds up when
+//! the journal database was lost or never saw a publish. The journal adds
+//! what git doesn't know, like deploy checks and which commit was a
+//! republish.
+
+use crate::config::PublishTarget;
+use crate::git::{FileRevision, Repo};
+use crate::journal::JournalEntry;
+use chrono::DateTime;
+use regex::Regex;
+use serde::Serialize;
+
+/// One entry on a post's timeline.
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryEntry {
+    /// RFC 3339: the commit's author time, or the event's when there's no
+    /// commit.
+    pub timestamp: String,
+    pub revision: Option<Revision>,
+    /// Journal events recorded against the commit. For an entry without
+    /// one, the single event it stands for.
+    pub events: Vec<JournalEntry>,
+}
+
+/// A commit that changed the post's file.
+#[derive(Debug, Clone, Serialize)]
+pub struct Revision {
+    pub sha: String,
+    pub message: String,
+    /// "added", "modified", "moved" or "deleted".
+    pub change: String,
+    /// Repo-relative path after the commit (before it, for a deletion).
+    pub path: String,
+    /// Set when the commit moved the file here.
+    pub moved_from: Option<String>,
+    /// Whether the post was live after the commit: filed where the content
+    /// pattern puts posts, and not `draft: true`.
+    pub live: bool,
+    /// Body words after the commit; 0 once the file is gone.
+    pub word_count: usize,
+    pub words_added: usize,
+    pub words_removed: usize,
+    pub lines_added: usize,
+    pub lines_removed: usize,
+}
+
+/// The post's timeline on a target, newest first.
+pub fn get_post_history(slug: &str, target_id: Option<&str>) -> Result<Vec<HistoryEntry>, String> {
+    let target = crate::config::resolve_target(target_id)?;
+    let events = crate::journal::entries_for_slug(slug).unwrap_or_else(|e| {
+        log::warn!("post_history: journal unavailable, using git alone: {}", e);
+        Vec::new()
+    });
+    Ok(merge(&target, revisions(&target, slug)?, events))
+}
+
+/// Commits to the post's file in the target repo, newest first.
+fn revisions(target: &PublishTarget, slug: &str) -> Result<Vec<HistoryEntry>, String> {
+    let pattern = |slug| {
+        crate::vault::post_path_regex(target, slug).ok_or_else(|| {
+            format!(
+                "Can't read content_path_pattern \"{}\"",
+                target.content_path_pattern
+            )
+        })
+    };
+    let (this_post, any_post) = (pattern(slug)?, pattern("{slug}")?);
+    let history = Repo::open(&target.repo_path)?.file_history(|path| this_post.is_match(path))?;
+    Ok(history
+        .into_iter()
+        .map(|file| HistoryEntry {
+            timestamp: file.author_time.clone(),
+            revision: Some(revision(file, &any_post)),
+            events: Vec::new(),
+        })
+        .collect())
+}
+
+fn revision(file: FileRevision, any_post: &Regex) -> Revision {
+    let change = match (&file.old_path, &file.new_path) {
+        (None, _) => "added",
+        (_, None) => "deleted",
+        (Some(old), Some(new)) if old != new => "moved",
+        _ => "modified",
+    };
+    let live = file
+        .new_path
+        .as_deref()
+        .is_some_and(|p| any_post.is_match(p))
+        && !file
+            .after
+            .as_deref()
+            .is_some_and(crate::vault::is_draft_content);
+    let before = file.before.as_deref().unwrap_or_default();
+    let after = file.after.as_deref().unwrap_or_default();
+    let (words_added, words_removed) = crate::publish_diff::word_delta(before, after);
+    Revision {
+        change: change.to_string(),
+        live,
+        word_count: crate::publish_diff::body_word_count(after),
+        words_added,
+        words_removed,
+        moved_from: file.old_path.clone().filter(|_| change == "moved"),
+        path: file.new_path.or(file.old_path).unwrap_or_default(),
+        sha: file.sha,
+        message: file.message,
+        lines_added: file.lines_added,
+        lines_removed: file.lines_removed,
+    }
+}
+
+/// Attach each journal event to the commit it recorded. Events without a
+/// commit in the history stand on their own, if they were for this target
+/// (an event with no target was for the default one).
+fn merge(
+    target: &PublishTarget,
+    mut history: Vec<HistoryEntry>,
+    events: Vec<JournalEntry>,
+) -> Vec<HistoryEntry> {
+    for event in events {
+        let commit = history.iter_mut().find(|entry| {
+            entry
+                .revision
+                .as_ref()
+                .is_some_and(|r| event.commit_sha.as_deref() == Some(r.sha.as_str()))
+        });
+        if let Some(entry) = commit {
+            entry.events.push(event);
+        } else if event
+            .target_id
+            .as_deref()
+            .map_or(target.is_default, |id| id == target.id)
+        {
+            history.push(HistoryEntry {
+                timestamp: event.timestamp.clone(),
+                revision: None,
+                events: vec![event],
+            });
+        }
+    }
+    history.sort_by_key(|entry| {
+        std::cmp::Reverse(DateTime::parse_from_rfc3339(&entry.timestamp).ok())
+    });
+    history
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config;
+    use crate::git::tests::remote_and_clone;
+    use std::fs;
+
+    fn event(event: &str, timestamp: &str, commit_sha: Option<&str>) -> JournalEntry {
+        JournalEntry {
+            id: 0,
+            timestamp: timestamp.into(),
+            event: event.into(),
+            slug: "new".into(),
+            title: None,
+            word_count: 0,
+            tags: String::new(),
+            content_type: "post".into(),
+            url: None,
+            target_id: None,
+            visibility: "public".into(),
+            commit_sha: commit_sha.map(String::from),
+            time_to_live_ms: None,
+            unpublish_strategy: None,
+        }
+    }
+
+    #[test]
+    fn history_follows_the_file_and_merges_the_journal() {
+        let (_remote, site) = remote_and_clone("history");
+        let mut target = config::AppConfig::default().publish_targets[0].clone();
+        target.repo_path = site.to_string_lossy().to_string();
+        let repo = Repo::open(&site).unwrap();
+        let commit = |path: &str, body: &str, message: &str| {
+            let abs = site.join(path);
+            fs::create_dir_all(abs.parent().unwrap()).unwrap();
+            fs::write(&abs, body).unwrap();
+            repo.add(&[path]).unwrap();
+            repo.commit(message).unwrap();
+            repo.head().unwrap()
+        };
+        let move_file = |from: &str, to: &str, message: &str| {
+            fs::create_dir_all(site.join(to).parent().unwrap()).unwrap();
+            fs::rename(site.join(from), site.join(to)).unwrap();
+            repo.add(&[from, to]).unwrap();
+            repo.commit(message).unwrap();
+        };
+
+        let text = "---\ntitle: Tea\n---\none two three four five six seven eight\n";
+        commit("content/blog/2024/old.md", text, "Publish: old");
+        move_file(
+            "content/blog/2024/old.md",
+            "content/blog/2025/new.md",
+            "Rename",
+        );
+        let edited = text.replace("eight", "eight nine ten");
+        let republished = commit("content/blog/2025/new.md", &edited, "Republish: new");
+        commit("content/blog/2025/other.md", "# other\n", "Publish: other");
+        move_file(
+            "content/blog/2025/new.md",
+            "content/drafts/new.md",
+            "Unpublish: new",
+        );
+
+        let history = revisions(&target, "new").unwrap();
+        let summary: Vec<(&str, &str, bool, usize)> = history
+            .iter()
+            .map(|h| {
+                let r = h.revision.as_ref().unwrap();
+                (r.message.as_str(), r.change.as_str(), r.live, r.word_count)
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("Unpublish: new", "moved", false, 10),
+                ("Republish: new", "modified", true, 10),
+                ("Rename", "moved", true, 8),
+                ("Publish: old", "added", true, 8),
+            ]
+        );
+        let edit = history[1].revision.as_ref().unwrap();
+        assert_eq!((edit.words_added, edit.words_removed), (10, 8));
+        assert_eq!((edit.lines_added, edit.lines_removed), (1, 1));
+        assert_eq!(
+            history[2].revision.as_ref().unwrap().moved_from.as_deref(),
+            Some("content/blog/2024/old.md")
+        );
+
+        let merged = merge(
+            &target,
+            history,
+            vec![
+                event("republish", "2000-01-01T00:00:00+00:00", Some(&republished)),
+                event("live", "2999-01-01T00:00:00+00:00", Some("elsewhere")),
+            ],
+        );
+        assert_eq!(merged.len(), 5);
+        assert_eq!(merged[0].events[0].event, "live");
+        assert!(merged[0].revision.is_none());
+        let with_event = merged
+            .iter()
+            .find(|h| !h.events.is_empty() && h.revision.is_some());
+        assert_eq!(
+            with_event.unwrap().revision.as_ref().unwrap().sha,
+            republished
+        );
+    }
+}
@@ -1,71 +1,38 @@
+use crate::commit_message::MessageFields;
 use crate::config;
+use crate::git::{CommitOutcome, GitError, PushOutcome, Repo};
+use crate::hooks::{HookEnv, HookRun};
 use std::fs;
 use std::path::Path;
-use std::process::Command;
 use tauri::Emitter;
 
-fn check_git_status(repo_path: &str) -> Result<(), String> {
-    // Check if we're in a git repo
-    let status = Command::new(crate::bin_paths::git())
-        .args(["rev-parse", "--git-dir"])
-        .current_dir(repo_path)
-        .output()
-        .map_err(|e| format!("Not a git repository: {}", e))?;
-
-    if !status.status.success() {
-        return Err("Not a git repository".into());
-    }
+/// Open the target repo and make sure it's fit to publish into: on a branch,
+/// no unresolved conflicts. Stray uncommitted files are only logged.
+pub(crate) fn check_git_status(repo_path: &str) -> Result<Repo, String> {
+    let repo = Repo::open(repo_path)?;
+    let status = repo.status()?;
 
     // Check for uncommitted changes - just log, don't block
-    let status = Command::new(crate::bin_paths::git())
-        .args(["status", "--porcelain"])
-        .current_dir(repo_path)
-        .output()
-        .map_err(|e| format!("Git status failed: {}", e))?;
-
-    let output = String::from_utf8_lossy(&status.stdout);
-    let dirty_files: Vec<&str> = output
-        .lines()
-        .filter(|l| !l.contains("content/blog/"))
-        .collect();
-
-    if !dirty_files.is_empty() {
+    let dirty_files = status
+        .entries
+        .iter()
+        .filter(|e| !e.path.contains("content/blog/"))
+        .count();
+    if dirty_files > 0 {
         log::warn!(
             "Note: {} uncommitted changes in repo (continuing anyway)",
-            dirty_files.len()
+            dirty_files
         );
     }
 
-    // Check if we're on a branch
-    let branch = Command::new(crate::bin_paths::git())
-        .args(["branch", "--show-current"])
-        .current_dir(repo_path)
-        .output()
-        .map_err(|e| format!("Git branch check failed: {}", e))?;
-
-    let branch_name = String::from_utf8_lossy(&branch.stdout).trim().to_string();
-    if branch_name.is_empty() {
+    if status.branch.is_none() {
         return Err("Detached HEAD state - please checkout a branch".into());
     }
-
-    // Check for merge conflicts
-    let conflicts = Command::new(crate::bin_paths::git())
-        .args(["diff", "--name-only", "--diff-filter=U"])
-        .current_dir(repo_path)
-        .output()
-        .map_err(|e| format!("Git conflict check failed: {}", e))?;
-
-    if !String::from_utf8_lossy(&conflicts.stdout).trim().is_empty() {
+    if !status.conflicts.is_empty() {
         return Err("Merge conflicts detected - please resolve before publishing".into());
     }
 
-    // Fetch to check if we're behind (non-blocking check)
-    let _ = Command::new(crate::bin_paths::git())
-        .args(["fetch", "--dry-run"])
-        .current_dir(repo_path)
-        .output();
-
-    Ok(())
+    Ok(repo)
 }
 
 #[derive(Debug, Clone, serde::Serialize)]
@@ -75,6 +42,11 @@ pub struct GitStatus {
     pub error: Option<String>,
     pub dirty_files: Vec<String>,
     pub has_conflicts: bool,
+    /// Publishes committed here but still waiting in the push queue.
+    pub pending_pushes: usize,
+    /// Queued publishes that stopped retrying (a conflict, auth...); they
+    /// need fixing in the repo, then "Retry now".
+    pub failed_pushes: usize,
 }
 
 pub fn get_git_status(target_id: Option<&str>) -> GitStatus {
@@ -87,42 +59,42 @@ pub fn get_git_status(target_id: Option<&str>) -> GitStatus {
                 error: Some(e),
                 dirty_files: vec![],
                 has_conflicts: false,
+                pending_pushes: 0,
+                failed_pushes: 0,
+            }
+        }
+    };
+    let status = match Repo::open(&target.repo_path).and_then(|r| r.status()) {
+        Ok(s) => s,
+        Err(e) => {
+            return GitStatus {
+                ok: false,
+                branch: String::new(),
+                error: Some(e.to_string()),
+                dirty_files: vec![],
+                has_conflicts: false,
+                pending_pushes: 0,
+                failed_pushes: 0,
             }
         }
     };
-    let repo_path = &target.repo_path;
-
-    // Get branch name
-    let branch = Command::new(crate::bin_paths::git())
-        .args(["branch", "--show-current"])
-        .current_dir(repo_path)
-        .output()
-        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
-        .unwrap_or_default();
-
-    // Check for dirty files
-    let status = Command::new(crate::bin_paths::git())
-        .args(["status", "--porcelain"])
-        .current_dir(repo_path)
-        .output();
 
+    let branch = status.branch.unwrap_or_default();
     let dirty_files: Vec<String> = status
-        .map(|o| {
-            String::from_utf8_lossy(&o.stdout)
-                .lines()
-                .filter(|l| !l.contains("content/blog/"))
-                .map(|l| l.to_string())
-                .collect()
-        })
-        .unwrap_or_default();
-
-    // Check for conflicts
-    let conflicts = Command::new(crate::bin_paths::git())
-        .args(["diff", "--name-only", "--diff-filter=U"])
-        .current_dir(repo_path)
-        .output()
-        .map(|o| !String::from_utf8_lossy(&o.stdout).trim().is_empty())
-        .unwrap_or(false);
+        .entries
+        .iter()
+        .filter(|e| !e.path.contains("content/blog/"))
+        .map(|e| e.to_string())
+        .collect();
+    let conflicts = !status.conflicts.is_empty();
+    let failed = crate::push_queue::get_failed(Some(&target.id)).unwrap_or_default();
+    let push_error = failed.last().map(|last| {
+        format!(
+            "{} can't push: {}",
+            last.slug,
+            last.last_error.as_deref().unwrap_or("unknown error")
+        )
+    });
 
     let error = if conflicts {
         Some("Merge conflicts - resolve before publishing".into())
@@ -131,7 +103,7 @@ pub fn get_git_status(target_id: Option<&str>) -> GitStatus {
     } else if branch.is_empty() {
         Some("Detached HEAD".into())
     } else {
-        None
+        push_error
     };
 
     GitStatus {
@@ -140,18 +112,66 @@ pub fn get_git_status(target_id: Option<&str>) -> GitStatus {
         error,
         dirty_files,
         has_conflicts: conflicts,
+        pending_pushes: crate::push_queue::pending_count(Some(&target.id)).unwrap_or(0),
+        failed_pushes: failed.len(),
     }
 }
 
-pub fn publish_file(
+/// Where a publish for `slug` lands in the target repo. Shared by
+/// `publish_file` and `plan_publish` so the dry run can't drift from the
+/// real thing.
+struct Destination {
+    dest_dir: String,
+    dest_path: String,
+    url: String,
+}
+
+fn resolve_destination(
+    target: &config::PublishTarget,
     source_path: &str,
     slug: &str,
-    target_id: Option<&str>,
-) -> Result<String, String> {
-    let app_config = config::get()?;
-    let target = config::resolve_target(target_id)?;
+) -> Destination {
+    let (year, month) = filing_date(target, source_path, slug);
+    let rel = target.content_path(&config::ContentPathFields {
+        year: &year,
+        month: &month,
+        slug,
+        content_type: crate::vault::content_type_for_path(source_path),
+    });
+    let dest_path = format!("{}/{}", target.repo_path, rel);
+    let dest_dir = Path::new(&dest_path)
+        .parent()
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|| target.repo_path.clone());
+    let url = target.post_url(Some(&year), Some(&month), slug);
+    Destination {
+        dest_dir,
+        dest_path,
+        url,
+    }
+}
+
+/// Year and month a post is filed under: its frontmatter `date`, else the
+/// folders it's already live in, else today.
+fn filing_date(target: &config::PublishTarget, source_path: &str, slug: &str) -> (String, String) {
+    if let Some(date) = crate::vault::post_date(source_path) {
+        return (date.format("%Y").to_string(), date.format("%m").to_string());
+    }
+    let now = chrono::Utc::now();
+    let this_month = now.format("%m").to_string();
+    if let Some((year, month)) = crate::vault::find_published_locations(target, slug)
+        .into_iter()
+        .find_map(|l| Some((l.year?, l.month)))
+    {
+        return (year, month.unwrap_or(this_month));
+    }
+    (now.format("%Y").to_string(), this_month)
+}
+
+/// Only files from the vault's blog/, drafts/ or week-notes/ may be published.
+fn check_source_path(vault_path: &str, source_path: &str) -> Result<(), String> {
     let normalized_path = source_path.replace('\\', "/");
-    if !normalized_path.starts_with(&app_config.vault.path)
+    if !normalized_path.starts_with(vault_path)
         || (!normalized_path.contains("/blog/")
             && !normalized_path.contains("/drafts/")
             && !normalized_path.contains("/week-notes/"))
@@ -160,265 +180,1913 @@ pub fn publish_file(
             "Publish blocked: file must live in vault blog/, drafts/, or week-notes/".into(),
         );
     }
+    Ok(())
+}
+
+/// The target's publish (or republish) message for writing `rendered` over
+/// whatever `dest_path` holds now.
+fn publish_commit_message(
+    target: &config::PublishTarget,
+    source_path: &str,
+    slug: &str,
+    dest_path: &str,
+    rendered: &str,
+) -> String {
+    let summary = crate::commit_message::summary(source_path);
+    let previous = fs::read_to_string(dest_path).ok();
+    let fields =
+        MessageFields::for_post(source_path, slug, &summary, previous.as_deref(), rendered);
+    crate::commit_message::publish(&target.commit, &fields)
+}
+
+/// The git steps `publish_file` takes, in order, rendered as the equivalent
+/// commands for display.
+fn publish_git_commands(dest_path: &str, commit_msg: &str) -> Vec<String> {
+    vec![
+        format!("git add {}", shell_quote(dest_path)),
+        format!("git commit -m {}", shell_quote(commit_msg)),
+        "git pull --rebase --autostash".into(),
+        "git push".into(),
+    ]
+}
+
+/// `publish_git_commands` for a pull-request target. Dispatch builds the
+/// commit without checking the branch out; these commands end the same way.
+fn branch_git_commands(rel_path: &str, commit_msg: &str, base: &str, branch: &str) -> Vec<String> {
+    vec![
+        "git fetch --prune origin".into(),
+        format!(
+            "git switch -C {} origin/{}",
+            shell_quote(branch),
+            shell_quote(base)
+        ),
+        format!("git add {}", shell_quote(rel_path)),
+        format!("git commit -m {}", shell_quote(commit_msg)),
+        format!("git push origin {}", shell_quote(branch)),
+        format!("git switch {}", shell_quote(base)),
+    ]
+}
+
+fn shell_quote(arg: &str) -> String {
+    if !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:@".contains(c))
+    {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+/// Everything `publish_file` would do, computed without copying a file or
+/// running a mutating git command. Feeds the publish confirm modal and lets
+/// scripts check a publish without side effects.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PublishPlan {
+    pub slug: String,
+    pub target_id: String,
+    pub source_path: String,
+    /// Absolute path the vault file would be copied to.
+    pub dest_path: String,
+    /// Whether the destination folder (e.g. `content/blog/2026`) already exists.
+    pub year_dir_exists: bool,
+    /// Whether `dest_path` already exists and would be overwritten.
+    pub overwrites_existing: bool,
+    /// Where the slug is currently live, if anywhere — may be a different
+    /// year folder than `dest_path`.
+    pub existing_published_url: Option<String>,
+    pub is_republish: bool,
+    pub commit_message: String,
+    /// Pull-request targets: the review branch the commit would go to.
+    pub branch: Option<String>,
+    pub git_commands: Vec<String>,
+    pub url: String,
+    /// The file list's warnings for the source file.
+    pub warnings: Vec<String>,
+    /// The lint findings behind them, at their configured levels.
+    pub lint: Vec<crate::lint::Finding>,
+    /// Set when a pre-flight check would stop the publish.
+    pub blocked_reason: Option<String>,
+}
+
+pub fn plan_publish(
+    source_path: &str,
+    slug: &str,
+    target_id: Option<&str>,
+) -> Result<PublishPlan, String> {
+    let app_config = config::get()?;
+    let target = config::resolve_target(target_id)?;
+    let dest = resolve_destination(&target, source_path, slug);
+    let rendered = fs::read_to_string(source_path)
+        .map(|content| crate::transform::preview(&content, &target, &app_config.vault))
+        .unwrap_or_default();
+    let commit_message =
+        publish_commit_message(&target, source_path, slug, &dest.dest_path, &rendered);
+
+    let lint = crate::lint::lint_file(source_path, &app_config.lint)?;
+    let blocked_reason = check_source_path(&app_config.vault.path, source_path)
+        .and_then(|_| crate::lint::check_publish(&lint, &app_config.lint))
+        .and_then(|_| check_git_status(&target.repo_path).map(|_| ()))
+        .and_then(|_| crate::collisions::check_publish(&target, slug, &dest.dest_path))
+        .err();
+
+    let (existing_published_url, _, _) =
+        crate::vault::find_published_info_for_target(&target, slug);
+    let warnings = crate::lint::warnings(&lint);
+    let branch = (target.mode == config::PublishMode::PullRequest).then(|| review_branch(slug));
+    let git_commands = match &branch {
+        Some(branch) => branch_git_commands(
+            relative_dest(&target, &dest.dest_path),
+            &commit_message,
+            &target.branch,
+            branch,
+        ),
+        None => publish_git_commands(&dest.dest_path, &commit_message),
+    };
+
+    Ok(PublishPlan {
+        slug: slug.to_string(),
+        target_id: target.id.clone(),
+        source_path: source_path.to_string(),
+        year_dir_exists: Path::new(&dest.dest_dir).is_dir(),
+        overwrites_existing: Path::new(&dest.dest_path).exists(),
+        is_republish: existing_published_url.is_some(),
+        existing_published_url,
+        git_commands,
+        commit_message,
+        branch,
+        url: dest.url,
+        dest_path: dest.dest_path,
+        warnings,
+        lint,
+        blocked_reason,
+    })
+}
+
+/// Result of a publish: the live URL and the commit that put it there.
+/// `commit_sha` is `None` when the file was identical to what's live and
+/// there was nothing to commit.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Published {
+    pub url: String,
+    pub commit_sha: Option<String>,
+    /// Pre- then post-publish hook runs, in order.
+    pub hooks: Vec<HookRun>,
+    /// Set when the commit was made but couldn't be pushed: why not. The
+    /// commit is in the push queue and isn't live yet (and `commit_sha` is
+    /// the local one, which the eventual pull may rebase).
+    pub pending_push: Option<String>,
+    /// Pull-request targets: the review branch the commit went to. The post
+    /// isn't live until `merge_publish_branch`.
+    pub branch: Option<String>,
+    /// Where to open the pull request, when the remote is a known host.
+    pub compare_url: Option<String>,
+}
+
+pub fn publish_file(
+    source_path: &str,
+    slug: &str,
+    target_id: Option<&str>,
+) -> Result<Published, String> {
+    let app_config = config::get()?;
+    let target = config::resolve_target(target_id)?;
+    check_source_path(&app_config.vault.path, source_path)?;
+    crate::lint::check_publish_file(source_path, &app_config.lint)?;
+    let is_republish = crate::vault::find_published_info_for_target(&target, slug)
+        .0
+        .is_some();
+    let published = publish_to_target(&target, &app_config.vault, source_path, slug)?;
+    if let Some(error) = &published.pending_push {
+        let commit_sha = published.commit_sha.as_deref();
+        queue_push(
+            &target,
+            source_path,
+            slug,
+            &published.url,
+            commit_sha,
+            is_republish,
+            error,
+        );
+    }
+    Ok(published)
+}
+
+/// Hand a publish that committed but couldn't push to the push queue.
+fn queue_push(
+    target: &config::PublishTarget,
+    source_path: &str,
+    slug: &str,
+    url: &str,
+    commit_sha: Option<&str>,
+    is_republish: bool,
+    error: &str,
+) {
+    let Some(commit_sha) = commit_sha else {
+        return;
+    };
+    let queued = crate::push_queue::enqueue(&crate::push_queue::NewPendingPush {
+        kind: "publish".into(),
+        target_id: target.id.clone(),
+        repo_path: target.repo_path.clone(),
+        commit_sha: commit_sha.to_string(),
+        source_path: source_path.to_string(),
+        slug: slug.to_string(),
+        url: url.to_string(),
+        dest_path: resolve_destination(target, source_path, slug).dest_path,
+        is_republish,
+        error: error.to_string(),
+    });
+    if let Err(e) = queued {
+        log::warn!("Failed to queue the push for {}: {}", slug, e);
+    }
+}
+
+/// Hand a revert, unpublish or rename commit that couldn't push to the push
+/// queue. Unlike a publish, it's journaled right away by the caller.
+pub(crate) fn queue_change(
+    target: &config::PublishTarget,
+    kind: &str,
+    slug: &str,
+    url: &str,
+    commit_sha: &str,
+    error: &str,
+) {
+    let queued = crate::push_queue::enqueue(&crate::push_queue::NewPendingPush {
+        kind: kind.to_string(),
+        target_id: target.id.clone(),
+        repo_path: target.repo_path.clone(),
+        commit_sha: commit_sha.to_string(),
+        source_path: String::new(),
+        slug: slug.to_string(),
+        url: url.to_string(),
+        dest_path: String::new(),
+        is_republish: false,
+        error: error.to_string(),
+    });
+    if let Err(e) = queued {
+        log::warn!("Failed to queue the {} push for {}: {}", kind, slug, e);
+    }
+}
+
+/// `publish_file` once the source has been vetted against the vault config.
+fn publish_to_target(
+    target: &config::PublishTarget,
+    vault: &config::VaultConfig,
+    source_path: &str,
+    slug: &str,
+) -> Result<Published, String> {
+    let _lock = crate::publish_lock::acquire(&target.repo_path, &format!("publish {}", slug))?;
 
     // Pre-flight checks
     log::warn!("Running pre-flight checks...");
-    check_git_status(&target.repo_path)?;
+    let repo = check_git_status(&target.repo_path)?;
 
-    // Determine year folder from content_path_pattern
-    let year = chrono::Utc::now().format("%Y").to_string();
-    let content_dir = target.content_path_pattern.replace("{year}", &year);
-    let dest_dir = format!("{}/{}", target.repo_path, content_dir);
-    let dest_path = format!("{}/{}.md", dest_dir, slug);
+    let Destination {
+        dest_dir,
+        dest_path,
+        url,
+    } = resolve_destination(target, source_path, slug);
+    crate::collisions::check_publish(target, slug, &dest_path)?;
+    let rendered = render_source(target, vault, source_path)?;
+    let hook_env = HookEnv {
+        target_id: &target.id,
+        slug,
+        url: &url,
+        source_path,
+        dest_path: &dest_path,
+        commit_sha: None,
+    };
+    let mut hooks = crate::hooks::pre_publish(target, &hook_env)?;
+    let message = publish_commit_message(target, source_path, slug, &dest_path, &rendered);
+
+    if target.mode == config::PublishMode::PullRequest {
+        return publish_to_branch(target, &repo, slug, &dest_path, url, &rendered, &message)
+            .map(|published| Published { hooks, ..published });
+    }
 
     log::warn!("Publishing {} -> {}", source_path, dest_path);
 
+    let start_head = repo.head();
+    let written = [Written {
+        dest_path: dest_path.clone(),
+        previous: fs::read(&dest_path).ok(),
+    }];
+
     // Ensure year directory exists
     fs::create_dir_all(&dest_dir).map_err(|e| format!("Failed to create dir: {}", e))?;
 
-    // Copy file
-    fs::copy(source_path, &dest_path).map_err(|e| format!("Failed to copy: {}", e))?;
+    fs::write(&dest_path, rendered).map_err(|e| format!("Failed to write: {}", e))?;
 
-    log::warn!("Copied file, running git commands...");
+    log::warn!("Wrote file, committing...");
 
-    // Git add, commit, push
-    let repo_path = &target.repo_path;
+    repo.add(&[dest_path.as_str()])?;
+    // Nothing to commit is fine - a republish of an unchanged file
+    let who = crate::commit_message::authorship(&target.commit);
+    let committed = match repo.commit_as(&message, &who)? {
+        CommitOutcome::Created(_) => true,
+        CommitOutcome::NothingToCommit => {
+            log::warn!("Nothing changed for {}", slug);
+            false
+        }
+    };
 
-    let add_output = Command::new(crate::bin_paths::git())
-        .args(["add", &dest_path])
-        .current_dir(repo_path)
-        .output()
-        .map_err(|e| format!("Git add failed: {}", e))?;
+    let pending_push = if committed {
+        let pending = push_commit(&repo, start_head.as_deref(), &written)?;
+        if let Some(e) = &pending {
+            log::warn!("Committed {} but couldn't push, queueing: {}", slug, e);
+        }
+        pending
+    } else {
+        pull_and_push(&repo)?;
+        None
+    };
 
-    if !add_output.status.success() {
-        return Err(format!(
-            "Git add failed: {}",
-            String::from_utf8_lossy(&add_output.stderr)
+    // HEAD is read after the rebase so the SHA is the one that's on the remote.
+    let commit_sha = if committed { repo.head() } else { None };
+
+    if pending_push.is_none() {
+        log::warn!("Published successfully!");
+        hooks.extend(crate::hooks::post_publish(
+            target,
+            &HookEnv {
+                commit_sha: commit_sha.as_deref(),
+                ..hook_env
+            },
         ));
     }
 
-    let commit_msg = format!("Publish: {}", slug);
-    let commit_output = Command::new(crate::bin_paths::git())
-        .args(["commit", "-m", &commit_msg])
-        .current_dir(repo_path)
-        .output()
-        .map_err(|e| format!("Git commit failed: {}", e))?;
+    // Return the URL using configured domain
+    Ok(Published {
+        url,
+        commit_sha,
+        hooks,
+        pending_push,
+        branch: None,
+        compare_url: None,
+    })
+}
+
+/// The review branch a pull-request publish of `slug` goes to.
+fn review_branch(slug: &str) -> String {
+    format!("dispatch/{}", slug)
+}
+
+/// `dest_path` relative to the target's repo.
+fn relative_dest<'a>(target: &config::PublishTarget, dest_path: &'a str) -> &'a str {
+    dest_path
+        .strip_prefix(target.repo_path.as_str())
+        .unwrap_or(dest_path)
+        .trim_start_matches('/')
+}
+
+/// Pull-request mode: commit the rendered post to its review branch and push
+/// that. The checked-out branch and the work tree aren't touched; post-publish
+/// hooks wait for the merge.
+fn publish_to_branch(
+    target: &config::PublishTarget,
+    repo: &Repo,
+    slug: &str,
+    dest_path: &str,
+    url: String,
+    rendered: &str,
+    message: &str,
+) -> Result<Published, String> {
+    let branch = review_branch(slug);
+    let local_branch = format!("refs/heads/{}", branch);
+    let remote_branch = format!("origin/{}", branch);
+    let base = format!("origin/{}", target.branch);
 
-    // Commit might "fail" if nothing changed - that's okay for republish
-    if !commit_output.status.success() {
-        let stderr = String::from_utf8_lossy(&commit_output.stderr);
-        let stdout = String::from_utf8_lossy(&commit_output.stdout);
-        // Check if it's just "nothing to commit"
-        if !stdout.contains("nothing to commit") && !stderr.contains("nothing to commit") {
-            log::warn!("Git commit output: {}", stdout);
-            log::warn!("Git commit stderr: {}", stderr);
-            // Continue anyway - file was still copied
+    log::warn!("Fetching latest changes...");
+    repo.fetch()?;
+    if repo.resolve(&base).is_none() {
+        return Err(format!("The remote has no {} branch", target.branch));
+    }
+    // Keep adding to an open review branch while it's still based on the
+    // latest target branch; once that has moved on, start the branch over
+    // from it so the merge can fast-forward.
+    let open = repo
+        .resolve(&remote_branch)
+        .or_else(|| repo.resolve(&local_branch));
+    let parent = match open {
+        Some(tip) if repo.contains(&tip, &base) => tip,
+        _ => base,
+    };
+
+    log::warn!("Committing {} to {}", dest_path, branch);
+    let commit_sha = match repo.commit_file_to_branch(
+        &branch,
+        &parent,
+        relative_dest(target, dest_path),
+        rendered.as_bytes(),
+        message,
+        &crate::commit_message::authorship(&target.commit),
+    )? {
+        CommitOutcome::Created(sha) => Some(sha),
+        CommitOutcome::NothingToCommit => {
+            log::warn!("Nothing changed for {}", slug);
+            None
         }
+    };
+    if commit_sha.is_none() && !repo.contains(&local_branch, &parent) {
+        // Identical to what's already on the target branch: nothing to review.
+        return Ok(Published {
+            url,
+            commit_sha: None,
+            hooks: Vec::new(),
+            pending_push: None,
+            branch: None,
+            compare_url: None,
+        });
     }
 
-    log::warn!("Pulling latest changes...");
+    let force = repo
+        .resolve(&remote_branch)
+        .is_some_and(|tip| !repo.contains(&local_branch, &tip));
+    log::warn!("Pushing {}...", branch);
+    repo.push_branch(&branch, force)?;
 
-    let pull_output = Command::new(crate::bin_paths::git())
-        .args(["pull", "--rebase", "--autostash"])
-        .current_dir(repo_path)
-        .output()
-        .map_err(|e| format!("Git pull failed: {}", e))?;
+    let compare_url = repo
+        .remote_web_url()
+        .map(|web| compare_url(&web, &target.branch, &branch));
+    Ok(Published {
+        url,
+        commit_sha,
+        hooks: Vec::new(),
+        pending_push: None,
+        branch: Some(branch),
+        compare_url,
+    })
+}
 
-    if !pull_output.status.success() {
-        let stderr = String::from_utf8_lossy(&pull_output.stderr);
-        let stdout = String::from_utf8_lossy(&pull_output.stdout);
-        log::warn!("Git pull stdout: {}", stdout);
-        log::warn!("Git pull stderr: {}", stderr);
+/// The page that opens a pull request for `branch` against `base`. GitLab
+/// has its own path; GitHub's is shared by Gitea and Forgejo.
+fn compare_url(web_url: &str, base: &str, branch: &str) -> String {
+    if web_url.contains("gitlab") {
+        format!("{}/-/compare/{}...{}", web_url, base, branch)
+    } else {
+        format!("{}/compare/{}...{}?expand=1", web_url, base, branch)
+    }
+}
 
-        // If pull failed, try to abort any in-progress rebase
-        let _ = Command::new(crate::bin_paths::git())
-            .args(["rebase", "--abort"])
-            .current_dir(repo_path)
-            .output();
+/// Put a pull-request publish live: fast-forward its review branch into the
+/// target branch, push, and run the post-publish hooks. Fine if the branch
+/// was already merged on the host. The review branch is deleted afterwards.
+pub fn merge_publish_branch(
+    source_path: &str,
+    slug: &str,
+    target_id: Option<&str>,
+) -> Result<Published, String> {
+    let target = config::resolve_target(target_id)?;
+    merge_branch(&target, source_path, slug)
+}
 
-        return Err(format!("Git pull failed: {}\n{}", stdout, stderr));
+fn merge_branch(
+    target: &config::PublishTarget,
+    source_path: &str,
+    slug: &str,
+) -> Result<Published, String> {
+    let _lock = crate::publish_lock::acquire(&target.repo_path, &format!("merge {}", slug))?;
+    let repo = check_git_status(&target.repo_path)?;
+    if repo.branch().as_deref() != Some(target.branch.as_str()) {
+        return Err(format!(
+            "{} has {} checked out, not {}",
+            target.repo_path,
+            repo.branch().unwrap_or_default(),
+            target.branch
+        ));
     }
+    let branch = review_branch(slug);
+
+    log::warn!("Fetching latest changes...");
+    repo.fetch()?;
+    repo.pull_rebase()?;
+    let remote_tip = repo.resolve(&format!("origin/{}", branch));
+    let tip = remote_tip
+        .clone()
+        .or_else(|| repo.resolve(&format!("refs/heads/{}", branch)))
+        .ok_or_else(|| format!("There's no {} branch to merge", branch))?;
+
+    log::warn!("Fast-forwarding {} to {}", target.branch, branch);
+    repo.fast_forward(&tip)?;
+    push(&repo)?;
+
+    if let Err(e) = repo.delete_branch(&branch) {
+        log::warn!("Failed to delete {}: {}", branch, e);
+    }
+    if remote_tip.is_some() {
+        if let Err(e) = repo.delete_remote_branch(&branch) {
+            log::warn!("Failed to delete {} on the remote: {}", branch, e);
+        }
+    }
+
+    let Destination { dest_path, url, .. } = resolve_destination(target, source_path, slug);
+    let hooks = crate::hooks::post_publish(
+        target,
+        &HookEnv {
+            target_id: &target.id,
+            slug,
+            url: &url,
+            source_path,
+            dest_path: &dest_path,
+            commit_sha: Some(&tip),
+        },
+    );
+    Ok(Published {
+        url,
+        commit_sha: Some(tip),
+        hooks,
+        pending_push: None,
+        branch: Some(branch),
+        compare_url: None,
+    })
+}
+
+pub(crate) fn pull_and_push(repo: &Repo) -> Result<(), GitError> {
+    log::warn!("Pulling latest changes...");
+    repo.pull_rebase()?;
 
     log::warn!("Pushing to remote...");
+    push(repo)
+}
 
-    let push_output = Command::new(crate::bin_paths::git())
-        .args(["push"])
-        .current_dir(repo_path)
-        .output()
-        .map_err(|e| format!("Git push failed: {}", e))?;
+/// `pull_and_push` for a commit Dispatch just made. Offline or a refused
+/// push leaves the commit for the push queue: that error comes back as
+/// `Ok(Some(_))`. Any other failure takes the commit back out and restores
+/// `written`, so the repo isn't left holding a commit nothing records.
+pub(crate) fn push_commit(
+    repo: &Repo,
+    start_head: Option<&str>,
+    written: &[Written],
+) -> Result<Option<String>, String> {
+    log::warn!("Pulling latest changes...");
+    match repo.pull_rebase() {
+        Ok(()) => {}
+        Err(e) if e.is_transient() => return Ok(Some(e.to_string())),
+        // The rebase was aborted, so the commit still sits on `start_head`.
+        Err(e) => {
+            rollback(repo, start_head, written);
+            return Err(e.into());
+        }
+    }
 
-    if !push_output.status.success() {
-        let stderr = String::from_utf8_lossy(&push_output.stderr);
-        // Check if it's "everything up-to-date" which is fine
-        if !stderr.contains("Everything up-to-date") && !stderr.contains("up to date") {
-            return Err(format!("Git push failed: {}", stderr));
+    log::warn!("Pushing to remote...");
+    match push(repo) {
+        Ok(()) => Ok(None),
+        Err(e) if e.is_transient() => Ok(Some(e.to_string())),
+        // The rebase moved the commit onto the remote's tip; drop just it.
+        Err(e) => {
+            let parent = repo.resolve("HEAD~1");
+            rollback(repo, parent.as_deref(), written);
+            Err(e.into())
         }
     }
+}
 
-    log::warn!("Published successfully!");
+/// Read a vault note and run the target's transforms over it, uploading any
+/// embedded attachments on the way.
+fn render_source(
+    target: &config::PublishTarget,
+    vault: &config::VaultConfig,
+    source_path: &str,
+) -> Result<String, String> {
+    let content =
+        fs::read_to_string(source_path).map_err(|e| format!("Failed to read source: {}", e))?;
+    crate::transform::apply(
+        &content,
+        &crate::transform::Context {
+            target,
+            vault,
+            upload: Some(&upload_attachment),
+        },
+    )
+}
 
-    // Return the URL using configured domain
-    let url = format!("{}/blog/{}/{}", target.domain, year, slug);
-    Ok(url)
+fn upload_attachment(path: &Path) -> Result<String, String> {
+    let result = crate::media::upload_blocking(&path.to_string_lossy(), Some("blog"));
+    match result.asset {
+        Some(asset) if result.success => Ok(asset.url),
+        _ => Err(result.error.unwrap_or_else(|| "Upload failed".into())),
+    }
 }
 
-pub fn unpublish_file(slug: &str, target_id: Option<&str>) -> Result<(), String> {
-    let target = config::resolve_target(target_id)?;
+/// Push, treating "already up to date" as success.
+fn push(repo: &Repo) -> Result<(), GitError> {
+    if repo.push()? == PushOutcome::UpToDate {
+        log::warn!("Remote already up to date");
+    }
+    Ok(())
+}
 
-    // Pre-flight checks
-    log::warn!("Running pre-flight checks...");
-    check_git_status(&target.repo_path)?;
-
-    // Derive blog path from content_path_pattern base
-    let content_base = target
-        .content_path_pattern
-        .split("/{year}")
-        .next()
-        .unwrap_or("content/blog");
-    let blog_path = format!("{}/{}", target.repo_path, content_base);
-    let drafts_path = format!("{}/content/drafts", target.repo_path);
-    fs::create_dir_all(&drafts_path).map_err(|e| format!("Failed to create drafts dir: {}", e))?;
-
-    let mut source_path: Option<String> = None;
-    if let Ok(entries) = fs::read_dir(&blog_path) {
-        for entry in entries.flatten() {
-            if entry.path().is_dir() {
-                let dir_name = entry.file_name().to_string_lossy().to_string();
-                if dir_name.len() != 4 || !dir_name.chars().all(|c| c.is_ascii_digit()) {
-                    continue;
-                }
-                let file_path = format!("{}/{}/{}.md", blog_path, dir_name, slug);
-                if Path::new(&file_path).exists() {
-                    source_path = Some(file_path);
-                    break;
-                }
-            }
+/// One target's result from `publish_file_to_targets`.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TargetOutcome {
+    pub target_id: String,
+    pub target_name: String,
+    pub success: bool,
+    pub url: Option<String>,
+    pub commit_sha: Option<String>,
+    pub is_republish: bool,
+    pub error: Option<String>,
+    pub hooks: Vec<HookRun>,
+    /// Committed but waiting in the push queue; see `Published::pending_push`.
+    pub pending_push: Option<String>,
+    /// Pull-request targets; see `Published::branch`.
+    pub branch: Option<String>,
+    pub compare_url: Option<String>,
+}
+
+/// Publish one file to several targets. An empty `target_ids` means the
+/// post's frontmatter `targets:`, else the default target. Each target is
+/// its own repo, so one failing doesn't stop the rest — check `success` on
+/// every outcome.
+pub fn publish_file_to_targets(
+    source_path: &str,
+    slug: &str,
+    target_ids: &[String],
+) -> Result<Vec<TargetOutcome>, String> {
+    let app_config = config::get()?;
+    check_source_path(&app_config.vault.path, source_path)?;
+    crate::lint::check_publish_file(source_path, &app_config.lint)?;
+    let targets = resolve_targets(source_path, target_ids)?;
+    let outcomes = publish_each(&targets, &app_config.vault, source_path, slug);
+    for (target, o) in targets.iter().zip(&outcomes) {
+        if let (Some(error), Some(url)) = (&o.pending_push, &o.url) {
+            let commit_sha = o.commit_sha.as_deref();
+            queue_push(
+                target,
+                source_path,
+                slug,
+                url,
+                commit_sha,
+                o.is_republish,
+                error,
+            );
         }
     }
+    Ok(outcomes)
+}
 
-    let source_path = source_path.ok_or_else(|| "Published file not found".to_string())?;
-    let dest_path = format!("{}/{}.md", drafts_path, slug);
-    if Path::new(&dest_path).exists() {
-        return Err(format!("Draft already exists: {}", dest_path));
+/// The targets a publish of `source_path` goes to. Unknown ids are an
+/// error up front, before anything is published anywhere.
+pub fn resolve_targets(
+    source_path: &str,
+    target_ids: &[String],
+) -> Result<Vec<config::PublishTarget>, String> {
+    let mut ids = target_ids.to_vec();
+    if ids.is_empty() {
+        ids = crate::vault::post_targets(source_path);
+    }
+    if ids.is_empty() {
+        return Ok(vec![config::default_target()?]);
     }
+    let mut targets: Vec<config::PublishTarget> = Vec::new();
+    for id in ids {
+        if !targets.iter().any(|t| t.id == id) {
+            targets.push(config::resolve_target(Some(&id))?);
+        }
+    }
+    Ok(targets)
+}
 
-    log::warn!("Unpublishing {} -> {}", source_path, dest_path);
-    fs::rename(&source_path, &dest_path).map_err(|e| format!("Failed to move file: {}", e))?;
+fn publish_each(
+    targets: &[config::PublishTarget],
+    vault: &config::VaultConfig,
+    source_path: &str,
+    slug: &str,
+) -> Vec<TargetOutcome> {
+    targets
+        .iter()
+        .map(|target| {
+            let is_republish = crate::vault::find_published_info_for_target(target, slug)
+                .0
+                .is_some();
+            let result = publish_to_target(target, vault, source_path, slug);
+            if let Err(e) = &result {
+                log::warn!("Publish of {} to {} failed: {}", slug, target.id, e);
+            }
+            let (published, error) = match result {
+                Ok(p) => (Some(p), None),
+                Err(e) => (None, Some(e)),
+            };
+            TargetOutcome {
+                target_id: target.id.clone(),
+                target_name: target.name.clone(),
+                success: published.is_some(),
+                url: published.as_ref().map(|p| p.url.clone()),
+                commit_sha: published.as_ref().and_then(|p| p.commit_sha.clone()),
+                is_republish,
+                error,
+                pending_push: published.as_ref().and_then(|p| p.pending_push.clone()),
+                branch: published.as_ref().and_then(|p| p.branch.clone()),
+                compare_url: published.as_ref().and_then(|p| p.compare_url.clone()),
+                hooks: published.map(|p| p.hooks).unwrap_or_default(),
+            }
+        })
+        .collect()
+}
 
-    // Git add, commit, push
-    let repo_path = &target.repo_path;
-    let add_output = Command::new(crate::bin_paths::git())
-        .args(["add", "-A", &source_path, &dest_path])
-        .current_dir(repo_path)
-        .output()
-        .map_err(|e| format!("Git add failed: {}", e))?;
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct BatchItem {
+    pub source_path: String,
+    pub slug: String,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct BatchPublished {
+    pub source_path: String,
+    pub slug: String,
+    pub url: String,
+    pub is_republish: bool,
+    /// Shared by every post in the batch.
+    pub commit_sha: Option<String>,
+    pub hooks: Vec<HookRun>,
+    /// Committed but waiting in the push queue; see `Published::pending_push`.
+    pub pending_push: Option<String>,
+}
+
+/// A file a publish wrote into the repo, and what to put back on rollback.
+pub(crate) struct Written {
+    pub(crate) dest_path: String,
+    /// Previous contents if the file already existed (republish).
+    pub(crate) previous: Option<Vec<u8>>,
+}
+
+/// Undo everything a publish did to the working tree and index: unstage the
+/// files, restore overwritten ones, delete new ones, and (if a commit was
+/// made) move HEAD back to `start_head`.
+pub(crate) fn rollback(repo: &Repo, start_head: Option<&str>, written: &[Written]) {
+    if let Some(head) = start_head {
+        if repo.head().as_deref() != Some(head) {
+            let _ = repo.reset_soft(head);
+        }
+    }
+    for w in written {
+        let _ = repo.unstage(&[w.dest_path.as_str()]);
+        match &w.previous {
+            Some(bytes) => {
+                let _ = fs::write(&w.dest_path, bytes);
+            }
+            None => {
+                let _ = fs::remove_file(&w.dest_path);
+            }
+        }
+    }
+    log::warn!("Publish rolled back ({} files)", written.len());
+}
+
+/// Publish several vault files as one commit and one push. Every file is
+/// validated before anything is copied; if a copy, the commit or a
+/// conflicting pull fails, the repo is put back the way it was found. A pull
+/// or push that can't reach the remote leaves the commit for the push queue.
+pub fn publish_batch(
+    items: &[BatchItem],
+    target_id: Option<&str>,
+) -> Result<Vec<BatchPublished>, String> {
+    if items.is_empty() {
+        return Err("Nothing to publish".into());
+    }
+    let app_config = config::get()?;
+    let target = config::resolve_target(target_id)?;
+    for item in items {
+        check_source_path(&app_config.vault.path, &item.source_path)
+            .and_then(|_| crate::lint::check_publish_file(&item.source_path, &app_config.lint))
+            .map_err(|e| format!("{}: {}", item.slug, e))?;
+    }
+    let published = publish_batch_to_target(&target, &app_config.vault, items)?;
+    for p in &published {
+        if let Some(error) = &p.pending_push {
+            let commit_sha = p.commit_sha.as_deref();
+            queue_push(
+                &target,
+                &p.source_path,
+                &p.slug,
+                &p.url,
+                commit_sha,
+                p.is_republish,
+                error,
+            );
+        }
+    }
+    Ok(published)
+}
 
-    if !add_output.status.success() {
+/// `publish_batch` once every source has been vetted against the vault config.
+fn publish_batch_to_target(
+    target: &config::PublishTarget,
+    vault: &config::VaultConfig,
+    items: &[BatchItem],
+) -> Result<Vec<BatchPublished>, String> {
+    if target.mode == config::PublishMode::PullRequest {
         return Err(format!(
-            "Git add failed: {}",
-            String::from_utf8_lossy(&add_output.stderr)
+            "{} publishes through pull requests, one post per branch; publish these one at a time",
+            target.name
         ));
     }
+    let _lock =
+        crate::publish_lock::acquire(&target.repo_path, &format!("publish {} posts", items.len()))?;
+
+    // Pre-flight checks — all of them, before touching the repo.
+    log::warn!("Running pre-flight checks for {} posts...", items.len());
+    let repo = check_git_status(&target.repo_path)?;
+    let mut rendered = Vec::with_capacity(items.len());
+    for (i, item) in items.iter().enumerate() {
+        if !Path::new(&item.source_path).exists() {
+            return Err(format!("{}: source file not found", item.slug));
+        }
+        if items[..i].iter().any(|other| other.slug == item.slug) {
+            return Err(format!("{}: listed twice in the batch", item.slug));
+        }
+        let dest = resolve_destination(target, &item.source_path, &item.slug);
+        crate::collisions::check_publish(target, &item.slug, &dest.dest_path)?;
+        rendered.push(
+            render_source(target, vault, &item.source_path)
+                .map_err(|e| format!("{}: {}", item.slug, e))?,
+        );
+    }
+    // Hooks last, so none run for a batch that was going to be refused.
+    let mut hooks = Vec::with_capacity(items.len());
+    for item in items {
+        let dest = resolve_destination(target, &item.source_path, &item.slug);
+        hooks.push(
+            crate::hooks::pre_publish(target, &batch_hook_env(target, item, &dest))
+                .map_err(|e| format!("{}: {}", item.slug, e))?,
+        );
+    }
+
+    let start_head = repo.head();
+
+    let mut written: Vec<Written> = Vec::new();
+    let mut published: Vec<BatchPublished> = Vec::new();
 
-    let commit_msg = format!("Unpublish: {}", slug);
-    let commit_output = Command::new(crate::bin_paths::git())
-        .args(["commit", "-m", &commit_msg])
-        .current_dir(repo_path)
-        .output()
-        .map_err(|e| format!("Git commit failed: {}", e))?;
+    for ((item, content), hooks) in items.iter().zip(rendered).zip(hooks) {
+        let dest = resolve_destination(target, &item.source_path, &item.slug);
+        let is_republish = crate::vault::find_published_info_for_target(target, &item.slug)
+            .0
+            .is_some();
+        let previous = fs::read(&dest.dest_path).ok();
 
-    if !commit_output.status.success() {
-        let stderr = String::from_utf8_lossy(&commit_output.stderr);
-        let stdout = String::from_utf8_lossy(&commit_output.stdout);
-        if !stdout.contains("nothing to commit") && !stderr.contains("nothing to commit") {
-            log::warn!("Git commit output: {}", stdout);
-            log::warn!("Git commit stderr: {}", stderr);
+        log::warn!("Publishing {} -> {}", item.source_path, dest.dest_path);
+        let copied = fs::create_dir_all(&dest.dest_dir)
+            .map_err(|e| format!("Failed to create dir: {}", e))
+            .and_then(|_| {
+                fs::write(&dest.dest_path, content)
+                    .map_err(|e| format!("Failed to write {}: {}", item.slug, e))
+            });
+        if let Err(e) = copied {
+            rollback(&repo, None, &written);
+            return Err(e);
         }
+        written.push(Written {
+            dest_path: dest.dest_path.clone(),
+            previous,
+        });
+
+        if let Err(e) = repo.add(&[dest.dest_path.as_str()]) {
+            rollback(&repo, None, &written);
+            return Err(e.into());
+        }
+
+        published.push(BatchPublished {
+            source_path: item.source_path.clone(),
+            slug: item.slug.clone(),
+            url: dest.url,
+            is_republish,
+            commit_sha: None,
+            hooks,
+            pending_push: None,
+        });
     }
 
-    log::warn!("Pulling latest changes...");
-    let pull_output = Command::new(crate::bin_paths::git())
-        .args(["pull", "--rebase", "--autostash"])
-        .current_dir(repo_path)
-        .output()
-        .map_err(|e| format!("Git pull failed: {}", e))?;
-
-    if !pull_output.status.success() {
-        let stderr = String::from_utf8_lossy(&pull_output.stderr);
-        let stdout = String::from_utf8_lossy(&pull_output.stdout);
-        log::warn!("Git pull stdout: {}", stdout);
-        log::warn!("Git pull stderr: {}", stderr);
-        let _ = Command::new(crate::bin_paths::git())
-            .args(["rebase", "--abort"])
-            .current_dir(repo_path)
-            .output();
-        return Err(format!("Git pull failed: {}\n{}", stdout, stderr));
+    let slugs: Vec<&str> = items.iter().map(|i| i.slug.as_str()).collect();
+    let commit_msg = crate::commit_message::batch(&target.commit, &slugs);
+    let who = crate::commit_message::authorship(&target.commit);
+    let committed = match repo.commit_as(&commit_msg, &who) {
+        Ok(CommitOutcome::Created(_)) => true,
+        // Every file identical to what's live — fine, nothing to push.
+        Ok(CommitOutcome::NothingToCommit) => {
+            log::warn!("Batch publish: nothing changed");
+            false
+        }
+        Err(e) => {
+            rollback(&repo, start_head.as_deref(), &written);
+            return Err(e.into());
+        }
+    };
+
+    if committed {
+        let pending_push = push_commit(&repo, start_head.as_deref(), &written)?;
+        match &pending_push {
+            None => log::warn!("Batch published {} posts", published.len()),
+            Some(e) => log::warn!("Batch committed but couldn't push, queueing: {}", e),
+        }
+        let commit_sha = repo.head();
+        for p in &mut published {
+            p.commit_sha = commit_sha.clone();
+            p.pending_push = pending_push.clone();
+        }
     }
 
-    log::warn!("Pushing to remote...");
-    let push_output = Command::new(crate::bin_paths::git())
-        .args(["push"])
-        .current_dir(repo_path)
-        .output()
-        .map_err(|e| format!("Git push failed: {}", e))?;
+    for (p, item) in published.iter_mut().zip(items) {
+        if p.pending_push.is_some() {
+            continue;
+        }
+        let dest = resolve_destination(target, &item.source_path, &item.slug);
+        let env = HookEnv {
+            commit_sha: p.commit_sha.as_deref(),
+            ..batch_hook_env(target, item, &dest)
+        };
+        let post = crate::hooks::post_publish(target, &env);
+        p.hooks.extend(post);
+    }
+    Ok(published)
+}
+
+fn batch_hook_env<'a>(
+    target: &'a config::PublishTarget,
+    item: &'a BatchItem,
+    dest: &'a Destination,
+) -> HookEnv<'a> {
+    HookEnv {
+        target_id: &target.id,
+        slug: &item.slug,
+        url: &dest.url,
+        source_path: &item.source_path,
+        dest_path: &dest.dest_path,
+        commit_sha: None,
+    }
+}
+
+/// Result of a revert: the revert commit, and whether it's still waiting in
+/// the push queue.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Reverted {
+    pub commit_sha: String,
+    /// See `Published::pending_push`.
+    pub pending_push: Option<String>,
+}
+
+/// Undo a publish by reverting the commit it produced and pushing the revert.
+/// Unlike `unpublish_file` this restores whatever the repo had before (the
+/// previous version on a republish, nothing on a first publish) and never
+/// touches the vault. `slug` and `url` label a queued push.
+pub fn revert_publish(
+    commit_sha: &str,
+    target_id: Option<&str>,
+    slug: &str,
+    url: &str,
+) -> Result<Reverted, String> {
+    let target = config::resolve_target(target_id)?;
+    let reverted = revert_in_target(&target, commit_sha)?;
+    if let Some(error) = &reverted.pending_push {
+        queue_change(&target, "revert", slug, url, &reverted.commit_sha, error);
+    }
+    Ok(reverted)
+}
+
+fn revert_in_target(target: &config::PublishTarget, commit_sha: &str) -> Result<Reverted, String> {
+    let repo_path = &target.repo_path;
+    let _lock = crate::publish_lock::acquire(repo_path, &format!("revert {}", commit_sha))?;
 
-    if !push_output.status.success() {
-        let stderr = String::from_utf8_lossy(&push_output.stderr);
-        if !stderr.contains("Everything up-to-date") && !stderr.contains("up to date") {
-            return Err(format!("Git push failed: {}", stderr));
+    log::warn!("Running pre-flight checks...");
+    let repo = check_git_status(repo_path)?;
+
+    // The revert only touches what the commit did, and refuses if any of it
+    // has local edits, so the files as they are now are what rollback restores.
+    let start_head = repo.head();
+    let written: Vec<Written> = repo
+        .commit_paths(commit_sha)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|path| Written {
+            previous: fs::read(&path).ok(),
+            dest_path: path.to_string_lossy().to_string(),
+        })
+        .collect();
+
+    log::warn!("Reverting {}...", commit_sha);
+    let who = crate::commit_message::authorship(&target.commit);
+    let reverted = repo.revert_as(commit_sha, &who, |message| {
+        crate::commit_message::revert(&target.commit, message)
+    });
+    match reverted {
+        Ok(CommitOutcome::Created(_)) => {}
+        Ok(CommitOutcome::NothingToCommit) => {
+            return Err("Already reverted — the commit's changes are no longer in the repo".into());
+        }
+        Err(GitError::UnknownRevision(_)) => {
+            return Err(format!(
+                "Commit {} is not in {} — was the history rewritten?",
+                commit_sha, repo_path
+            ));
+        }
+        Err(GitError::Conflict(paths)) => {
+            return Err(format!(
+                "Git revert failed (later commits touch the same file?): {}",
+                paths.join(", ")
+            ));
         }
+        Err(e) => return Err(e.into()),
+    }
+
+    let pending_push = push_commit(&repo, start_head.as_deref(), &written)?;
+    match &pending_push {
+        Some(e) => log::warn!("Reverted {} but couldn't push, queueing: {}", commit_sha, e),
+        None => log::warn!("Reverted {}", commit_sha),
+    }
+    let commit_sha = repo
+        .head()
+        .ok_or_else(|| "Reverted, but could not read the new HEAD".to_string())?;
+    Ok(Reverted {
+        commit_sha,
+        pending_push,
+    })
+}
+
+/// Result of an unpublish: how the post came down, and the commit that did
+/// it (`None` when there was nothing to commit).
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Unpublished {
+    pub strategy: config::UnpublishStrategy,
+    /// Where the post was live.
+    pub url: String,
+    pub commit_sha: Option<String>,
+    /// See `Published::pending_push`.
+    pub pending_push: Option<String>,
+}
+
+pub fn unpublish_file(slug: &str, target_id: Option<&str>) -> Result<Unpublished, String> {
+    let target = config::resolve_target(target_id)?;
+    let unpublished = unpublish_from_target(&target, slug)?;
+    if let (Some(error), Some(commit_sha)) = (&unpublished.pending_push, &unpublished.commit_sha) {
+        queue_change(
+            &target,
+            "unpublish",
+            slug,
+            &unpublished.url,
+            commit_sha,
+            error,
+        );
     }
+    Ok(unpublished)
+}
+
+/// Take a live post down the way `target.unpublish.strategy` says. Every
+/// file touched is put back if anything fails before the commit; after it,
+/// the commit is rolled back or queued like a publish's.
+fn unpublish_from_target(
+    target: &config::PublishTarget,
+    slug: &str,
+) -> Result<Unpublished, String> {
+    let _lock = crate::publish_lock::acquire(&target.repo_path, &format!("unpublish {}", slug))?;
+
+    // Pre-flight checks
+    log::warn!("Running pre-flight checks...");
+    let repo = check_git_status(&target.repo_path)?;
+
+    let live = crate::vault::find_published_locations(target, slug)
+        .into_iter()
+        .find(|l| !crate::vault::is_drafted(&l.path))
+        .ok_or_else(|| "Published file not found".to_string())?;
+    let source_path = live.path.to_string_lossy().to_string();
+    let url = target.post_url(live.year.as_deref(), live.month.as_deref(), slug);
+
+    let start_head = repo.head();
+    let mut written = vec![Written {
+        previous: fs::read(&source_path).ok(),
+        dest_path: source_path.clone(),
+    }];
+    let strategy = target.unpublish.strategy;
+    let taken_down = take_down(target, &live, slug, &url, &mut written);
+    let committed = taken_down.and_then(|_| {
+        let touched: Vec<&str> = written.iter().map(|w| w.dest_path.as_str()).collect();
+        repo.add(&touched)?;
+        let message = crate::commit_message::unpublish(&target.commit, slug);
+        let who = crate::commit_message::authorship(&target.commit);
+        Ok(repo.commit_as(&message, &who)? != CommitOutcome::NothingToCommit)
+    });
+    let committed = committed.inspect_err(|_| rollback(&repo, start_head.as_deref(), &written))?;
+
+    let pending_push = if committed {
+        let pending = push_commit(&repo, start_head.as_deref(), &written)?;
+        if let Some(e) = &pending {
+            log::warn!("Unpublished {} but couldn't push, queueing: {}", slug, e);
+        }
+        pending
+    } else {
+        log::warn!("Nothing to commit for unpublish of {}", slug);
+        pull_and_push(&repo)?;
+        None
+    };
 
     log::warn!("Unpublished successfully!");
+    Ok(Unpublished {
+        strategy,
+        url,
+        commit_sha: if committed { repo.head() } else { None },
+        pending_push,
+    })
+}
+
+/// The working-tree half of an unpublish. Each file it changes or creates is
+/// added to `written` before (or, for a move's destination, as soon as) it's
+/// touched, so a failure part way can be undone.
+fn take_down(
+    target: &config::PublishTarget,
+    live: &crate::vault::PublishedLocation,
+    slug: &str,
+    url: &str,
+    written: &mut Vec<Written>,
+) -> Result<(), String> {
+    let source_path = live.path.to_string_lossy().to_string();
+    match target.unpublish.strategy {
+        config::UnpublishStrategy::MoveToDrafts => {
+            let drafts_path = format!("{}/content/drafts", target.repo_path);
+            written.push(move_out(&source_path, &drafts_path, slug)?);
+        }
+        config::UnpublishStrategy::Archive => {
+            let day = chrono::Local::now().format("%Y-%m-%d");
+            let archive_dir = target.unpublish.archive_dir.trim_matches('/');
+            let archive_path = format!("{}/{}/{}", target.repo_path, archive_dir, day);
+            written.push(move_out(&source_path, &archive_path, slug)?);
+        }
+        strategy @ (config::UnpublishStrategy::MarkDraft
+        | config::UnpublishStrategy::MarkUnlisted) => {
+            let key = match strategy {
+                config::UnpublishStrategy::MarkDraft => "draft",
+                _ => "unlisted",
+            };
+            log::warn!("Unpublishing {}: setting {}: true", source_path, key);
+            crate::vault::set_frontmatter_field(&source_path, key, "true")?;
+        }
+        config::UnpublishStrategy::Delete => {
+            // Record the 410 first: a target that can't say the post is gone
+            // keeps it rather than serving a bare 404.
+            if let Some(path) = crate::redirects::file(target) {
+                written.push(Written {
+                    previous: fs::read(&path).ok(),
+                    dest_path: path.to_string_lossy().to_string(),
+                });
+            }
+            crate::redirects::gone(target, crate::redirects::site_path(target, url))?;
+            log::warn!("Unpublishing {}: deleting it", source_path);
+            fs::remove_file(&source_path).map_err(|e| format!("Failed to delete file: {}", e))?;
+        }
+    }
     Ok(())
 }
 
+/// Move a live post into `dir` as `{slug}.md`, refusing to overwrite one
+/// already there. Returns where it went, as a new file rollback removes.
+fn move_out(source_path: &str, dir: &str, slug: &str) -> Result<Written, String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir, e))?;
+    let dest_path = format!("{}/{}.md", dir, slug);
+    if Path::new(&dest_path).exists() {
+        return Err(format!("Already exists: {}", dest_path));
+    }
+
+    log::warn!("Unpublishing {} -> {}", source_path, dest_path);
+    fs::rename(source_path, &dest_path).map_err(|e| format!("Failed to move file: {}", e))?;
+    Ok(Written {
+        dest_path,
+        previous: None,
+    })
+}
+
+/// A post's `unpublish_at` has passed: take it down from every target it's
+/// live on. The field is cleared once they all succeed; a target that fails
+/// keeps it live and is retried on the next tick. A post that isn't live
+/// anywhere is left alone while a `publish_at` is still pending.
+fn scheduled_unpublish(app_handle: &tauri::AppHandle, file: &crate::MarkdownFile) {
+    let slug = file.filename.trim_end_matches(".md");
+    let Ok(app_config) = config::get() else {
+        return;
+    };
+    let live: Vec<&config::PublishTarget> = app_config
+        .publish_targets
+        .iter()
+        .filter(|t| {
+            crate::vault::find_published_locations(t, slug)
+                .iter()
+                .any(|l| !crate::vault::is_drafted(&l.path))
+        })
+        .collect();
+    if live.is_empty() && file.publish_at.is_some() {
+        return;
+    }
+
+    log::warn!("Scheduled unpublish triggered for: {}", slug);
+    let mut unpublished = Vec::new();
+    let mut failed = false;
+    for target in live {
+        match unpublish_file(slug, Some(target.id.as_str())) {
+            Ok(done) => {
+                crate::record_unpublish_event(slug, Some(target.id.as_str()), &done);
+                unpublished.push(target.id.clone());
+            }
+            Err(e) => {
+                log::warn!(
+                    "Scheduled unpublish of {} from {} failed: {}",
+                    slug,
+                    target.name,
+                    e
+                );
+                failed = true;
+            }
+        }
+    }
+    if !failed {
+        let _ = crate::vault::remove_frontmatter_field(&file.path, "unpublish_at");
+    }
+    if !unpublished.is_empty() {
+        let _ = app_handle.emit(
+            "scheduled-unpublish",
+            serde_json::json!({
+                "slug": slug,
+                "title": file.title,
+                "targets": unpublished
+            }),
+        );
+        std::thread::spawn(crate::sketchybar_cache::update);
+    }
+}
+
+/// A post's `publish_at` has passed and it isn't live yet: publish it to
+/// its targets. False if it went out nowhere; the UI is told why.
+fn scheduled_publish(app_handle: &tauri::AppHandle, file: &crate::MarkdownFile) -> bool {
+    let slug = file.filename.trim_end_matches(".md");
+    log::warn!("Scheduled publish triggered for: {}", slug);
+    let failed = |error: String| {
+        log::warn!("Scheduled publish failed for {}: {}", slug, error);
+        let _ = app_handle.emit(
+            "scheduled-publish-failed",
+            serde_json::json!({
+                "slug": slug,
+                "title": file.title,
+                "error": error
+            }),
+        );
+        false
+    };
+    let outcomes = match publish_file_to_targets(&file.path, slug, &[]) {
+        Ok(outcomes) => outcomes,
+        Err(e) => return failed(e),
+    };
+    let live: Vec<&TargetOutcome> = outcomes.iter().filter(|o| o.success).collect();
+    // Out on at least one target: it's published. Failed targets are logged
+    // and can be republished by hand.
+    let Some(first) = live.first() else {
+        let errors: Vec<String> = outcomes
+            .iter()
+            .map(|o| {
+                format!(
+                    "{}: {}",
+                    o.target_name,
+                    o.error.as_deref().unwrap_or_default()
+                )
+            })
+            .collect();
+        return failed(errors.join("; "));
+    };
+    // Queued pushes and review branches count as published here, but they're
+    // journaled once they land or are merged.
+    for o in live
+        .iter()
+        .filter(|o| o.pending_push.is_none() && o.branch.is_none())
+    {
+        crate::record_publish_event(
+            &file.path,
+            slug,
+            o.url.as_deref().unwrap_or_default(),
+            o.is_republish,
+            Some(o.target_id.as_str()),
+            o.commit_sha.as_deref(),
+        );
+        if let Ok(target) = config::resolve_target(Some(&o.target_id)) {
+            crate::deploy::watch(
+                app_handle,
+                &target,
+                &file.path,
+                slug,
+                o.url.as_deref().unwrap_or_default(),
+                o.commit_sha.as_deref(),
+            );
+        }
+    }
+    // Remove publish_at from frontmatter
+    let _ = crate::vault::remove_frontmatter_field(&file.path, "publish_at");
+    // Emit event to frontend
+    let _ = app_handle.emit(
+        "scheduled-publish",
+        serde_json::json!({
+            "slug": slug,
+            "url": first.url,
+            "title": file.title,
+            "targets": outcomes
+        }),
+    );
+    log::warn!(
+        "Scheduled publish succeeded on {}/{} targets",
+        live.len(),
+        outcomes.len()
+    );
+    true
+}
+
+/// The checker sleeps until the next scheduled time, but no longer than
+/// this, so a laptop that slept through one catches up soon after waking.
+const MAX_SCHEDULE_WAIT_SECS: u64 = 300;
+
+/// How often to retry a post whose time has passed but didn't go through
+/// (a failed unpublish, a target that's down).
+const SCHEDULE_RETRY_SECS: u64 = 60;
+
 pub async fn run_schedule_checker(app_handle: tauri::AppHandle) {
-    use tokio::time::{interval, Duration};
-    let mut ticker = interval(Duration::from_secs(60));
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use tokio::time::{sleep, Duration};
+
+    if let Err(e) = crate::schedule_index::rebuild() {
+        log::warn!("Couldn't index scheduled posts: {}", e);
+    }
+
+    // Scheduled publishes that failed, by the note's modified time then. An
+    // unreachable remote already leaves the commit queued, so what's left
+    // (a lint block, a slug collision, a dirty repo) needs a person: the
+    // note isn't tried again until it's saved again.
+    let mut stuck: HashMap<PathBuf, u64> = HashMap::new();
 
     loop {
-        ticker.tick().await;
-        if let Ok(files) = crate::vault::get_recent_files(500) {
-            let now = chrono::Utc::now();
-            for file in files {
-                if let Some(ref publish_at_str) = file.publish_at {
-                    // Skip already-published files
-                    if file.published_url.is_some() {
-                        continue;
-                    }
-                    // Try parsing the scheduled datetime
-                    if let Ok(scheduled_time) = chrono::DateTime::parse_from_rfc3339(publish_at_str)
-                    {
-                        if now >= scheduled_time {
-                            let slug = file.filename.trim_end_matches(".md");
-                            log::warn!("Scheduled publish triggered for: {}", slug);
-                            match publish_file(&file.path, slug, None) {
-                                Ok(url) => {
-                                    // Remove publish_at from frontmatter
-                                    let _ = crate::vault::remove_frontmatter_field(
-                                        &file.path,
-                                        "publish_at",
-                                    );
-                                    // Emit event to frontend
-                                    let _ = app_handle.emit(
-                                        "scheduled-publish",
-                                        serde_json::json!({
-                                            "slug": slug,
-                                            "url": url,
-                                            "title": file.title
-                                        }),
-                                    );
-                                    log::warn!("Scheduled publish succeeded: {}", url);
-                                }
-                                Err(e) => {
-                                    log::warn!("Scheduled publish failed for {}: {}", slug, e);
-                                }
-                            }
-                        }
+        let now = chrono::Utc::now();
+        let (due, _) = crate::schedule_index::due(now);
+        for (path, times) in due {
+            let file = match crate::vault::get_file(&path.to_string_lossy()) {
+                Ok(file) => file,
+                Err(e) => {
+                    log::warn!("Skipping scheduled {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            if times.unpublish_at.is_some_and(|at| now >= at) {
+                scheduled_unpublish(&app_handle, &file);
+            }
+            if times.publish_at.is_some_and(|at| now >= at) {
+                if file.published_url.is_some() {
+                    // Published by hand in the meantime: nothing left to do.
+                    let _ = crate::vault::remove_frontmatter_field(&file.path, "publish_at");
+                } else if stuck.get(&path) != Some(&file.modified) {
+                    if scheduled_publish(&app_handle, &file) {
+                        stuck.remove(&path);
+                    } else {
+                        stuck.insert(path.clone(), file.modified);
                     }
                 }
             }
+            // Don't wait on the watcher to see what that changed.
+            crate::schedule_index::refresh(&[path]);
+        }
+
+        let now = chrono::Utc::now();
+        let (due, next) = crate::schedule_index::due(now);
+        stuck.retain(|path, _| due.iter().any(|(p, _)| p == path));
+        let mut wait = next
+            .and_then(|at| (at.to_utc() - now).to_std().ok())
+            .map_or(MAX_SCHEDULE_WAIT_SECS, |d| d.as_secs() + 1)
+            .min(MAX_SCHEDULE_WAIT_SECS);
+        if due.iter().any(|(path, _)| !stuck.contains_key(path)) {
+            wait = wait.min(SCHEDULE_RETRY_SECS);
+        }
+        tokio::select! {
+            _ = sleep(Duration::from_secs(wait)) => {}
+            _ = crate::schedule_index::changed() => {}
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::tests::{
+        push_from_elsewhere, remote_and_clone, scratch, set_origin, UNREACHABLE_URL,
+    };
+    use std::path::PathBuf;
+
+    fn target_for(site: &Path) -> config::PublishTarget {
+        config::PublishTarget {
+            name: "Test".into(),
+            id: "test".into(),
+            repo_path: site.to_str().unwrap().into(),
+            domain: "https://example.com".into(),
+            content_path_pattern: "content/blog/{year}".into(),
+            url_pattern: String::new(),
+            branch: "main".into(),
+            is_default: true,
+            mode: config::PublishMode::Direct,
+            transforms: config::TransformConfig::default(),
+            hooks: config::PublishHooks::default(),
+            verify: config::DeployVerify::default(),
+            commit: config::CommitConfig::default(),
+            redirects: config::RedirectsConfig::default(),
+            unpublish: config::UnpublishConfig::default(),
+        }
+    }
+
+    fn remote_main(remote: &Path) -> String {
+        git2::Repository::open_bare(remote)
+            .unwrap()
+            .refname_to_id("refs/heads/main")
+            .unwrap()
+            .to_string()
+    }
+
+    fn vault() -> config::VaultConfig {
+        config::VaultConfig {
+            path: scratch("vault").to_string_lossy().to_string(),
+            ..config::AppConfig::default().vault
+        }
+    }
+
+    fn vault_post(vault: &config::VaultConfig, slug: &str, body: &str) -> PathBuf {
+        let path = Path::new(&vault.path).join(format!("{}.md", slug));
+        fs::write(&path, body).unwrap();
+        path
+    }
+
+    #[test]
+    fn publish_lands_on_the_remote() {
+        let vault = vault();
+        let (remote, site) = remote_and_clone("publish");
+        let target = target_for(&site);
+        let source = vault_post(&vault, "hello", "# hello\n%%not for the site%%\n");
+
+        let published =
+            publish_to_target(&target, &vault, source.to_str().unwrap(), "hello").unwrap();
+        let sha = published.commit_sha.expect("a publish commit");
+        assert_eq!(remote_main(&remote), sha);
+        assert!(published.url.starts_with("https://example.com/blog/"));
+        let dest = resolve_destination(&target, source.to_str().unwrap(), "hello").dest_path;
+        assert_eq!(fs::read_to_string(dest).unwrap(), "# hello\n\n");
+
+        // Republishing an unchanged file is fine and makes no commit.
+        let again = publish_to_target(&target, &vault, source.to_str().unwrap(), "hello").unwrap();
+        assert_eq!(again.commit_sha, None);
+        assert_eq!(remote_main(&remote), sha);
+    }
+
+    #[test]
+    fn republish_commit_uses_the_templates_and_author() {
+        let vault = vault();
+        let (_remote, site) = remote_and_clone("template");
+        let mut target = target_for(&site);
+        target.commit.author = Some(config::GitIdentity {
+            name: "Dispatch".into(),
+            email: "dispatch@example.com".into(),
+        });
+        let source = vault_post(&vault, "tpl", "# tpl\none two\n");
+        let source = source.to_str().unwrap();
+        let head = |site: &Path| {
+            let repo = git2::Repository::open(site).unwrap();
+            let commit = repo.head().unwrap().peel_to_commit().unwrap();
+            let author = commit.author().name().unwrap().to_string();
+            (commit.summary().unwrap().to_string(), author)
+        };
+
+        publish_to_target(&target, &vault, source, "tpl").unwrap();
+        assert_eq!(head(&site), ("Publish: tpl".into(), "Dispatch".into()));
+
+        fs::write(source, "# tpl\none two three four\n").unwrap();
+        publish_to_target(&target, &vault, source, "tpl").unwrap();
+        assert_eq!(head(&site).0, "Republish: tpl (+2 words)");
+    }
+
+    #[test]
+    fn unpublish_follows_the_target_strategy() {
+        let vault = vault();
+        use config::UnpublishStrategy::*;
+        let (remote, site) = remote_and_clone("unpublish");
+        let mut target = target_for(&site);
+        let source = vault_post(&vault, "gone", "---\ndate: 2025-03-01\n---\n# gone\n");
+        let source = source.to_str().unwrap();
+        let live = site.join("content/blog/2025/gone.md");
+        let unpublish = |target: &mut config::PublishTarget, strategy| {
+            target.unpublish.strategy = strategy;
+            publish_to_target(target, &vault, source, "gone").unwrap();
+            let done = unpublish_from_target(target, "gone")?;
+            assert_eq!(done.strategy, strategy);
+            assert_eq!(done.commit_sha, Some(remote_main(&remote)));
+            Ok::<_, String>(())
+        };
+
+        unpublish(&mut target, MoveToDrafts).unwrap();
+        assert!(!live.exists());
+        assert!(site.join("content/drafts/gone.md").exists());
+
+        unpublish(&mut target, MarkDraft).unwrap();
+        assert!(fs::read_to_string(&live).unwrap().contains("draft: true"));
+        let info = crate::vault::find_published_info_for_target(&target, "gone");
+        assert_eq!(info.0, None);
+        let err = unpublish_from_target(&target, "gone").unwrap_err();
+        assert_eq!(err, "Published file not found");
+
+        unpublish(&mut target, Archive).unwrap();
+        assert!(!live.exists());
+        let day = chrono::Local::now().format("%Y-%m-%d");
+        assert!(site
+            .join(format!("content/archive/{}/gone.md", day))
+            .exists());
+
+        // Deleting needs somewhere to say the URL is gone.
+        target.redirects.format = config::RedirectFormat::Vercel;
+        let err = unpublish(&mut target, Delete).unwrap_err();
+        assert!(err.contains("410"), "{}", err);
+        assert!(live.exists());
+        target.redirects.format = config::RedirectFormat::Netlify;
+        unpublish(&mut target, Delete).unwrap();
+        assert!(!live.exists());
+        assert_eq!(
+            fs::read_to_string(site.join("_redirects")).unwrap(),
+            "/blog/2025/gone  /404.html  410\n"
+        );
+
+        // A push that can't succeed puts the post and the redirects back.
+        let repo = Repo::open(&site).unwrap();
+        fs::write(site.join("_redirects"), "/old  /new  301\n").unwrap();
+        repo.add(&["_redirects"]).unwrap();
+        repo.commit("Redirect /old").unwrap();
+        publish_to_target(&target, &vault, source, "gone").unwrap();
+        let before = repo.head();
+        set_origin(&site, site.with_file_name("gone.git").to_str().unwrap());
+        for strategy in [Delete, MoveToDrafts, MarkDraft] {
+            target.unpublish.strategy = strategy;
+            assert!(unpublish_from_target(&target, "gone").is_err());
+            assert_eq!(repo.head(), before);
+            assert!(repo.status().unwrap().entries.is_empty(), "{:?}", strategy);
+            assert!(!fs::read_to_string(&live).unwrap().contains("draft: true"));
+            assert_eq!(
+                fs::read_to_string(site.join("_redirects")).unwrap(),
+                "/old  /new  301\n"
+            );
+        }
+        // Offline: the commit stays, for the push queue.
+        set_origin(&site, UNREACHABLE_URL);
+        let queued = unpublish_from_target(&target, "gone").unwrap();
+        assert!(queued.pending_push.is_some());
+        assert_eq!(queued.url, "https://example.com/blog/2025/gone");
+        assert_eq!(repo.head(), queued.commit_sha);
+    }
+
+    #[test]
+    fn pull_request_mode_pushes_a_branch_and_merges_later() {
+        let vault = vault();
+        let (remote, site) = remote_and_clone("review");
+        let target = config::PublishTarget {
+            mode: config::PublishMode::PullRequest,
+            ..target_for(&site)
+        };
+        let source = vault_post(&vault, "review", "# review\n");
+        let source = source.to_str().unwrap();
+        let main_before = remote_main(&remote);
+
+        let published = publish_to_target(&target, &vault, source, "review").unwrap();
+        assert_eq!(published.branch.as_deref(), Some("dispatch/review"));
+        let sha = published.commit_sha.expect("a review commit");
+        let remote_repo = git2::Repository::open_bare(&remote).unwrap();
+        let branch_tip = |repo: &git2::Repository| {
+            repo.refname_to_id("refs/heads/dispatch/review")
+                .ok()
+                .map(|oid| oid.to_string())
+        };
+        assert_eq!(branch_tip(&remote_repo), Some(sha.clone()));
+        assert_eq!(remote_main(&remote), main_before);
+        let dest = resolve_destination(&target, source, "review").dest_path;
+        assert!(!Path::new(&dest).exists());
+
+        let merged = merge_branch(&target, source, "review").unwrap();
+        assert_eq!(merged.commit_sha.as_deref(), Some(sha.as_str()));
+        assert_eq!(remote_main(&remote), sha);
+        assert_eq!(fs::read_to_string(&dest).unwrap(), "# review\n");
+        assert_eq!(branch_tip(&remote_repo), None);
+    }
+
+    #[test]
+    fn compare_urls_follow_the_host() {
+        assert_eq!(
+            compare_url("https://github.com/ej/site", "main", "dispatch/a"),
+            "https://github.com/ej/site/compare/main...dispatch/a?expand=1"
+        );
+        assert_eq!(
+            compare_url("https://gitlab.com/ej/site", "main", "dispatch/a"),
+            "https://gitlab.com/ej/site/-/compare/main...dispatch/a"
+        );
+    }
+
+    #[test]
+    fn year_comes_from_the_post_not_the_clock() {
+        let vault = vault();
+        let (_remote, site) = remote_and_clone("year");
+        let target = target_for(&site);
+
+        let dated = vault_post(&vault, "late", "---\ndate: 2025-12-30\n---\n# late\n");
+        let dest = resolve_destination(&target, dated.to_str().unwrap(), "late");
+        assert_eq!(
+            dest.dest_path,
+            format!("{}/content/blog/2025/late.md", target.repo_path)
+        );
+        assert_eq!(dest.url, "https://example.com/blog/2025/late");
+
+        // No date: stay wherever the post is already live.
+        fs::create_dir_all(site.join("content/blog/2019")).unwrap();
+        fs::write(site.join("content/blog/2019/old.md"), "# old\n").unwrap();
+        let undated = vault_post(&vault, "old", "# old, edited\n");
+        let dest = resolve_destination(&target, undated.to_str().unwrap(), "old");
+        assert_eq!(
+            dest.dest_path,
+            format!("{}/content/blog/2019/old.md", target.repo_path)
+        );
+
+        let mut by_month = target.clone();
+        by_month.content_path_pattern = "content/{content_type}/{year}/{month}/{slug}".into();
+        let dest = resolve_destination(&by_month, dated.to_str().unwrap(), "late");
+        assert!(dest.dest_path.ends_with("content/post/2025/12/late.md"));
+    }
+
+    #[test]
+    fn publish_refuses_to_hide_a_post_live_in_another_year() {
+        let vault = vault();
+        let (_remote, site) = remote_and_clone("hide");
+        let target = target_for(&site);
+        fs::create_dir_all(site.join("content/blog/2026")).unwrap();
+        fs::write(site.join("content/blog/2026/late.md"), "# late\n").unwrap();
+        let repo = Repo::open(&site).unwrap();
+        repo.add(&["content/blog/2026/late.md"]).unwrap();
+        repo.commit("Publish: late").unwrap();
+
+        let dated = vault_post(&vault, "late", "---\ndate: 2025-12-30\n---\n# late\n");
+        let err = publish_to_target(&target, &vault, dated.to_str().unwrap(), "late").unwrap_err();
+        assert!(err.contains("content/blog/2026/late.md"), "{}", err);
+        assert!(!site.join("content/blog/2025/late.md").exists());
+
+        let items = [BatchItem {
+            source_path: dated.to_str().unwrap().into(),
+            slug: "late".into(),
+        }];
+        assert!(publish_batch_to_target(&target, &vault, &items).is_err());
+    }
+
+    #[test]
+    fn batch_is_one_commit_and_rolls_back_on_conflict() {
+        let vault = vault();
+        let (remote, site) = remote_and_clone("batch");
+        let target = target_for(&site);
+        let items: Vec<BatchItem> = ["a", "b"]
+            .iter()
+            .map(|slug| BatchItem {
+                source_path: vault_post(&vault, slug, "# post\n")
+                    .to_str()
+                    .unwrap()
+                    .into(),
+                slug: slug.to_string(),
+            })
+            .collect();
+
+        let published = publish_batch_to_target(&target, &vault, &items).unwrap();
+        let sha = published[0].commit_sha.clone().expect("a batch commit");
+        assert!(published
+            .iter()
+            .all(|p| p.commit_sha.as_deref() == Some(sha.as_str())));
+        assert_eq!(remote_main(&remote), sha);
+
+        // Someone else changes a post on the remote; our batch can't rebase.
+        let a = resolve_destination(&target, &items[0].source_path, "a").dest_path;
+        let rel = a.strip_prefix(&format!("{}/", target.repo_path)).unwrap();
+        push_from_elsewhere(&remote, rel);
+        let c = BatchItem {
+            source_path: vault_post(&vault, "c", "# c\n").to_str().unwrap().into(),
+            slug: "c".into(),
+        };
+        let c_dest = resolve_destination(&target, &c.source_path, "c").dest_path;
+        fs::write(&items[0].source_path, "# a, edited\n").unwrap();
+        let before = Repo::open(&site).unwrap().head();
+
+        assert!(publish_batch_to_target(&target, &vault, &[items[0].clone(), c]).is_err());
+        let repo = Repo::open(&site).unwrap();
+        assert_eq!(repo.head(), before);
+        assert!(repo.status().unwrap().entries.is_empty());
+        assert_eq!(fs::read_to_string(&a).unwrap(), "# post\n");
+        assert!(!Path::new(&c_dest).exists());
+    }
+
+    #[test]
+    fn conflicting_publish_takes_its_commit_back() {
+        let vault = vault();
+        let (remote, site) = remote_and_clone("conflict");
+        let target = target_for(&site);
+        let source = vault_post(&vault, "clash", "# clash\n");
+        publish_to_target(&target, &vault, source.to_str().unwrap(), "clash").unwrap();
+
+        // The remote changes the same post; the republish can't rebase.
+        let dest = resolve_destination(&target, source.to_str().unwrap(), "clash").dest_path;
+        let rel = dest
+            .strip_prefix(&format!("{}/", target.repo_path))
+            .unwrap();
+        push_from_elsewhere(&remote, rel);
+        fs::write(&source, "# clash, edited\n").unwrap();
+        let before = Repo::open(&site).unwrap().head();
+
+        assert!(publish_to_target(&target, &vault, source.to_str().unwrap(), "clash").is_err());
+        let repo = Repo::open(&site).unwrap();
+        assert_eq!(repo.head(), before);
+        assert!(repo.status().unwrap().entries.is_empty());
+        assert_eq!(fs::read_to_string(&dest).unwrap(), "# clash\n");
+    }
+
+    #[test]
+    fn failing_pre_hook_leaves_the_repo_alone() {
+        let vault = vault();
+        let (remote, site) = remote_and_clone("hooks");
+        let before = remote_main(&remote);
+        let mut target = target_for(&site);
+        target.hooks.pre_publish = vec!["test \"$DISPATCH_SLUG\" != blocked".into()];
+        target.hooks.post_publish = vec!["echo \"$DISPATCH_COMMIT_SHA\"".into()];
+
+        let blocked = vault_post(&vault, "blocked", "# no\n");
+        let err =
+            publish_to_target(&target, &vault, blocked.to_str().unwrap(), "blocked").unwrap_err();
+        assert!(err.starts_with("Pre-publish hook"), "{}", err);
+        assert_eq!(remote_main(&remote), before);
+        assert!(Repo::open(&site)
+            .unwrap()
+            .status()
+            .unwrap()
+            .entries
+            .is_empty());
+
+        let fine = vault_post(&vault, "fine", "# yes\n");
+        let published = publish_to_target(&target, &vault, fine.to_str().unwrap(), "fine").unwrap();
+        let post = published.hooks.last().unwrap();
+        assert_eq!(Some(post.stdout.trim()), published.commit_sha.as_deref());
+    }
+
+    #[test]
+    fn unreachable_remote_keeps_the_commit_for_the_push_queue() {
+        let vault = vault();
+        let (remote, site) = remote_and_clone("offline");
+        let before = remote_main(&remote);
+        let mut target = target_for(&site);
+        target.hooks.post_publish = vec!["echo should-not-run".into()];
+        set_origin(&site, UNREACHABLE_URL);
+
+        let source = vault_post(&vault, "queued", "# queued\n");
+        let published =
+            publish_to_target(&target, &vault, source.to_str().unwrap(), "queued").unwrap();
+        assert!(published.pending_push.is_some());
+        assert!(published.hooks.is_empty());
+        let local = published.commit_sha.expect("a local commit");
+        let repo = Repo::open(&site).unwrap();
+        assert_eq!(repo.head().as_deref(), Some(local.as_str()));
+
+        // Back online, with someone else's commit to rebase over.
+        set_origin(&site, remote.to_str().unwrap());
+        push_from_elsewhere(&remote, "other.md");
+        assert_ne!(remote_main(&remote), before);
+        pull_and_push(&repo).unwrap();
+        assert_eq!(repo.find_rebased(&local), Some(remote_main(&remote)));
+    }
+
+    #[test]
+    fn each_target_gets_its_own_outcome() {
+        let vault = vault();
+        let (remote, site) = remote_and_clone("multi-main");
+        let (mirror_remote, mirror_site) = remote_and_clone("multi-mirror");
+        let main = target_for(&site);
+        let mirror = config::PublishTarget {
+            id: "mirror".into(),
+            domain: "https://mirror.example.com".into(),
+            ..target_for(&mirror_site)
+        };
+        let broken = config::PublishTarget {
+            id: "broken".into(),
+            repo_path: scratch("not-a-repo").to_string_lossy().to_string(),
+            ..target_for(&site)
+        };
+        let source = vault_post(&vault, "both", "# both\n");
+
+        let outcomes = publish_each(
+            &[main, broken, mirror],
+            &vault,
+            source.to_str().unwrap(),
+            "both",
+        );
+        let ids: Vec<(&str, bool)> = outcomes
+            .iter()
+            .map(|o| (o.target_id.as_str(), o.success))
+            .collect();
+        assert_eq!(
+            ids,
+            vec![("test", true), ("broken", false), ("mirror", true)]
+        );
+        assert!(outcomes[1].error.is_some());
+        assert_eq!(
+            outcomes[0].commit_sha.as_deref(),
+            Some(remote_main(&remote).as_str())
+        );
+        assert_eq!(
+            outcomes[2].commit_sha.as_deref(),
+            Some(remote_main(&mirror_remote).as_str())
+        );
+        assert!(outcomes[2]
+            .url
+            .as_deref()
+            .unwrap()
+            .starts_with("https://mirror.example.com/blog/"));
+    }
+
+    #[test]
+    fn revert_undoes_a_publish() {
+        let vault = vault();
+        let (remote, site) = remote_and_clone("revert-publish");
+        let mut target = target_for(&site);
+        target.commit.author = Some(config::GitIdentity {
+            name: "Dispatch".into(),
+            email: "dispatch@example.com".into(),
+        });
+        target.commit.trailers = vec!["Reviewed-by: Ed <ed@example.com>".into()];
+        let source = vault_post(&vault, "oops", "# oops\n");
+
+        let published =
+            publish_to_target(&target, &vault, source.to_str().unwrap(), "oops").unwrap();
+        let sha = published.commit_sha.unwrap();
+        let dest = resolve_destination(&target, source.to_str().unwrap(), "oops").dest_path;
+        assert!(Path::new(&dest).exists());
+
+        let revert_sha = revert_in_target(&target, &sha).unwrap().commit_sha;
+        assert!(!Path::new(&dest).exists());
+        assert_eq!(remote_main(&remote), revert_sha);
+        let git = git2::Repository::open(&site).unwrap();
+        let revert = git.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(revert.author().name(), Some("Dispatch"));
+        assert!(revert.message().unwrap().ends_with(&format!(
+            "reverts commit {}.\n\nReviewed-by: Ed <ed@example.com>\n",
+            sha
+        )));
+
+        // A remote that can't be reached keeps the revert for the push
+        // queue; one that's gone for good takes it back out.
+        let source = vault_post(&vault, "later", "# later\n");
+        let later = publish_to_target(&target, &vault, source.to_str().unwrap(), "later")
+            .unwrap()
+            .commit_sha
+            .unwrap();
+        let later_dest = resolve_destination(&target, source.to_str().unwrap(), "later").dest_path;
+        let repo = Repo::open(&site).unwrap();
+        let before = repo.head();
+        set_origin(&site, site.with_file_name("gone.git").to_str().unwrap());
+        assert!(revert_in_target(&target, &later).is_err());
+        assert_eq!(repo.head(), before);
+        assert!(Path::new(&later_dest).exists());
+        assert!(repo.status().unwrap().entries.is_empty());
+        set_origin(&site, UNREACHABLE_URL);
+        let queued = revert_in_target(&target, &later).unwrap();
+        assert!(queued.pending_push.is_some());
+        assert_eq!(repo.head(), Some(queued.commit_sha));
+        assert!(!Path::new(&later_dest).exists());
+        set_origin(&site, remote.to_str().unwrap());
+
+        let again = revert_in_target(&target, &sha).unwrap_err();
+        assert!(again.starts_with("Already reverted"), "{}", again);
+        let missing = revert_in_target(&target, "0123456789012345678901234567890123456789");
+        assert!(missing.unwrap_err().contains("history rewritten"));
+    }
+
+    #[test]
+    fn shell_quote_leaves_plain_paths_alone() {
+        assert_eq!(
+            shell_quote("/Users/ej/code/site/content/blog/2026/post.md"),
+            "/Users/ej/code/site/content/blog/2026/post.md"
+        );
+        assert_eq!(shell_quote("Publish: my-post"), "'Publish: my-post'");
+        assert_eq!(shell_quote("it's"), "'it'\\''s'");
+    }
+
+    #[test]
+    fn publish_git_commands_match_publish_order() {
+        let cmds = publish_git_commands("/repo/content/blog/2026/a.md", "Publish: a");
+        assert_eq!(
+            cmds,
+            vec![
+                "git add /repo/content/blog/2026/a.md",
+                "git commit -m 'Publish: a'",
+                "git pull --rebase --autostash",
+                "git push",
+            ]
+        );
+    }
+
+    #[test]
+    fn check_source_path_requires_publishable_dir() {
+        assert!(check_source_path("/vault", "/vault/blog/2026/a.md").is_ok());
+        assert!(check_source_path("/vault", "/vault/week-notes/2026-01.md").is_ok());
+        assert!(check_source_path("/vault", "/vault/private/a.md").is_err());
+        assert!(check_source_path("/vault", "/elsewhere/blog/a.md").is_err());
+    }
+}
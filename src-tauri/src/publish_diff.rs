@@ -44,22 +44,41 @@ pub struct PublishDiff {
     pub error: Option<String>,
 }
 
-/// Strip YAML frontmatter and return body only. Mirrors `normalize_content`
-/// in vault.rs but returns owned String without further whitespace munging
-/// (we want to preserve real line-by-line text for the diff renderer).
+/// The body after the frontmatter, as owned text without further whitespace
+/// munging (we want to preserve real line-by-line text for the diff
+/// renderer).
 fn strip_frontmatter(content: &str) -> String {
-    if let Some(after_start) = content.strip_prefix("---") {
-        if let Some(end) = after_start.find("---") {
-            return after_start[end + 3..].trim_start_matches('\n').to_string();
-        }
-    }
-    content.to_string()
+    crate::frontmatter::body(content)
+        .trim_start_matches('\n')
+        .to_string()
 }
 
 fn word_count(s: &str) -> usize {
     s.split_whitespace().count()
 }
 
+/// Words in a post's body, frontmatter aside.
+pub fn body_word_count(raw: &str) -> usize {
+    word_count(&strip_frontmatter(raw))
+}
+
+/// Words (added, removed) going from `published_raw` to `source_raw`, on the
+/// same frontmatter-free line diff the changes panel shows.
+pub fn word_delta(published_raw: &str, source_raw: &str) -> (usize, usize) {
+    let published_body = strip_frontmatter(published_raw);
+    let source_body = strip_frontmatter(source_raw);
+    let diff = TextDiff::from_lines(&published_body, &source_body);
+    let mut delta = (0, 0);
+    for change in diff.iter_all_changes() {
+        match change.tag() {
+            ChangeTag::Insert => delta.0 += word_count(&change.to_string()),
+            ChangeTag::Delete => delta.1 += word_count(&change.to_string()),
+            ChangeTag::Equal => {}
+        }
+    }
+    delta
+}
+
 /// Compute diff between source file and published copy, looking up the
 /// published copy via the existing `vault::find_published_info_for_target`
 /// resolver so we honor the user's configured publish target.
@@ -80,8 +99,11 @@ pub fn compute_publish_diff(source_file_path: &str) -> Result<PublishDiff, Strin
     let (_url, _date, published_content) =
         crate::vault::find_published_info_for_target(&target, slug);
 
+    // Compare what a publish would write now, not the raw note.
     let source_raw = fs::read_to_string(source_file_path)
         .map_err(|e| format!("Failed to read source: {}", e))?;
+    let vault = crate::config::get()?.vault;
+    let source_raw = crate::transform::preview(&source_raw, &target, &vault);
 
     let Some(published_raw) = published_content else {
         return Ok(PublishDiff {
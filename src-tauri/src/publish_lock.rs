@@ -0,0 +1,266 @@
+//! One git operation at a time per target repo.
+//!
+//! The scheduler, the push queue, the companion server and the desktop UI
+//! can all publish into the same repo at once. Everything that commits,
+//! pushes or moves files there takes this lock first. Inside the app,
+//! callers queue for it in arrival order. Across processes, a lock file in
+//! the repo's git dir says who holds it. The file records the holder's pid,
+//! so a lock left behind by a crash is taken over instead of wedging the
+//! repo (where pids can't be checked, once the file is old enough).
+
+use crate::git::Repo;
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Condvar, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+const LOCK_FILE: &str = "dispatch-publish.lock";
+
+/// How long a caller waits its turn before giving up.
+const WAIT: Duration = Duration::from_secs(60);
+
+/// How often a waiter looks at a lock file another process holds.
+const POLL: Duration = Duration::from_millis(250);
+
+/// Where the holder's pid can't be checked, how old a lock file has to be
+/// before it's taken over.
+#[cfg(not(unix))]
+const STALE_AFTER: Duration = Duration::from_secs(10 * 60);
+
+static QUEUES: LazyLock<Mutex<HashMap<PathBuf, Queue>>> = LazyLock::new(Default::default);
+static TURN: Condvar = Condvar::new();
+static NEXT_TICKET: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Default)]
+struct Queue {
+    /// Tickets waiting, in arrival order.
+    waiting: VecDeque<u64>,
+    /// What the current holder is doing, while one is.
+    held_by: Option<String>,
+}
+
+/// Held for the length of a git operation; released on drop.
+#[derive(Debug)]
+pub struct RepoLock {
+    key: PathBuf,
+    file: PathBuf,
+}
+
+impl Drop for RepoLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.file);
+        if let Ok(mut queues) = QUEUES.lock() {
+            if let Some(queue) = queues.get_mut(&self.key) {
+                queue.held_by = None;
+            }
+        }
+        TURN.notify_all();
+    }
+}
+
+/// Take the lock on the repo at `repo_path`, waiting behind whoever got
+/// there first. `what` is shown to anyone who has to wait
+/// (`publish tea`).
+pub fn acquire(repo_path: &str, what: &str) -> Result<RepoLock, String> {
+    acquire_within(repo_path, what, WAIT)
+}
+
+fn acquire_within(repo_path: &str, what: &str, wait: Duration) -> Result<RepoLock, String> {
+    let git_dir = Repo::open(repo_path)?.git_dir().to_path_buf();
+    let key = fs::canonicalize(&git_dir).unwrap_or(git_dir);
+    let file = key.join(LOCK_FILE);
+    let ticket = NEXT_TICKET.fetch_add(1, Ordering::SeqCst);
+    let deadline = Instant::now() + wait;
+
+    let mut queues = QUEUES.lock().map_err(|e| format!("Publish lock: {}", e))?;
+    queues
+        .entry(key.clone())
+        .or_default()
+        .waiting
+        .push_back(ticket);
+    loop {
+        let queue = queues.entry(key.clone()).or_default();
+        let mut holder = queue.held_by.clone();
+        if holder.is_none() && queue.waiting.front() == Some(&ticket) {
+            match take_file(&file, what) {
+                Ok(()) => {
+                    queue.waiting.pop_front();
+                    queue.held_by = Some(what.to_string());
+                    return Ok(RepoLock { key, file });
+                }
+                Err(Taken::By(other)) => holder = Some(other),
+                Err(Taken::Io(e)) => {
+                    queue.waiting.retain(|t| *t != ticket);
+                    TURN.notify_all();
+                    return Err(format!("Couldn't create {}: {}", file.display(), e));
+                }
+            }
+        }
+
+        let now = Instant::now();
+        if now >= deadline {
+            queue.waiting.retain(|t| *t != ticket);
+            TURN.notify_all();
+            return Err(format!(
+                "Publish in progress for {} ({}); try again when it's done",
+                repo_name(repo_path),
+                holder.unwrap_or_else(|| "another publish is queued first".into())
+            ));
+        }
+        queues = TURN
+            .wait_timeout(queues, (deadline - now).min(POLL))
+            .map_err(|e| format!("Publish lock: {}", e))?
+            .0;
+    }
+}
+
+enum Taken {
+    /// Another process holds it, doing this.
+    By(String),
+    Io(std::io::Error),
+}
+
+/// Create the lock file, clearing one left by a process that's gone.
+fn take_file(file: &Path, what: &str) -> Result<(), Taken> {
+    for _ in 0..2 {
+        match OpenOptions::new().write(true).create_new(true).open(file) {
+            Ok(mut f) => {
+                return writeln!(f, "{} {}", std::process::id(), what).map_err(Taken::Io);
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                let text = fs::read_to_string(file).unwrap_or_default();
+                let (pid, holder) = text.trim().split_once(' ').unwrap_or((text.trim(), ""));
+                match pid.parse::<u32>() {
+                    Ok(pid) if pid != std::process::id() && is_running(pid, file) => {
+                        return Err(Taken::By(holder.to_string()));
+                    }
+                    // Our own pid means a previous run that crashed: this
+                    // process would know if it held the lock.
+                    _ => {
+                        log::warn!(
+                            "Clearing stale publish lock {} ({})",
+                            file.display(),
+                            text.trim()
+                        );
+                        let _ = fs::remove_file(file);
+                    }
+                }
+            }
+            Err(e) => return Err(Taken::Io(e)),
+        }
+    }
+    Err(Taken::By("another process".into()))
+}
+
+/// Signal 0 checks a pid without signalling it. ESRCH means there's no such
+/// process; EPERM means there is, it just isn't ours.
+#[cfg(unix)]
+fn is_running(pid: u32, _file: &Path) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // 0 would ask about our own process group.
+    if pid == 0 {
+        return false;
+    }
+    if unsafe { libc::kill(pid, 0) } == 0 {
+        return true;
+    }
+    std::io::Error::last_os_error().raw_os_error() != Some(libc::ESRCH)
+}
+
+/// No pid check here: a lock file older than any git operation takes is
+/// treated as left behind.
+#[cfg(not(unix))]
+fn is_running(_pid: u32, file: &Path) -> bool {
+    fs::metadata(file)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_none_or(|age| age < STALE_AFTER)
+}
+
+fn repo_name(repo_path: &str) -> String {
+    Path::new(repo_path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| repo_path.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::tests::{remote_and_clone, scratch};
+    use std::sync::Arc;
+
+    #[test]
+    fn waiters_go_in_arrival_order() {
+        let (_, site) = remote_and_clone("lock-fifo");
+        let site = site.to_str().unwrap().to_string();
+        let order = Arc::new(Mutex::new(Vec::new()));
+
+        let first = acquire(&site, "publish first").unwrap();
+        assert!(site_lock_file(&site).exists());
+        let waiters: Vec<_> = (0..3)
+            .map(|i| {
+                let (site, order) = (site.clone(), order.clone());
+                let handle = std::thread::spawn(move || {
+                    let _lock = acquire(&site, &format!("publish {}", i)).unwrap();
+                    order.lock().unwrap().push(i);
+                });
+                // Let each one queue before the next arrives.
+                std::thread::sleep(Duration::from_millis(50));
+                handle
+            })
+            .collect();
+
+        let err = acquire_within(&site, "publish late", Duration::from_millis(10)).unwrap_err();
+        assert!(err.starts_with("Publish in progress for "), "{}", err);
+        assert!(err.contains("(publish first)"), "{}", err);
+
+        drop(first);
+        for handle in waiters {
+            handle.join().unwrap();
+        }
+        assert_eq!(*order.lock().unwrap(), [0, 1, 2]);
+        assert!(!site_lock_file(&site).exists());
+    }
+
+    #[test]
+    fn lock_files_from_dead_processes_are_taken_over() {
+        let (_, site) = remote_and_clone("lock-stale");
+        let site = site.to_str().unwrap().to_string();
+
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        fs::write(
+            site_lock_file(&site),
+            format!("{} rename a -> b\n", child.id()),
+        )
+        .unwrap();
+        let err = acquire_within(&site, "publish tea", Duration::from_millis(300)).unwrap_err();
+        assert!(err.contains("(rename a -> b)"), "{}", err);
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+        assert!(!is_running(child.id(), &site_lock_file(&site)));
+        // Someone else's process (unless we're root): EPERM, still alive.
+        assert!(is_running(1, &site_lock_file(&site)));
+        assert!(!is_running(0, &site_lock_file(&site)));
+        let lock = acquire_within(&site, "publish tea", Duration::from_millis(300)).unwrap();
+        let text = fs::read_to_string(site_lock_file(&site)).unwrap();
+        assert_eq!(text, format!("{} publish tea\n", std::process::id()));
+        drop(lock);
+
+        assert!(acquire(scratch("lock-none").to_str().unwrap(), "publish").is_err());
+    }
+
+    fn site_lock_file(site: &str) -> PathBuf {
+        Path::new(site).join(".git").join(LOCK_FILE)
+    }
+}
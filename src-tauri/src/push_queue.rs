@@ -0,0 +1,550 @@
+//! Push queue — publish commits that were made locally but never reached
+//! the remote.
+//!
+//! When the pull or push at the end of a publish can't reach the remote, or
+//! the remote refuses it, the commit is already in the target repo. It's
+//! recorded here instead of being reported as a failure, and a background
+//! task keeps retrying the repo's pull + push with backoff. The journal
+//! event, post-publish hooks and deploy check for a queued publish happen
+//! only once the push lands. Reverts, unpublishes and renames queue the same
+//! way, but they're journaled when they're made. A retry that fails for a reason waiting won't
+//! fix (a conflict, auth) marks the item failed and stops retrying it until
+//! someone asks.
+
+use crate::git::Repo;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use tauri::Emitter;
+
+/// First retry after this long, doubling per failed attempt...
+const BASE_BACKOFF_SECS: i64 = 30;
+/// ...up to this.
+const MAX_BACKOFF_SECS: i64 = 15 * 60;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingPush {
+    pub id: i64,
+    /// What made the commit: "publish", "revert", "unpublish" or "rename".
+    pub kind: String,
+    pub target_id: String,
+    pub repo_path: String,
+    /// The local commit. Pulling may rebase it onto a new SHA.
+    pub commit_sha: String,
+    pub source_path: String,
+    pub slug: String,
+    pub url: String,
+    pub dest_path: String,
+    pub is_republish: bool,
+    /// "pending" until the push lands, then "pushed". "failed" once a retry
+    /// hits an error that needs a person; only "Retry now" tries again.
+    pub status: String,
+    pub attempt_count: i32,
+    pub last_error: Option<String>,
+    pub next_attempt_at: String,
+    /// The commit's SHA on the remote, once pushed.
+    pub pushed_sha: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewPendingPush {
+    pub kind: String,
+    pub target_id: String,
+    pub repo_path: String,
+    pub commit_sha: String,
+    pub source_path: String,
+    pub slug: String,
+    pub url: String,
+    pub dest_path: String,
+    pub is_republish: bool,
+    /// Why the commit couldn't push.
+    pub error: String,
+}
+
+// ---------------------------------------------------------------------------
+// Database
+// ---------------------------------------------------------------------------
+
+static DB: OnceLock<Result<Mutex<Connection>, String>> = OnceLock::new();
+
+fn db_path() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_default();
+    PathBuf::from(home)
+        .join("Library/Application Support/com.ejfox.dispatch")
+        .join("push_queue.db")
+}
+
+fn init_db() -> Result<Mutex<Connection>, String> {
+    let path = db_path();
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    let conn = Connection::open(&path)
+        .map_err(|e| format!("Failed to open push queue database: {}", e))?;
+    conn.execute_batch("PRAGMA journal_mode=WAL;")
+        .map_err(|e| format!("Failed to set pragmas: {}", e))?;
+    init_schema(&conn)?;
+    Ok(Mutex::new(conn))
+}
+
+fn get_db() -> Result<&'static Mutex<Connection>, String> {
+    DB.get_or_init(init_db).as_ref().map_err(|e| e.clone())
+}
+
+fn init_schema(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS pending_pushes (
+            id              INTEGER PRIMARY KEY AUTOINCREMENT,
+            target_id       TEXT NOT NULL,
+            repo_path       TEXT NOT NULL,
+            commit_sha      TEXT NOT NULL,
+            source_path     TEXT NOT NULL,
+            slug            TEXT NOT NULL,
+            url             TEXT NOT NULL,
+            dest_path       TEXT NOT NULL,
+            is_republish    INTEGER NOT NULL DEFAULT 0,
+            status          TEXT NOT NULL DEFAULT 'pending',
+            attempt_count   INTEGER NOT NULL DEFAULT 0,
+            last_error      TEXT,
+            next_attempt_at TEXT NOT NULL,
+            pushed_sha      TEXT,
+            created_at      TEXT NOT NULL,
+            updated_at      TEXT NOT NULL,
+            kind            TEXT NOT NULL DEFAULT 'publish'
+        );
+        CREATE INDEX IF NOT EXISTS idx_pending_status ON pending_pushes(status);",
+    )
+    .map_err(|e| format!("Failed to create push queue schema: {}", e))?;
+
+    // Queues from before reverts and unpublishes were queued hold only publishes.
+    let has_kind: bool = conn
+        .query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('pending_pushes') WHERE name = 'kind'",
+            [],
+            |r| r.get::<_, i64>(0),
+        )
+        .map(|n| n > 0)
+        .map_err(|e| format!("Failed to inspect push queue schema: {}", e))?;
+    if !has_kind {
+        conn.execute(
+            "ALTER TABLE pending_pushes ADD COLUMN kind TEXT NOT NULL DEFAULT 'publish'",
+            [],
+        )
+        .map_err(|e| format!("Failed to migrate push queue schema: {}", e))?;
+    }
+    Ok(())
+}
+
+const COLUMNS: &str = "id, target_id, repo_path, commit_sha, source_path, slug, url, dest_path, is_republish, status, attempt_count, last_error, next_attempt_at, pushed_sha, created_at, updated_at, kind";
+
+fn item_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<PendingPush> {
+    Ok(PendingPush {
+        id: row.get(0)?,
+        kind: row.get(16)?,
+        target_id: row.get(1)?,
+        repo_path: row.get(2)?,
+        commit_sha: row.get(3)?,
+        source_path: row.get(4)?,
+        slug: row.get(5)?,
+        url: row.get(6)?,
+        dest_path: row.get(7)?,
+        is_republish: row.get(8)?,
+        status: row.get(9)?,
+        attempt_count: row.get(10)?,
+        last_error: row.get(11)?,
+        next_attempt_at: row.get(12)?,
+        pushed_sha: row.get(13)?,
+        created_at: row.get(14)?,
+        updated_at: row.get(15)?,
+    })
+}
+
+/// Wait before the next try after `attempts` failed ones.
+fn backoff(attempts: i32) -> chrono::Duration {
+    let secs = BASE_BACKOFF_SECS.saturating_mul(1 << attempts.clamp(0, 16));
+    chrono::Duration::seconds(secs.min(MAX_BACKOFF_SECS))
+}
+
+// ---------------------------------------------------------------------------
+// Queue operations
+// ---------------------------------------------------------------------------
+
+/// Record an unpushed publish commit. Returns the new row ID.
+pub fn enqueue(item: &NewPendingPush) -> Result<i64, String> {
+    let db = get_db()?.lock().map_err(|e| format!("DB lock: {}", e))?;
+    insert(&db, item, chrono::Utc::now())
+}
+
+/// Pending pushes, oldest first — for one target, or all of them.
+pub fn get_pending(target_id: Option<&str>) -> Result<Vec<PendingPush>, String> {
+    let db = get_db()?.lock().map_err(|e| format!("DB lock: {}", e))?;
+    select_pending(&db, target_id)
+}
+
+/// Pushes that stopped retrying, oldest first.
+pub fn get_failed(target_id: Option<&str>) -> Result<Vec<PendingPush>, String> {
+    let db = get_db()?.lock().map_err(|e| format!("DB lock: {}", e))?;
+    select_status(&db, "failed", target_id)
+}
+
+pub fn pending_count(target_id: Option<&str>) -> Result<usize, String> {
+    get_pending(target_id).map(|items| items.len())
+}
+
+fn insert(
+    conn: &Connection,
+    item: &NewPendingPush,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<i64, String> {
+    conn.execute(
+        "INSERT INTO pending_pushes
+         (target_id, repo_path, commit_sha, source_path, slug, url, dest_path, is_republish,
+          last_error, next_attempt_at, created_at, updated_at, kind)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?11, ?12)",
+        params![
+            item.target_id,
+            item.repo_path,
+            item.commit_sha,
+            item.source_path,
+            item.slug,
+            item.url,
+            item.dest_path,
+            item.is_republish,
+            item.error,
+            (now + backoff(0)).to_rfc3339(),
+            now.to_rfc3339(),
+            item.kind,
+        ],
+    )
+    .map_err(|e| format!("Insert failed: {}", e))?;
+    Ok(conn.last_insert_rowid())
+}
+
+fn select_pending(conn: &Connection, target_id: Option<&str>) -> Result<Vec<PendingPush>, String> {
+    select_status(conn, "pending", target_id)
+}
+
+fn select_status(
+    conn: &Connection,
+    status: &str,
+    target_id: Option<&str>,
+) -> Result<Vec<PendingPush>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM pending_pushes
+             WHERE status = ?1 AND (?2 IS NULL OR target_id = ?2)
+             ORDER BY id",
+            COLUMNS
+        ))
+        .map_err(|e| format!("Query: {}", e))?;
+    let rows = stmt
+        .query_map(params![status, target_id], item_from_row)
+        .map_err(|e| format!("Query: {}", e))?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Row parse: {}", e))
+}
+
+/// Pending items whose next attempt is due by `now`.
+fn select_due(
+    conn: &Connection,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<Vec<PendingPush>, String> {
+    Ok(select_pending(conn, None)?
+        .into_iter()
+        .filter(|item| {
+            chrono::DateTime::parse_from_rfc3339(&item.next_attempt_at)
+                .map(|at| at <= now)
+                .unwrap_or(true)
+        })
+        .collect())
+}
+
+fn mark_pushed(
+    conn: &Connection,
+    id: i64,
+    pushed_sha: &str,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<(), String> {
+    conn.execute(
+        "UPDATE pending_pushes SET status = 'pushed', pushed_sha = ?1, last_error = NULL, updated_at = ?2 WHERE id = ?3",
+        params![pushed_sha, now.to_rfc3339(), id],
+    )
+    .map_err(|e| format!("Mark pushed: {}", e))?;
+    Ok(())
+}
+
+fn mark_retry(
+    conn: &Connection,
+    item: &PendingPush,
+    error: &str,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<(), String> {
+    let next = now + backoff(item.attempt_count + 1);
+    conn.execute(
+        "UPDATE pending_pushes SET attempt_count = attempt_count + 1, last_error = ?1, next_attempt_at = ?2, updated_at = ?3 WHERE id = ?4",
+        params![error, next.to_rfc3339(), now.to_rfc3339(), item.id],
+    )
+    .map_err(|e| format!("Mark retry: {}", e))?;
+    Ok(())
+}
+
+fn mark_failed(
+    conn: &Connection,
+    item: &PendingPush,
+    error: &str,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<(), String> {
+    conn.execute(
+        "UPDATE pending_pushes SET status = 'failed', attempt_count = attempt_count + 1, last_error = ?1, updated_at = ?2 WHERE id = ?3",
+        params![error, now.to_rfc3339(), item.id],
+    )
+    .map_err(|e| format!("Mark failed: {}", e))?;
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Retrying
+// ---------------------------------------------------------------------------
+
+/// Retry `items` (grouped by repo) now, ignoring their backoff. Returns how
+/// many landed.
+fn flush(app_handle: &tauri::AppHandle, items: Vec<PendingPush>) -> Result<usize, String> {
+    let mut by_repo: BTreeMap<String, Vec<PendingPush>> = BTreeMap::new();
+    for item in items {
+        by_repo
+            .entry(item.repo_path.clone())
+            .or_default()
+            .push(item);
+    }
+
+    let mut landed = 0;
+    for (repo_path, items) in by_repo {
+        // Everything committed in a repo goes up together, so one success
+        // clears every item pending there.
+        let _lock = match crate::publish_lock::acquire(&repo_path, "retry queued pushes") {
+            Ok(lock) => lock,
+            Err(e) => {
+                log::warn!("Push queue: {}", e);
+                continue;
+            }
+        };
+        let result = Repo::open(&repo_path)
+            .and_then(|repo| crate::publish::pull_and_push(&repo).map(|_| repo));
+        let db = get_db()?.lock().map_err(|e| format!("DB lock: {}", e))?;
+        let now = chrono::Utc::now();
+        let repo = match result {
+            Ok(repo) => repo,
+            Err(e) if e.is_transient() => {
+                log::warn!("Push queue: {} still can't push: {}", repo_path, e);
+                for item in &items {
+                    let _ = mark_retry(&db, item, &e.to_string(), now);
+                }
+                continue;
+            }
+            Err(e) => {
+                log::warn!("Push queue: {} can't push, giving up: {}", repo_path, e);
+                for item in &items {
+                    let _ = mark_failed(&db, item, &e.to_string(), now);
+                }
+                let failed = select_status(&db, "failed", None)?;
+                drop(db);
+                let failed: Vec<&PendingPush> = failed
+                    .iter()
+                    .filter(|f| items.iter().any(|i| i.id == f.id))
+                    .collect();
+                let _ = app_handle.emit("push-queue-failed", &failed);
+                continue;
+            }
+        };
+        // Anything else waiting on this repo went up with the push too.
+        let mut items = items;
+        let waiting = select_pending(&db, None)?
+            .into_iter()
+            .chain(select_status(&db, "failed", None)?);
+        for other in waiting {
+            if other.repo_path == repo_path && !items.iter().any(|i| i.id == other.id) {
+                items.push(other);
+            }
+        }
+        let pushed: Vec<(PendingPush, String)> = items
+            .into_iter()
+            .map(|item| {
+                let sha = repo
+                    .find_rebased(&item.commit_sha)
+                    .unwrap_or_else(|| item.commit_sha.clone());
+                (item, sha)
+            })
+            .collect();
+        for (item, sha) in &pushed {
+            mark_pushed(&db, item.id, sha, now)?;
+        }
+        drop(db);
+        for (item, sha) in &pushed {
+            on_landed(app_handle, item, sha);
+        }
+        landed += pushed.len();
+    }
+    Ok(landed)
+}
+
+/// A queued publish is live: journal it and do what a direct publish would
+/// have done after its push. Other kinds were journaled when committed.
+fn on_landed(app_handle: &tauri::AppHandle, item: &PendingPush, pushed_sha: &str) {
+    log::warn!(
+        "Push queue: {} of {} landed on {}",
+        item.kind,
+        item.slug,
+        item.target_id
+    );
+    if item.kind != "publish" {
+        let _ = app_handle.emit("push-queue-landed", item);
+        return;
+    }
+    let commit_sha = Some(pushed_sha);
+    crate::record_publish_event(
+        &item.source_path,
+        &item.slug,
+        &item.url,
+        item.is_republish,
+        Some(&item.target_id),
+        commit_sha,
+    );
+    if let Ok(target) = crate::config::resolve_target(Some(&item.target_id)) {
+        crate::hooks::post_publish(
+            &target,
+            &crate::hooks::HookEnv {
+                target_id: &item.target_id,
+                slug: &item.slug,
+                url: &item.url,
+                source_path: &item.source_path,
+                dest_path: &item.dest_path,
+                commit_sha,
+            },
+        );
+        crate::deploy::watch(
+            app_handle,
+            &target,
+            &item.source_path,
+            &item.slug,
+            &item.url,
+            commit_sha,
+        );
+    }
+    let _ = app_handle.emit("push-queue-landed", item);
+}
+
+/// Retry every pending push right away, and the failed ones too ("Retry
+/// now" in the UI, once whatever stopped them is sorted out).
+pub fn retry_all(app_handle: &tauri::AppHandle) -> Result<usize, String> {
+    let mut items = get_pending(None)?;
+    items.extend(get_failed(None)?);
+    flush(app_handle, items)
+}
+
+// ---------------------------------------------------------------------------
+// Background retrier
+// ---------------------------------------------------------------------------
+
+/// Runs every 30 seconds and retries whatever is due.
+pub async fn run_push_queue(app_handle: tauri::AppHandle) {
+    use tokio::time::{interval, Duration};
+    let mut ticker = interval(Duration::from_secs(30));
+
+    loop {
+        ticker.tick().await;
+        let due = match get_db()
+            .and_then(|db| db.lock().map_err(|e| format!("DB lock: {}", e)))
+            .and_then(|db| select_due(&db, chrono::Utc::now()))
+        {
+            Ok(items) => items,
+            Err(e) => {
+                log::warn!("Push queue error: {}", e);
+                continue;
+            }
+        };
+        if due.is_empty() {
+            continue;
+        }
+        log::warn!("Push queue: retrying {} pending publishes", due.len());
+        if let Err(e) = flush(&app_handle, due) {
+            log::warn!("Push queue error: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(slug: &str) -> NewPendingPush {
+        NewPendingPush {
+            kind: "publish".into(),
+            target_id: "website".into(),
+            repo_path: "/site".into(),
+            commit_sha: "abc123".into(),
+            source_path: format!("/vault/blog/{}.md", slug),
+            slug: slug.into(),
+            url: format!("https://example.com/blog/2026/{}", slug),
+            dest_path: format!("/site/content/blog/2026/{}.md", slug),
+            is_republish: false,
+            error: "Git push failed: Could not resolve host".into(),
+        }
+    }
+
+    #[test]
+    fn retries_back_off_until_pushed() {
+        let conn = Connection::open_in_memory().unwrap();
+        init_schema(&conn).unwrap();
+        let t0 = chrono::Utc::now();
+        insert(&conn, &item("one"), t0).unwrap();
+        insert(&conn, &item("two"), t0).unwrap();
+
+        assert!(select_due(&conn, t0).unwrap().is_empty());
+        let due = select_due(&conn, t0 + chrono::Duration::seconds(31)).unwrap();
+        assert_eq!(due.len(), 2);
+
+        let t1 = t0 + chrono::Duration::seconds(31);
+        mark_retry(&conn, &due[0], "still offline", t1).unwrap();
+        mark_pushed(&conn, due[1].id, "def456", t1).unwrap();
+
+        let pending = select_pending(&conn, Some("website")).unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].attempt_count, 1);
+        assert_eq!(pending[0].last_error.as_deref(), Some("still offline"));
+        assert!(select_due(&conn, t1 + chrono::Duration::seconds(59))
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            select_due(&conn, t1 + chrono::Duration::seconds(60))
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(select_pending(&conn, Some("mirror")).unwrap().is_empty());
+
+        // A failed push stops coming due.
+        mark_failed(&conn, &pending[0], "Conflicts in one.md", t1).unwrap();
+        assert!(select_due(&conn, t1 + chrono::Duration::days(1))
+            .unwrap()
+            .is_empty());
+        let failed = select_status(&conn, "failed", Some("website")).unwrap();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].last_error.as_deref(), Some("Conflicts in one.md"));
+    }
+
+    #[test]
+    fn backoff_doubles_and_caps() {
+        assert_eq!(backoff(0).num_seconds(), 30);
+        assert_eq!(backoff(3).num_seconds(), 240);
+        assert_eq!(backoff(10).num_seconds(), MAX_BACKOFF_SECS);
+        assert_eq!(backoff(100).num_seconds(), MAX_BACKOFF_SECS);
+    }
+}
@@ -0,0 +1,251 @@
+//! Redirect files in a target's repo, written when a live post's slug changes
+//! or it's deleted.
+//!
+//! Adding `/a -> /b` also repoints any existing redirect that ended at `/a`,
+//! so renaming a post twice leaves one hop rather than a chain, and drops a
+//! `/b -> ...` entry that would now loop back.
+
+use crate::config::{PublishTarget, RedirectFormat};
+use serde_json::{json, Map, Value};
+use std::fs;
+use std::path::PathBuf;
+
+/// The file a format lives in when `RedirectsConfig.path` is empty.
+pub fn default_path(format: RedirectFormat) -> &'static str {
+    match format {
+        RedirectFormat::None => "",
+        RedirectFormat::Netlify => "_redirects",
+        RedirectFormat::Vercel => "vercel.json",
+        RedirectFormat::JsonMap => "redirects.json",
+    }
+}
+
+/// The target's redirects file, or None when it doesn't keep one.
+pub fn file(target: &PublishTarget) -> Option<PathBuf> {
+    let format = target.redirects.format;
+    let rel = match target.redirects.path.trim().trim_start_matches('/') {
+        "" => default_path(format),
+        path => path,
+    };
+    (format != RedirectFormat::None).then(|| PathBuf::from(&target.repo_path).join(rel))
+}
+
+/// Add a permanent redirect between two site paths (`/blog/2026/old`) to the
+/// target's redirects file, creating it if needed. Returns the file written,
+/// or None when the target doesn't keep one.
+pub fn add(target: &PublishTarget, from: &str, to: &str) -> Result<Option<PathBuf>, String> {
+    update(target, |format, existing| match format {
+        RedirectFormat::Netlify => netlify(existing, from, to),
+        RedirectFormat::Vercel => vercel(existing, from, to),
+        RedirectFormat::JsonMap => json_map(existing, from, to),
+        RedirectFormat::None => unreachable!(),
+    })
+}
+
+/// Mark a site path as gone for good (410). `vercel.json` redirects can't
+/// answer 410, so that format is refused, as is a target without a file.
+pub fn gone(target: &PublishTarget, path: &str) -> Result<PathBuf, String> {
+    let written = update(target, |format, existing| match format {
+        RedirectFormat::Netlify => Ok(netlify_gone(existing, path)),
+        RedirectFormat::JsonMap => json_map_gone(existing, path),
+        RedirectFormat::Vercel => Err("vercel.json redirects can't answer 410 Gone".into()),
+        RedirectFormat::None => unreachable!(),
+    })?;
+    written.ok_or_else(|| {
+        format!(
+            "{} has no redirects file to record the deleted post's 410 in",
+            target.name
+        )
+    })
+}
+
+/// `https://example.com/blog/2026/x` -> `/blog/2026/x`.
+pub fn site_path<'a>(target: &PublishTarget, url: &'a str) -> &'a str {
+    url.strip_prefix(target.domain.as_str()).unwrap_or(url)
+}
+
+/// Rewrite the target's redirects file with `edit(format, current text)`.
+fn update(
+    target: &PublishTarget,
+    edit: impl FnOnce(RedirectFormat, &str) -> Result<String, String>,
+) -> Result<Option<PathBuf>, String> {
+    let Some(path) = file(target) else {
+        return Ok(None);
+    };
+    let rel = path
+        .strip_prefix(&target.repo_path)
+        .unwrap_or(&path)
+        .display();
+    let existing = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(format!("Failed to read {}: {}", rel, e)),
+    };
+
+    let updated = edit(target.redirects.format, &existing)
+        .map_err(|e| format!("Couldn't update {}: {}", rel, e))?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create dir: {}", e))?;
+    }
+    fs::write(&path, updated).map_err(|e| format!("Failed to write {}: {}", rel, e))?;
+    Ok(Some(path))
+}
+
+/// `_redirects`: one `from to [status]` rule per line, first match wins.
+/// Lines we don't touch keep their spacing and comments.
+fn netlify(existing: &str, from: &str, to: &str) -> Result<String, String> {
+    let mut out = String::with_capacity(existing.len() + from.len() + to.len() + 8);
+    for line in existing.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields.as_slice() {
+            [source, ..] if source.starts_with('#') => out.push_str(line),
+            [source, ..] if *source == from || *source == to => continue,
+            [source, dest, rest @ ..] if *dest == from => {
+                let rule: Vec<&str> = [*source, to]
+                    .into_iter()
+                    .chain(rest.iter().copied())
+                    .collect();
+                out.push_str(&rule.join("  "));
+            }
+            _ => out.push_str(line),
+        }
+        out.push('\n');
+    }
+    out.push_str(&format!("{}  {}  301\n", from, to));
+    Ok(out)
+}
+
+/// Any rule for `path` is replaced by a 410 that serves the site's 404 page.
+/// Rules that redirected an earlier URL to `path` answer 410 too, rather
+/// than sending visitors on to it.
+fn netlify_gone(existing: &str, path: &str) -> String {
+    let mut out = String::with_capacity(existing.len() + path.len() + 20);
+    for line in existing.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields.as_slice() {
+            [source, ..] if source.starts_with('#') => out.push_str(line),
+            [source, ..] if *source == path => continue,
+            [source, dest, ..] if *dest == path => {
+                out.push_str(&format!("{}  /404.html  410", source))
+            }
+            _ => out.push_str(line),
+        }
+        out.push('\n');
+    }
+    out.push_str(&format!("{}  /404.html  410\n", path));
+    out
+}
+
+/// `vercel.json`: `{"redirects": [{"source", "destination", "permanent"}]}`
+/// alongside whatever else the file configures.
+fn vercel(existing: &str, from: &str, to: &str) -> Result<String, String> {
+    let mut root = parse_object(existing)?;
+    let redirects = root
+        .entry("redirects")
+        .or_insert_with(|| Value::Array(Vec::new()))
+        .as_array_mut()
+        .ok_or("\"redirects\" isn't an array")?;
+    redirects.retain(|r| {
+        let source = r.get("source").and_then(Value::as_str);
+        source != Some(from) && source != Some(to)
+    });
+    for rule in redirects.iter_mut() {
+        if rule.get("destination").and_then(Value::as_str) == Some(from) {
+            rule["destination"] = json!(to);
+        }
+    }
+    redirects.push(json!({ "source": from, "destination": to, "permanent": true }));
+    to_pretty(&Value::Object(root))
+}
+
+/// A flat `{"/old": "/new"}` object.
+fn json_map(existing: &str, from: &str, to: &str) -> Result<String, String> {
+    let mut map = parse_object(existing)?;
+    map.remove(to);
+    for dest in map.values_mut() {
+        if dest.as_str() == Some(from) {
+            *dest = json!(to);
+        }
+    }
+    map.insert(from.to_string(), json!(to));
+    to_pretty(&Value::Object(map))
+}
+
+/// A gone path maps to `null`, as do entries that pointed at it.
+fn json_map_gone(existing: &str, path: &str) -> Result<String, String> {
+    let mut map = parse_object(existing)?;
+    for dest in map.values_mut() {
+        if dest.as_str() == Some(path) {
+            *dest = Value::Null;
+        }
+    }
+    map.insert(path.to_string(), Value::Null);
+    to_pretty(&Value::Object(map))
+}
+
+fn parse_object(text: &str) -> Result<Map<String, Value>, String> {
+    if text.trim().is_empty() {
+        return Ok(Map::new());
+    }
+    match serde_json::from_str(text).map_err(|e| e.to_string())? {
+        Value::Object(map) => Ok(map),
+        _ => Err("expected a JSON object".into()),
+    }
+}
+
+fn to_pretty(value: &Value) -> Result<String, String> {
+    serde_json::to_string_pretty(value)
+        .map(|s| s + "\n")
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn netlify_rules_are_appended_and_chains_collapsed() {
+        let existing = "# moved posts\n/blog/2024/first   /blog/2024/second 301\n/blog/2024/second  /elsewhere  302\n/keep /kept\n";
+        assert_eq!(
+            netlify(existing, "/blog/2024/second", "/blog/2024/third").unwrap(),
+            "# moved posts\n/blog/2024/first  /blog/2024/third  301\n/keep /kept\n/blog/2024/second  /blog/2024/third  301\n"
+        );
+        assert_eq!(netlify("", "/a", "/b").unwrap(), "/a  /b  301\n");
+    }
+
+    #[test]
+    fn vercel_keeps_other_settings_and_drops_loops() {
+        let existing = r#"{"cleanUrls": true, "redirects": [{"source": "/b", "destination": "/a", "permanent": true}, {"source": "/z", "destination": "/a"}]}"#;
+        let updated: Value = serde_json::from_str(&vercel(existing, "/a", "/b").unwrap()).unwrap();
+        assert_eq!(
+            updated,
+            json!({
+                "cleanUrls": true,
+                "redirects": [
+                    { "source": "/z", "destination": "/b" },
+                    { "source": "/a", "destination": "/b", "permanent": true }
+                ]
+            })
+        );
+        assert!(vercel("[]", "/a", "/b").is_err());
+    }
+
+    #[test]
+    fn json_map_repoints_old_entries() {
+        let updated = json_map("{\"/x\": \"/a\"}", "/a", "/b").unwrap();
+        assert_eq!(updated, "{\n  \"/x\": \"/b\",\n  \"/a\": \"/b\"\n}\n");
+    }
+
+    #[test]
+    fn gone_paths_answer_410() {
+        assert_eq!(
+            netlify_gone("/a  /b  301\n/older /a 301\n/x  /y  301\n", "/a"),
+            "/older  /404.html  410\n/x  /y  301\n/a  /404.html  410\n"
+        );
+        assert_eq!(
+            json_map_gone("{\"/x\": \"/a\", \"/y\": \"/z\"}", "/a").unwrap(),
+            "{\n  \"/x\": null,\n  \"/y\": \"/z\",\n  \"/a\": null\n}\n"
+        );
+    }
+}
@@ -0,0 +1,454 @@
+//! Changing a live post's slug without breaking its inbound links.
+//!
+//! One commit in the target repo moves the post's file and adds a redirect
+//! from the old URL (see `redirects`), and is pushed. Then the vault note is
+//! renamed and every link to it rewritten across the vault, the way Obsidian
+//! does on a rename: `[[old]]`, `[[old#heading|alias]]`, `![[old]]`, and
+//! markdown links to the old URL. Code blocks are left alone.
+
+use crate::config::{self, PublishTarget, VaultConfig};
+use crate::git::CommitOutcome;
+use crate::patterns::WIKI_LINK;
+use crate::publish::{push_commit, rollback, Written};
+use crate::transform::{in_inline_code, outside_code};
+use regex::{Captures, Regex};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Renamed {
+    pub old_url: String,
+    pub url: String,
+    pub commit_sha: Option<String>,
+    /// Repo-relative redirects file, None when the target doesn't keep one.
+    pub redirects_file: Option<String>,
+    /// The vault note's path, new unless `vault_error` says the move failed;
+    /// None when no vault note had the old slug.
+    pub source_path: Option<String>,
+    /// Vault notes whose links were rewritten.
+    pub relinked: Vec<String>,
+    /// The rename is committed, but the remote couldn't be reached or
+    /// refused it: why. The commit waits in the push queue.
+    pub push_error: Option<String>,
+    /// The rename is committed, but renaming the note or rewriting links to
+    /// it failed; finish that in the vault by hand.
+    pub vault_error: Option<String>,
+}
+
+pub fn rename_published_slug(
+    old: &str,
+    new: &str,
+    target_id: Option<&str>,
+) -> Result<Renamed, String> {
+    let app_config = config::get()?;
+    let target = config::resolve_target(target_id)?;
+    let renamed = rename(&target, &app_config.vault, old, new)?;
+    if let (Some(error), Some(commit_sha)) = (&renamed.push_error, &renamed.commit_sha) {
+        crate::publish::queue_change(&target, "rename", new, &renamed.url, commit_sha, error);
+    }
+    Ok(renamed)
+}
+
+fn rename(
+    target: &PublishTarget,
+    vault: &VaultConfig,
+    old: &str,
+    new: &str,
+) -> Result<Renamed, String> {
+    check_slug(new)?;
+    if old == new {
+        return Err("The new slug is the same as the old one".into());
+    }
+    let _lock =
+        crate::publish_lock::acquire(&target.repo_path, &format!("rename {} -> {}", old, new))?;
+    let location = crate::vault::find_published_locations(target, old)
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("\"{}\" isn't live on {}", old, target.name))?;
+    if !crate::vault::find_published_locations(target, new).is_empty() {
+        return Err(format!("\"{}\" is already live on {}", new, target.name));
+    }
+
+    let notes = crate::collisions::vault_notes(vault);
+    let note = match notes.get(old).map(Vec::as_slice) {
+        None | Some([]) => None,
+        Some([one]) => Some(one.clone()),
+        Some(many) => {
+            return Err(format!(
+                "{} vault notes are named {}.md; rename by hand",
+                many.len(),
+                old
+            ))
+        }
+    };
+    let new_note = note
+        .as_ref()
+        .map(|n| n.with_file_name(format!("{}.md", new)));
+    if let Some(taken) = new_note.as_ref().filter(|p| p.exists()) {
+        return Err(format!("{} already exists in the vault", taken.display()));
+    }
+
+    let repo = crate::publish::check_git_status(&target.repo_path)?;
+    let year = location.year.as_deref().unwrap_or_default();
+    let rel = target.content_path(&config::ContentPathFields {
+        year,
+        month: location.month.as_deref().unwrap_or_default(),
+        slug: new,
+        content_type: note
+            .as_deref()
+            .map(|n| crate::vault::content_type_for_path(&n.to_string_lossy()))
+            .unwrap_or("post"),
+    });
+    let old_dest = location.path.to_string_lossy().to_string();
+    let new_dest = format!("{}/{}", target.repo_path, rel);
+    if Path::new(&new_dest).exists() {
+        return Err(format!("{} already exists in the repo", rel));
+    }
+
+    let (year, month) = (location.year.as_deref(), location.month.as_deref());
+    let old_url = target.post_url(year, month, old);
+    let url = target.post_url(year, month, new);
+    let (old_path, new_path) = (
+        crate::redirects::site_path(target, &old_url),
+        crate::redirects::site_path(target, &url),
+    );
+
+    // Repo: move, redirect, commit, push. Anything failing puts the files
+    // back; a commit that can only wait for the remote is left for the push
+    // queue. The vault isn't touched until then, so a vault failure can't
+    // strand the commit.
+    let start_head = repo.head();
+    let mut written = vec![Written {
+        previous: fs::read(&old_dest).ok(),
+        dest_path: old_dest.clone(),
+    }];
+    if let Some(path) = crate::redirects::file(target) {
+        written.push(Written {
+            previous: fs::read(&path).ok(),
+            dest_path: path.to_string_lossy().to_string(),
+        });
+    }
+    if let Some(dir) = Path::new(&new_dest).parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create dir: {}", e))?;
+    }
+    fs::rename(&old_dest, &new_dest).map_err(|e| format!("Failed to move file: {}", e))?;
+    written.push(Written {
+        dest_path: new_dest.clone(),
+        previous: None,
+    });
+
+    let committed = crate::redirects::add(target, old_path, new_path).and_then(|redirected| {
+        let staged: Vec<&str> = written.iter().map(|w| w.dest_path.as_str()).collect();
+        let message = crate::commit_message::rename(&target.commit, old, new);
+        let who = crate::commit_message::authorship(&target.commit);
+        repo.add(&staged)?;
+        Ok((redirected, repo.commit_as(&message, &who)?))
+    });
+    let (redirected, outcome) =
+        committed.inspect_err(|_| rollback(&repo, start_head.as_deref(), &written))?;
+    let push_error = match outcome {
+        CommitOutcome::Created(_) => push_commit(&repo, start_head.as_deref(), &written)?,
+        CommitOutcome::NothingToCommit => None,
+    };
+    // Read after the pull, which may have rebased it.
+    let commit_sha = match outcome {
+        CommitOutcome::Created(_) => repo.head(),
+        CommitOutcome::NothingToCommit => None,
+    };
+    if let Some(e) = &push_error {
+        log::warn!("Rename of {} committed but not pushed: {}", old, e);
+    }
+
+    // Vault: the note, then everything linking to it. The links only follow
+    // once the note has moved.
+    let mut source_path = note.clone();
+    let mut relinked = Vec::new();
+    let mut vault_error = None;
+    if let (Some(from), Some(to)) = (&note, &new_note) {
+        match fs::rename(from, to) {
+            Ok(()) => source_path = Some(to.clone()),
+            Err(e) => vault_error = Some(format!("Failed to rename vault note: {}", e)),
+        }
+    }
+    if vault_error.is_none() {
+        match relink_vault(
+            vault,
+            &Relink::new(old, new, &target.domain, old_path, new_path),
+        ) {
+            Ok(changed) => relinked = changed,
+            Err(e) => vault_error = Some(e),
+        }
+    }
+    if let Some(e) = &vault_error {
+        log::warn!(
+            "Rename of {} committed but the vault wasn't updated: {}",
+            old,
+            e
+        );
+    }
+
+    Ok(Renamed {
+        redirects_file: redirected.map(|p| {
+            p.strip_prefix(&target.repo_path)
+                .unwrap_or(&p)
+                .to_string_lossy()
+                .to_string()
+        }),
+        source_path: source_path.map(|p| p.to_string_lossy().to_string()),
+        old_url,
+        url,
+        commit_sha,
+        relinked,
+        push_error,
+        vault_error,
+    })
+}
+
+/// Slugs are URL path segments: lowercase letters, digits and single hyphens.
+fn check_slug(slug: &str) -> Result<(), String> {
+    let ok = !slug.is_empty()
+        && !slug.starts_with('-')
+        && !slug.ends_with('-')
+        && !slug.contains("--")
+        && slug
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+    if ok {
+        Ok(())
+    } else {
+        Err(format!(
+            "\"{}\" isn't a valid slug (lowercase letters, digits and hyphens)",
+            slug
+        ))
+    }
+}
+
+/// Rewrites links to the renamed post in one note's text.
+struct Relink<'a> {
+    old: &'a str,
+    new: &'a str,
+    /// `](/old/path)` or `](https://domain/old/path#frag)`; group 1 is the
+    /// domain if present, group 2 any `#fragment` or `?query`.
+    old_url: Regex,
+    new_path: &'a str,
+}
+
+impl<'a> Relink<'a> {
+    fn new(old: &'a str, new: &'a str, domain: &str, old_path: &str, new_path: &'a str) -> Self {
+        let pattern = format!(
+            r"\]\(({})?{}/?((?:[#?][^)\s]*)?)\)",
+            regex::escape(domain.trim_end_matches('/')),
+            regex::escape(old_path)
+        );
+        Relink {
+            old,
+            new,
+            old_url: Regex::new(&pattern).expect("escaped pattern"),
+            new_path,
+        }
+    }
+
+    fn apply(&self, text: &str) -> Result<String, String> {
+        outside_code(text, |prose| {
+            let linked = WIKI_LINK.replace_all(prose, |caps: &Captures| {
+                let whole = caps.get(0).unwrap();
+                let name = caps[2].trim();
+                let (folder, file) = match name.rfind('/') {
+                    Some(i) => name.split_at(i + 1),
+                    None => ("", name),
+                };
+                let (stem, ext) = match file.strip_suffix(".md") {
+                    Some(stem) => (stem, ".md"),
+                    None => (file, ""),
+                };
+                if !stem.eq_ignore_ascii_case(self.old) || in_inline_code(prose, whole.start()) {
+                    return whole.as_str().to_string();
+                }
+                format!(
+                    "{}[[{}{}{}{}{}]]",
+                    &caps[1],
+                    folder,
+                    self.new,
+                    ext,
+                    caps.get(3).map_or("", |m| m.as_str()),
+                    caps.get(4)
+                        .map_or(String::new(), |m| format!("|{}", m.as_str()))
+                )
+            });
+            Ok(self
+                .old_url
+                .replace_all(&linked, |caps: &Captures| {
+                    format!(
+                        "]({}{}{})",
+                        caps.get(1).map_or("", |m| m.as_str()),
+                        self.new_path,
+                        &caps[2]
+                    )
+                })
+                .into_owned())
+        })
+    }
+}
+
+/// Run `relink` over every note in the vault, writing back the ones that
+/// changed. Hidden folders (`.obsidian`, `.trash`) are skipped.
+fn relink_vault(vault: &VaultConfig, relink: &Relink) -> Result<Vec<String>, String> {
+    let mut changed = Vec::new();
+    let notes = WalkDir::new(&vault.path)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| e.depth() == 0 || !e.file_name().to_string_lossy().starts_with('.'))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "md"));
+    for entry in notes {
+        let path: PathBuf = entry.into_path();
+        let Ok(text) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let updated = relink.apply(&text)?;
+        if updated != text {
+            fs::write(&path, updated)
+                .map_err(|e| format!("Failed to update links in {}: {}", path.display(), e))?;
+            changed.push(path.to_string_lossy().to_string());
+        }
+    }
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RedirectFormat;
+    use crate::git::tests::{remote_and_clone, scratch, set_origin, write, UNREACHABLE_URL};
+
+    #[test]
+    fn links_follow_the_rename_outside_code() {
+        let relink = Relink::new(
+            "old-post",
+            "new-post",
+            "https://example.com",
+            "/blog/2025/old-post",
+            "/blog/2025/new-post",
+        );
+        let text = "See [[old-post]], [[blog/Old-Post.md#Why|why]] and ![[old-post]].\n\
+                    [web](https://example.com/blog/2025/old-post#top) [rel](/blog/2025/old-post/)\n\
+                    [[old-posts]] [x](/blog/2025/old-post-2) `[[old-post]]`\n\
+                    ```\n[[old-post]]\n```\n";
+        assert_eq!(
+            relink.apply(text).unwrap(),
+            "See [[new-post]], [[blog/new-post.md#Why|why]] and ![[new-post]].\n\
+             [web](https://example.com/blog/2025/new-post#top) [rel](/blog/2025/new-post)\n\
+             [[old-posts]] [x](/blog/2025/old-post-2) `[[old-post]]`\n\
+             ```\n[[old-post]]\n```\n"
+        );
+    }
+
+    #[test]
+    fn slugs_must_be_url_safe() {
+        assert!(check_slug("tea-and-toast-2").is_ok());
+        for bad in ["", "Tea", "tea toast", "-tea", "tea--toast", "tea/toast"] {
+            assert!(check_slug(bad).is_err(), "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn rename_moves_redirects_and_relinks_in_one_commit() {
+        let (_remote, site) = remote_and_clone("rename");
+        let root = scratch("rename-vault");
+        let vault = VaultConfig {
+            path: root.to_string_lossy().to_string(),
+            ..config::AppConfig::default().vault
+        };
+        let mut target = config::AppConfig::default().publish_targets[0].clone();
+        target.repo_path = site.to_string_lossy().to_string();
+        target.domain = "https://example.com".into();
+        target.redirects.format = RedirectFormat::Netlify;
+        target.redirects.path = "static/_redirects".into();
+
+        write(&site, "content/blog/2025/old.md", "# Old\n");
+        write(&root, "blog/old.md", "# Old\nSee [[old#Intro]].\n");
+        write(&root, "notes/elsewhere.md", "Linked: [[old|it]]\n");
+        write(&root, ".trash/gone.md", "[[old]]\n");
+        let repo = crate::git::Repo::open(&site).unwrap();
+        repo.add(&[site.join("content").to_str().unwrap()]).unwrap();
+        repo.commit("Publish: old").unwrap();
+
+        assert!(rename(&target, &vault, "old", "Bad Slug").is_err());
+        let renamed = rename(&target, &vault, "old", "new").unwrap();
+        assert_eq!(renamed.url, "https://example.com/blog/2025/new");
+        assert_eq!(renamed.push_error, None);
+        assert_eq!(renamed.vault_error, None);
+        assert_eq!(renamed.redirects_file.as_deref(), Some("static/_redirects"));
+        assert!(!site.join("content/blog/2025/old.md").exists());
+        assert!(site.join("content/blog/2025/new.md").exists());
+        assert_eq!(
+            fs::read_to_string(site.join("static/_redirects")).unwrap(),
+            "/blog/2025/old  /blog/2025/new  301\n"
+        );
+        assert!(repo.status().unwrap().entries.is_empty());
+
+        assert_eq!(
+            fs::read_to_string(root.join("blog/new.md")).unwrap(),
+            "# Old\nSee [[new#Intro]].\n"
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("notes/elsewhere.md")).unwrap(),
+            "Linked: [[new|it]]\n"
+        );
+        assert_eq!(
+            fs::read_to_string(root.join(".trash/gone.md")).unwrap(),
+            "[[old]]\n"
+        );
+        assert_eq!(renamed.relinked.len(), 2);
+
+        // The old slug is gone, so a second rename has nothing to move.
+        assert!(rename(&target, &vault, "old", "newer").is_err());
+    }
+
+    #[test]
+    fn a_rename_that_cant_push_is_rolled_back_or_left_for_the_queue() {
+        let (_remote, site) = remote_and_clone("rename-push");
+        let root = scratch("rename-push-vault");
+        let vault = VaultConfig {
+            path: root.to_string_lossy().to_string(),
+            ..config::AppConfig::default().vault
+        };
+        let mut target = config::AppConfig::default().publish_targets[0].clone();
+        target.repo_path = site.to_string_lossy().to_string();
+        target.redirects.format = RedirectFormat::Netlify;
+        target.redirects.path = "static/_redirects".into();
+
+        write(&site, "content/blog/2025/old.md", "# Old\n");
+        write(&root, "blog/old.md", "[[old]]\n");
+        let repo = crate::git::Repo::open(&site).unwrap();
+        repo.add(&[site.join("content").to_str().unwrap()]).unwrap();
+        repo.commit("Publish: old").unwrap();
+        let head = repo.head();
+
+        // A remote that isn't there won't be back: nothing changes anywhere.
+        set_origin(&site, site.with_file_name("gone.git").to_str().unwrap());
+        assert!(rename(&target, &vault, "old", "new").is_err());
+        assert_eq!(repo.head(), head);
+        assert!(repo.status().unwrap().entries.is_empty());
+        assert!(site.join("content/blog/2025/old.md").exists());
+        assert!(!site.join("content/blog/2025/new.md").exists());
+        assert!(!site.join("static/_redirects").exists());
+        assert_eq!(
+            fs::read_to_string(root.join("blog/old.md")).unwrap(),
+            "[[old]]\n"
+        );
+
+        // Out of reach: the commit stays for the queue and the vault follows.
+        set_origin(&site, UNREACHABLE_URL);
+        let renamed = rename(&target, &vault, "old", "new").unwrap();
+        assert!(renamed.push_error.is_some());
+        assert_eq!(renamed.commit_sha, repo.head());
+        assert_ne!(repo.head(), head);
+        assert_eq!(
+            fs::read_to_string(root.join("blog/new.md")).unwrap(),
+            "[[new]]\n"
+        );
+    }
+}
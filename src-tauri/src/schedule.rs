@@ -0,0 +1,332 @@
+//! Reads the times in `publish_at` / `unpublish_at`.
+//!
+//! Besides RFC 3339, a schedule can be written as a local time
+//! (`2026-03-01 09:00`, `2026-03-01T09:00`), a date (`2026-03-01`, midnight),
+//! or relative to now (`tomorrow 9am`, `friday at noon`, `in 2 hours`). Local
+//! times are read in `AppConfig.timezone`, or the system zone when that's
+//! unset. Values are normalized to RFC 3339 with the zone's offset when
+//! they're written, so a relative time is pinned to when it was set.
+
+use crate::config::AppConfig;
+use crate::frontmatter::Frontmatter;
+use crate::lint::Finding;
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, SecondsFormat,
+    TimeZone, Utc, Weekday,
+};
+use chrono_tz::Tz;
+
+/// How late a schedule can be before the vault flags it. The checker wakes
+/// for each scheduled time, and at least every `MAX_SCHEDULE_WAIT_SECS`
+/// (five minutes) in case the machine slept through one; this leaves room
+/// for that catch-up and a retry, so anything later didn't fire.
+const OVERDUE_AFTER_MINUTES: i64 = 10;
+
+/// The zone local schedule times are read in.
+#[derive(Debug, Clone, Copy)]
+pub enum Zone {
+    Local,
+    Named(Tz),
+}
+
+impl Zone {
+    /// An IANA zone name (`Europe/London`); blank means the system zone.
+    pub fn named(name: &str) -> Result<Zone, String> {
+        match name.trim() {
+            "" => Ok(Zone::Local),
+            name => name
+                .parse::<Tz>()
+                .map(Zone::Named)
+                .map_err(|_| format!("Unknown timezone \"{}\"", name)),
+        }
+    }
+
+    /// The configured zone, falling back to the system zone if it's not one
+    /// we know.
+    pub fn from_config(app_config: &AppConfig) -> Zone {
+        let name = app_config.timezone.as_deref().unwrap_or_default();
+        Zone::named(name).unwrap_or_else(|e| {
+            log::warn!("{}, using the system timezone", e);
+            Zone::Local
+        })
+    }
+
+    fn at(&self, instant: DateTime<Utc>) -> DateTime<FixedOffset> {
+        match self {
+            Zone::Local => instant.with_timezone(&chrono::Local).fixed_offset(),
+            Zone::Named(tz) => instant.with_timezone(tz).fixed_offset(),
+        }
+    }
+
+    /// A wall-clock time in this zone. An ambiguous time (clocks going back)
+    /// takes the earlier instant; one skipped by clocks going forward moves
+    /// an hour later.
+    fn resolve(&self, local: NaiveDateTime) -> Option<DateTime<FixedOffset>> {
+        let lookup = |t: &NaiveDateTime| match self {
+            Zone::Local => chrono::Local
+                .from_local_datetime(t)
+                .earliest()
+                .map(|t| t.fixed_offset()),
+            Zone::Named(tz) => tz
+                .from_local_datetime(t)
+                .earliest()
+                .map(|t| t.fixed_offset()),
+        };
+        lookup(&local).or_else(|| lookup(&(local + Duration::hours(1))))
+    }
+}
+
+/// Read a schedule value. `now` anchors the relative forms.
+pub fn parse(
+    input: &str,
+    zone: &Zone,
+    now: DateTime<Utc>,
+) -> Result<DateTime<FixedOffset>, String> {
+    let raw = input.trim();
+    let unreadable = || format!("Can't read \"{}\" as a date and time", raw);
+    if let Ok(t) = DateTime::parse_from_rfc3339(raw)
+        .or_else(|_| DateTime::parse_from_rfc3339(&raw.replacen(' ', "T", 1)))
+    {
+        return Ok(t);
+    }
+
+    let mut text = raw.to_lowercase();
+    // `2026-03-01t09:00`: split the date from the time.
+    if text.get(..10).and_then(parse_date).is_some() && text[10..].starts_with('t') {
+        text.replace_range(10..11, " ");
+    }
+    let words: Vec<&str> = text.split_whitespace().filter(|w| *w != "at").collect();
+
+    match words.as_slice() {
+        ["now"] => return Ok(zone.at(now)),
+        ["in", n, unit] => {
+            let n: i64 = n.parse().map_err(|_| unreadable())?;
+            let step = match unit.trim_end_matches('s') {
+                "min" | "minute" => Duration::minutes(n),
+                "hr" | "hour" => Duration::hours(n),
+                "day" => Duration::days(n),
+                "week" => Duration::weeks(n),
+                _ => return Err(unreadable()),
+            };
+            return Ok(zone.at(now + step));
+        }
+        _ => {}
+    }
+
+    let today = zone.at(now).date_naive();
+    let (date, rest) = match words.split_first() {
+        Some((first, rest)) => match day(first, today) {
+            Some(date) => (date, rest),
+            None => (today, &words[..]),
+        },
+        None => return Err(unreadable()),
+    };
+    let time = match rest {
+        [] => NaiveTime::MIN,
+        rest => parse_time(&rest.concat()).ok_or_else(unreadable)?,
+    };
+    zone.resolve(date.and_time(time)).ok_or_else(unreadable)
+}
+
+/// Whether `input` is read relative to now (`tomorrow 9am`, `in 2 hours`,
+/// a bare time), so that it only holds still once it's been normalized.
+pub fn is_relative(input: &str) -> bool {
+    let raw = input.trim();
+    let rfc3339 = DateTime::parse_from_rfc3339(raw)
+        .or_else(|_| DateTime::parse_from_rfc3339(&raw.replacen(' ', "T", 1)));
+    rfc3339.is_err() && raw.get(..10).and_then(parse_date).is_none()
+}
+
+/// `parse`, written back as RFC 3339 in the zone (`2026-03-01T09:00:00-05:00`).
+pub fn normalize(input: &str, zone: &Zone, now: DateTime<Utc>) -> Result<String, String> {
+    parse(input, zone, now).map(|t| t.to_rfc3339_opts(SecondsFormat::Secs, true))
+}
+
+/// `normalize` in the configured zone, for a value about to be written.
+pub fn normalize_now(input: &str) -> Result<String, String> {
+    let zone = Zone::from_config(&crate::config::get()?);
+    normalize(input, &zone, Utc::now())
+}
+
+/// Lint findings for the schedule in a note's frontmatter block, on the
+/// line of the field: a value that can't be read (`schedule/unreadable`), a
+/// relative one typed into the note, which only Dispatch's own writes pin
+/// (`schedule/unpinned`), or one that should have fired by now and didn't
+/// (`schedule/overdue`).
+pub fn findings(frontmatter: &str, is_live: bool, zone: &Zone, now: DateTime<Utc>) -> Vec<Finding> {
+    let (fields, _) = Frontmatter::parse(frontmatter);
+    let overdue =
+        |t: DateTime<FixedOffset>| now - t.to_utc() > Duration::minutes(OVERDUE_AFTER_MINUTES);
+    let mut findings = Vec::new();
+    for (key, pending) in [("publish_at", !is_live), ("unpublish_at", is_live)] {
+        let Some(value) = fields.get_str(key) else {
+            continue;
+        };
+        let (rule, message) = match parse(&value, zone, now) {
+            Err(_) => ("schedule/unreadable", format!("Unreadable {}", key)),
+            Ok(_) if is_relative(&value) => ("schedule/unpinned", format!("Unpinned {}", key)),
+            Ok(t) if pending && overdue(t) => {
+                let action = key.trim_end_matches("_at");
+                ("schedule/overdue", format!("Scheduled {} overdue", action))
+            }
+            Ok(_) => continue,
+        };
+        let (start, end) = field_span(frontmatter, key);
+        findings.push(crate::lint::finding(frontmatter, rule, start, end, message));
+    }
+    findings
+}
+
+/// The line `key:` is written on in a frontmatter block, less its newline;
+/// the top of the block if it isn't found there.
+fn field_span(frontmatter: &str, key: &str) -> (usize, usize) {
+    let mut start = 0;
+    for line in frontmatter.split_inclusive('\n') {
+        let field = line.trim_start().strip_prefix(key);
+        if field.is_some_and(|rest| rest.starts_with(':')) {
+            return (start, start + line.trim_end().len());
+        }
+        start += line.len();
+    }
+    (0, 0)
+}
+
+fn parse_date(text: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(text, "%Y-%m-%d").ok()
+}
+
+/// A date, `today`, `tomorrow`, or a weekday: the next one, a week out if
+/// it's today.
+fn day(word: &str, today: NaiveDate) -> Option<NaiveDate> {
+    match word {
+        "today" => return Some(today),
+        "tomorrow" => return today.succ_opt(),
+        _ => {}
+    }
+    if let Some(date) = parse_date(word) {
+        return Some(date);
+    }
+    let weekday: Weekday = word.parse().ok()?;
+    let ahead = (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+    Some(today + Duration::days(if ahead == 0 { 7 } else { ahead as i64 }))
+}
+
+/// `09:00`, `17:30:15`, `9am`, `9:30pm`, `noon`, `midnight`.
+fn parse_time(text: &str) -> Option<NaiveTime> {
+    match text {
+        "noon" => return NaiveTime::from_hms_opt(12, 0, 0),
+        "midnight" => return Some(NaiveTime::MIN),
+        _ => {}
+    }
+    let (clock, pm) = match (text.strip_suffix("am"), text.strip_suffix("pm")) {
+        (Some(clock), _) => (clock, Some(false)),
+        (_, Some(clock)) => (clock, Some(true)),
+        _ => (text, None),
+    };
+    let parts: Vec<u32> = clock
+        .split(':')
+        .map(|p| p.parse().ok())
+        .collect::<Option<_>>()?;
+    let (hour, minute, second) = match (parts.as_slice(), pm) {
+        // A bare number is only a time with am/pm after it.
+        ([hour], Some(_)) => (*hour, 0, 0),
+        ([hour, minute], _) => (*hour, *minute, 0),
+        ([hour, minute, second], _) => (*hour, *minute, *second),
+        _ => return None,
+    };
+    let hour = match pm {
+        Some(_) if !(1..=12).contains(&hour) => return None,
+        Some(pm) => hour % 12 + if pm { 12 } else { 0 },
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, second)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_york() -> Zone {
+        Zone::named("America/New_York").unwrap()
+    }
+
+    // Friday 2026-02-27, 14:30 in New York.
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 2, 27, 19, 30, 0).unwrap()
+    }
+
+    fn read(input: &str) -> String {
+        normalize(input, &new_york(), now()).unwrap()
+    }
+
+    #[test]
+    fn absolute_times_are_read_in_the_zone() {
+        assert_eq!(read("2026-03-01T09:00:00Z"), "2026-03-01T09:00:00Z");
+        assert_eq!(
+            read("2026-03-01 09:00:00+01:00"),
+            "2026-03-01T09:00:00+01:00"
+        );
+        assert_eq!(read("2026-03-01 09:00"), "2026-03-01T09:00:00-05:00");
+        assert_eq!(read("2026-03-01T09:00"), "2026-03-01T09:00:00-05:00");
+        assert_eq!(read("2026-03-01"), "2026-03-01T00:00:00-05:00");
+        assert_eq!(read("2026-03-01 9:15pm"), "2026-03-01T21:15:00-05:00");
+        // After the clocks go forward on 2026-03-08, and inside the gap itself.
+        assert_eq!(read("2026-03-09 09:00"), "2026-03-09T09:00:00-04:00");
+        assert_eq!(read("2026-03-08 02:30"), "2026-03-08T03:30:00-04:00");
+        for bad in ["", "soon", "2026-13-01", "25:00", "9", "13pm", "in a while"] {
+            assert!(parse(bad, &new_york(), now()).is_err(), "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn relative_times_are_pinned_to_now() {
+        assert_eq!(read("now"), "2026-02-27T14:30:00-05:00");
+        assert_eq!(read("tomorrow 9am"), "2026-02-28T09:00:00-05:00");
+        assert_eq!(read("Tomorrow at 9 AM"), "2026-02-28T09:00:00-05:00");
+        assert_eq!(read("today noon"), "2026-02-27T12:00:00-05:00");
+        assert_eq!(read("5:45pm"), "2026-02-27T17:45:00-05:00");
+        assert_eq!(read("monday"), "2026-03-02T00:00:00-05:00");
+        assert_eq!(read("friday 12am"), "2026-03-06T00:00:00-05:00");
+        assert_eq!(read("in 90 minutes"), "2026-02-27T16:00:00-05:00");
+        assert_eq!(read("in 1 week"), "2026-03-06T14:30:00-05:00");
+    }
+
+    #[test]
+    fn flags_unreadable_and_missed_schedules() {
+        let lint = |publish: Option<&str>, unpublish: Option<&str>, live| {
+            let mut frontmatter = "---\ntitle: Tea\n".to_string();
+            for (key, value) in [("publish_at", publish), ("unpublish_at", unpublish)] {
+                if let Some(value) = value {
+                    frontmatter += &format!("{}: {}\n", key, value);
+                }
+            }
+            frontmatter += "---\n";
+            findings(&frontmatter, live, &new_york(), now())
+        };
+        let messages = |findings: Vec<Finding>| -> Vec<String> {
+            findings.into_iter().map(|f| f.message).collect()
+        };
+        let unreadable = lint(Some("next week-ish"), None, false);
+        assert_eq!(unreadable[0].rule, "schedule/unreadable");
+        assert_eq!((unreadable[0].line, unreadable[0].column), (3, 1));
+        assert_eq!(messages(unreadable), ["Unreadable publish_at"]);
+        assert_eq!(
+            messages(lint(
+                Some("2026-02-27 09:00"),
+                Some("2026-02-27 14:28"),
+                false
+            )),
+            ["Scheduled publish overdue"]
+        );
+        let overdue = lint(Some("2026-02-27 09:00"), Some("2026-02-27 14:00"), true);
+        assert_eq!(overdue[0].rule, "schedule/overdue");
+        assert_eq!(overdue[0].line, 4);
+        assert_eq!(messages(overdue), ["Scheduled unpublish overdue"]);
+        assert_eq!(
+            messages(lint(Some("tomorrow"), Some("in 2 days"), false)),
+            ["Unpinned publish_at", "Unpinned unpublish_at"]
+        );
+        assert!(lint(Some("2026-03-02 09:00"), None, false).is_empty());
+        assert!(lint(None, None, true).is_empty());
+    }
+}
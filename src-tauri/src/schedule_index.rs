@@ -0,0 +1,209 @@
+//! Which vault notes have a `publish_at` / `unpublish_at`, so the schedule
+//! checker can sleep until the next one comes due instead of rescanning the
+//! vault every minute.
+//!
+//! Built by walking the whole vault once at startup (no file-list cap), then
+//! kept current by `vault_watcher`, which passes along the notes each save
+//! touched. Anything that changes the index wakes the checker.
+
+use crate::schedule::{self, Zone};
+use crate::vault::Scan;
+use chrono::{DateTime, FixedOffset, Utc};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use tokio::sync::Notify;
+use walkdir::WalkDir;
+
+static INDEX: LazyLock<Mutex<HashMap<PathBuf, Times>>> = LazyLock::new(Default::default);
+static CHANGED: LazyLock<Notify> = LazyLock::new(Notify::new);
+
+/// When a note is scheduled to go up and/or come down.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Times {
+    pub publish_at: Option<DateTime<FixedOffset>>,
+    pub unpublish_at: Option<DateTime<FixedOffset>>,
+}
+
+impl Times {
+    fn next(&self) -> Option<DateTime<FixedOffset>> {
+        self.publish_at.into_iter().chain(self.unpublish_at).min()
+    }
+}
+
+/// Walk the vault and replace the index.
+pub fn rebuild() -> Result<(), String> {
+    let scan = Scan::new()?;
+    let index: HashMap<PathBuf, Times> = WalkDir::new(scan.vault_path())
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let times = read(e.path(), &scan)?;
+            Some((e.into_path(), times))
+        })
+        .collect();
+    log::info!("schedule_index: {} scheduled note(s)", index.len());
+    *INDEX.lock().map_err(|e| e.to_string())? = index;
+    CHANGED.notify_one();
+    Ok(())
+}
+
+/// Re-read notes that changed on disk; ones that were deleted, or no longer
+/// have a schedule, drop out.
+pub fn refresh(paths: &[PathBuf]) {
+    let Ok(scan) = Scan::new() else {
+        return;
+    };
+    let updates: Vec<(&PathBuf, Option<Times>)> =
+        paths.iter().map(|path| (path, read(path, &scan))).collect();
+    let Ok(mut index) = INDEX.lock() else {
+        return;
+    };
+    let mut changed = false;
+    for (path, times) in updates {
+        let previous = match times {
+            Some(times) => index.insert(path.clone(), times),
+            None => index.remove(path),
+        };
+        changed |= previous != times;
+    }
+    if changed {
+        CHANGED.notify_one();
+    }
+}
+
+/// Notes with a time that has come, and the next time after `now` that one
+/// will.
+pub fn due(now: DateTime<Utc>) -> (Vec<(PathBuf, Times)>, Option<DateTime<FixedOffset>>) {
+    match INDEX.lock() {
+        Ok(index) => due_in(&index, now),
+        Err(_) => (Vec::new(), None),
+    }
+}
+
+/// Resolves once the index has changed since the last call.
+pub async fn changed() {
+    CHANGED.notified().await
+}
+
+fn due_in(
+    index: &HashMap<PathBuf, Times>,
+    now: DateTime<Utc>,
+) -> (Vec<(PathBuf, Times)>, Option<DateTime<FixedOffset>>) {
+    let mut due = Vec::new();
+    let mut next: Option<DateTime<FixedOffset>> = None;
+    for (path, times) in index {
+        let (past, future): (Vec<_>, Vec<_>) = [times.publish_at, times.unpublish_at]
+            .into_iter()
+            .flatten()
+            .partition(|t| *t <= now);
+        if !past.is_empty() {
+            due.push((path.clone(), *times));
+        }
+        next = future.into_iter().chain(next).min();
+    }
+    (due, next)
+}
+
+fn read(path: &Path, scan: &Scan) -> Option<Times> {
+    if path.extension().and_then(|ext| ext.to_str()) != Some("md") || !scan.lists(path) {
+        return None;
+    }
+    let (publish_at, unpublish_at) = crate::vault::schedule_fields(path)?;
+    let time = |key, value: Option<String>| read_time(path, key, &value?, scan.zone(), scan.now());
+    let times = Times {
+        publish_at: time("publish_at", publish_at),
+        unpublish_at: time("unpublish_at", unpublish_at),
+    };
+    times.next().map(|_| times)
+}
+
+/// Read a schedule value. Nothing is written back: only Dispatch's own
+/// writes normalize a value, since a note being typed into may be half
+/// done. A relative value (`tomorrow 9am`) would move every time it's read,
+/// so it isn't scheduled; neither is one that can't be read. The vault scan
+/// warns about both.
+fn read_time(
+    path: &Path,
+    key: &str,
+    value: &str,
+    zone: &Zone,
+    now: DateTime<Utc>,
+) -> Option<DateTime<FixedOffset>> {
+    match schedule::parse(value, zone, now) {
+        Ok(_) if schedule::is_relative(value) => {
+            log::warn!("Ignoring unpinned {} in {}: {}", key, path.display(), value);
+            None
+        }
+        Ok(at) => Some(at),
+        Err(e) => {
+            log::warn!("Ignoring {} in {}: {}", key, path.display(), e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(hour: u32) -> Option<DateTime<FixedOffset>> {
+        Some(
+            Utc.with_ymd_and_hms(2026, 3, 1, hour, 0, 0)
+                .unwrap()
+                .fixed_offset(),
+        )
+    }
+
+    #[test]
+    fn due_lists_passed_times_and_finds_the_next() {
+        let index = HashMap::from([
+            (
+                PathBuf::from("/v/blog/a.md"),
+                Times {
+                    publish_at: at(9),
+                    unpublish_at: at(18),
+                },
+            ),
+            (
+                PathBuf::from("/v/blog/b.md"),
+                Times {
+                    publish_at: at(12),
+                    unpublish_at: None,
+                },
+            ),
+        ]);
+        let now = Utc.with_ymd_and_hms(2026, 3, 1, 10, 0, 0).unwrap();
+        let (due, next) = due_in(&index, now);
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].0, PathBuf::from("/v/blog/a.md"));
+        assert_eq!(next, at(12));
+
+        let (due, next) = due_in(&index, now + chrono::Duration::days(1));
+        assert_eq!(due.len(), 2);
+        assert_eq!(next, None);
+    }
+
+    #[test]
+    fn only_fixed_times_are_scheduled() {
+        let note = Path::new("/vault/blog/note.md");
+        let zone = Zone::named("UTC").unwrap();
+        let now = Utc.with_ymd_and_hms(2026, 3, 1, 10, 0, 0).unwrap();
+        let read = |value| read_time(note, "publish_at", value, &zone, now);
+
+        assert_eq!(
+            read("2026-03-02 09:00"),
+            Some(
+                Utc.with_ymd_and_hms(2026, 3, 2, 9, 0, 0)
+                    .unwrap()
+                    .fixed_offset()
+            )
+        );
+        assert_eq!(read("2026-03-02T09:00:00Z"), read("2026-03-02 09:00"));
+        // Half-typed or relative: left for the vault scan to warn about.
+        assert_eq!(read("tomorrow"), None);
+        assert_eq!(read("tomorrow 9am"), None);
+        assert_eq!(read("whenever"), None);
+    }
+}
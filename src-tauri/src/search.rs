@@ -0,0 +1,709 @@
+//! Full-text search over the vault's notes and the posts live in each
+//! target's repo, with SQLite FTS5 tables in the vault index's database.
+//!
+//! `vault_index` hands each note's text over as it re-reads the note. Repo
+//! posts are checked when a search runs: a stat of every post file, and a
+//! re-read of the ones whose mtime or size moved. A post that's the live
+//! copy of a vault note is found through the note, so it isn't listed
+//! twice.
+//!
+//! A query is words and "quoted phrases", all of which have to match.
+//! Words are stemmed, so "publish" finds "publishing". Results are ranked
+//! by BM25 with titles weighted over body text, and carry the lines that
+//! matched.
+
+use crate::config::PublishTarget;
+use crate::frontmatter::Frontmatter;
+use crate::vault::{self, PublishedLocation};
+use crate::vault_index::{self, Note};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+
+/// Results returned when the query doesn't set a limit.
+const DEFAULT_LIMIT: usize = 50;
+
+/// Matching lines shown per result.
+const SNIPPETS: usize = 3;
+
+/// Characters of a matching line shown.
+const SNIPPET_CHARS: usize = 160;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SearchQuery {
+    /// Words and "quoted phrases"; every one has to match. Empty lists
+    /// everything the filters let through.
+    pub text: String,
+    /// Tags a result has to have, all of them.
+    pub tags: Vec<String>,
+    /// A folder the file has to be under, relative to the vault or to its
+    /// repo (`drafts`, `content/blog/2024`).
+    pub dir: Option<String>,
+    /// "post" or "weeknote".
+    pub content_type: Option<String>,
+    /// Only published (true) or only unpublished (false) results.
+    pub published: Option<bool>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResults {
+    pub hits: Vec<SearchHit>,
+    /// Matches before `limit` cut them down.
+    pub total: usize,
+    pub facets: Facets,
+}
+
+/// Match counts on either side of `published`, whichever side the query
+/// asked for.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Facets {
+    pub published: usize,
+    pub unpublished: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    /// "vault" for a note, "repo" for a post that's only in a target's repo.
+    pub source: String,
+    pub path: String,
+    pub slug: String,
+    pub title: Option<String>,
+    pub content_type: String,
+    pub tags: Vec<String>,
+    /// The repo post's target; None for notes.
+    pub target_id: Option<String>,
+    pub published_url: Option<String>,
+    /// Higher is a better match; 0 when the query had no text.
+    pub score: f64,
+    pub snippets: Vec<Snippet>,
+}
+
+/// A line that matched.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Snippet {
+    /// 1-based, in the file.
+    pub line: usize,
+    pub text: String,
+}
+
+/// A post file in a target's repo, as the last search saw it.
+struct RepoPost {
+    target_id: String,
+    rel_path: String,
+    slug: String,
+    url: String,
+    title: Option<String>,
+    tags: Vec<String>,
+}
+
+// ---------------------------------------------------------------------------
+// Database
+// ---------------------------------------------------------------------------
+
+/// Create the search tables. True when they weren't there yet, so notes
+/// the index already holds need their text added.
+pub(crate) fn init_schema(conn: &Connection) -> Result<bool, String> {
+    let existed: bool = conn
+        .query_row(
+            "SELECT COUNT(*) > 0 FROM sqlite_master WHERE name = 'search_docs'",
+            [],
+            |r| r.get(0),
+        )
+        .map_err(|e| format!("Query failed: {}", e))?;
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS search_docs (
+            id     INTEGER PRIMARY KEY,
+            source TEXT NOT NULL,
+            path   TEXT NOT NULL,
+            UNIQUE (source, path)
+        );
+        CREATE VIRTUAL TABLE IF NOT EXISTS search_text USING fts5(
+            title,
+            content,
+            tokenize = 'porter unicode61'
+        );
+        CREATE TABLE IF NOT EXISTS repo_posts (
+            path      TEXT PRIMARY KEY,
+            target_id TEXT NOT NULL,
+            mtime_ms  INTEGER NOT NULL,
+            size      INTEGER NOT NULL,
+            rel_path  TEXT NOT NULL,
+            slug      TEXT NOT NULL,
+            url       TEXT NOT NULL,
+            title     TEXT,
+            tags      TEXT NOT NULL,
+            draft     INTEGER NOT NULL
+        );",
+    )
+    .map_err(|e| format!("Failed to create search schema: {}", e))?;
+    Ok(!existed)
+}
+
+/// Replace a note's text.
+pub(crate) fn index_note(
+    conn: &Connection,
+    path: &str,
+    title: Option<&str>,
+    content: &str,
+) -> Result<(), String> {
+    index_doc(conn, "vault", path, title, content)
+}
+
+/// Drop a note's text, or a deleted folder's notes.
+pub(crate) fn remove_note(conn: &Connection, path: &str) -> Result<(), String> {
+    remove_docs(conn, "vault", path)
+}
+
+fn index_doc(
+    conn: &Connection,
+    source: &str,
+    path: &str,
+    title: Option<&str>,
+    content: &str,
+) -> Result<(), String> {
+    remove_docs(conn, source, path)?;
+    conn.execute(
+        "INSERT INTO search_docs (source, path) VALUES (?1, ?2)",
+        params![source, path],
+    )
+    .map_err(|e| format!("Insert failed: {}", e))?;
+    conn.execute(
+        "INSERT INTO search_text (rowid, title, content) VALUES (?1, ?2, ?3)",
+        params![conn.last_insert_rowid(), title, content],
+    )
+    .map_err(|e| format!("Insert failed: {}", e))?;
+    Ok(())
+}
+
+fn remove_docs(conn: &Connection, source: &str, path: &str) -> Result<(), String> {
+    let where_clause = "source = ?1 AND (path = ?2 OR substr(path, 1, length(?2) + 1) = ?2 || '/')";
+    conn.execute(
+        &format!(
+            "DELETE FROM search_text WHERE rowid IN (SELECT id FROM search_docs WHERE {})",
+            where_clause
+        ),
+        params![source, path],
+    )
+    .map_err(|e| format!("Delete failed: {}", e))?;
+    conn.execute(
+        &format!("DELETE FROM search_docs WHERE {}", where_clause),
+        params![source, path],
+    )
+    .map_err(|e| format!("Delete failed: {}", e))?;
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Repo posts
+// ---------------------------------------------------------------------------
+
+/// Re-read the repo posts that changed since the last search, and drop
+/// the ones that are gone.
+fn sync_posts(conn: &Connection, targets: &[PublishTarget]) -> Result<(), String> {
+    let known: HashMap<String, (i64, i64)> = {
+        let mut stmt = conn
+            .prepare("SELECT path, mtime_ms, size FROM repo_posts")
+            .map_err(|e| format!("Query failed: {}", e))?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, (row.get(1)?, row.get(2)?))))
+            .map_err(|e| format!("Query failed: {}", e))?
+            .collect::<Result<_, _>>()
+            .map_err(|e| format!("Row read failed: {}", e))?;
+        rows
+    };
+
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|e| format!("Search index transaction: {}", e))?;
+    let mut seen = HashSet::new();
+    for target in targets {
+        for copy in vault::find_all_published(target) {
+            let path = copy.path.to_string_lossy().to_string();
+            // Targets can share a repo.
+            if seen.contains(&path) {
+                continue;
+            }
+            let Ok(meta) = fs::metadata(&copy.path) else {
+                continue;
+            };
+            let stamp = (vault_index::mtime_ms(&meta), meta.len() as i64);
+            if known.get(&path) != Some(&stamp) {
+                let Ok(content) = fs::read_to_string(&copy.path) else {
+                    continue;
+                };
+                index_post(&tx, target, &copy, stamp, &content)?;
+            }
+            seen.insert(path);
+        }
+    }
+    for path in known.keys().filter(|p| !seen.contains(*p)) {
+        tx.execute("DELETE FROM repo_posts WHERE path = ?1", params![path])
+            .map_err(|e| format!("Delete failed: {}", e))?;
+        remove_docs(&tx, "repo", path)?;
+    }
+    tx.commit()
+        .map_err(|e| format!("Search index commit: {}", e))
+}
+
+fn index_post(
+    conn: &Connection,
+    target: &PublishTarget,
+    copy: &PublishedLocation,
+    (mtime_ms, size): (i64, i64),
+    content: &str,
+) -> Result<(), String> {
+    let path = copy.path.to_string_lossy().to_string();
+    let (frontmatter, body) = Frontmatter::parse(content);
+    let title = frontmatter
+        .get_str("title")
+        .filter(|t| !t.is_empty())
+        .or_else(|| vault::extract_h1_title(body));
+    // Drafts stay in the table so they aren't re-read every search, but
+    // aren't searched.
+    let draft = frontmatter.get_bool("draft");
+    let rel_path = copy
+        .path
+        .strip_prefix(&target.repo_path)
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|_| path.clone());
+    conn.execute(
+        "INSERT OR REPLACE INTO repo_posts
+         (path, target_id, mtime_ms, size, rel_path, slug, url, title, tags, draft)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            path,
+            target.id,
+            mtime_ms,
+            size,
+            rel_path,
+            copy.slug,
+            target.post_url(copy.year.as_deref(), copy.month.as_deref(), &copy.slug),
+            title,
+            serde_json::to_string(&frontmatter.tags()).unwrap_or_default(),
+            draft,
+        ],
+    )
+    .map_err(|e| format!("Insert failed: {}", e))?;
+    if draft {
+        remove_docs(conn, "repo", &path)
+    } else {
+        index_doc(conn, "repo", &path, title.as_deref(), content)
+    }
+}
+
+fn select_posts(conn: &Connection) -> Result<HashMap<String, RepoPost>, String> {
+    let mut stmt = conn
+        .prepare("SELECT path, target_id, rel_path, slug, url, title, tags FROM repo_posts")
+        .map_err(|e| format!("Query failed: {}", e))?;
+    let posts = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get(0)?,
+                RepoPost {
+                    target_id: row.get(1)?,
+                    rel_path: row.get(2)?,
+                    slug: row.get(3)?,
+                    url: row.get(4)?,
+                    title: row.get(5)?,
+                    tags: serde_json::from_str(&row.get::<_, String>(6)?).unwrap_or_default(),
+                },
+            ))
+        })
+        .map_err(|e| format!("Query failed: {}", e))?
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("Row read failed: {}", e))?;
+    Ok(posts)
+}
+
+// ---------------------------------------------------------------------------
+// Searching
+// ---------------------------------------------------------------------------
+
+/// Search the vault and every target's published posts.
+pub fn search(query: &SearchQuery) -> Result<SearchResults, String> {
+    let app_config = crate::config::get()?;
+    vault_index::with_index(|conn| {
+        sync_posts(conn, &app_config.publish_targets)?;
+        search_in(conn, &app_config.vault.path, query)
+    })
+}
+
+fn search_in(
+    conn: &Connection,
+    vault_path: &str,
+    query: &SearchQuery,
+) -> Result<SearchResults, String> {
+    let terms = terms(&query.text);
+    let notes: HashMap<String, Note> = vault_index::select_notes(conn)?
+        .into_iter()
+        .map(|note| (note.path.clone(), note))
+        .collect();
+    let posts = select_posts(conn)?;
+    // A repo post with a vault note's slug is that note's copy, on whichever
+    // target it went out to; the note's hit stands for it.
+    let slugs: HashSet<&str> = notes.values().map(|note| note.slug.as_str()).collect();
+    let live_copies: HashSet<&str> = posts
+        .iter()
+        .filter(|(_, post)| slugs.contains(post.slug.as_str()))
+        .map(|(path, _)| path.as_str())
+        .collect();
+
+    let mut facets = Facets::default();
+    let mut hits = Vec::new();
+    for (id, source, path, score) in matches(conn, &terms)? {
+        let found = if source == "vault" {
+            notes.get(&path).map(|note| {
+                let rel = path.strip_prefix(vault_path).unwrap_or(&path);
+                (
+                    note_hit(note, score),
+                    rel.trim_start_matches('/').to_string(),
+                )
+            })
+        } else if live_copies.contains(path.as_str()) {
+            None
+        } else {
+            posts
+                .get(&path)
+                .map(|post| (post_hit(&path, post, score), post.rel_path.clone()))
+        };
+        let Some((hit, rel)) = found else {
+            continue;
+        };
+        if !passes_filters(query, &hit, &rel) {
+            continue;
+        }
+        let published = hit.published_url.is_some();
+        if published {
+            facets.published += 1;
+        } else {
+            facets.unpublished += 1;
+        }
+        if query.published.is_some_and(|p| p != published) {
+            continue;
+        }
+        hits.push((id, hit));
+    }
+
+    let total = hits.len();
+    hits.truncate(query.limit.unwrap_or(DEFAULT_LIMIT));
+    let hits = hits
+        .into_iter()
+        .map(|(id, mut hit)| {
+            let content: Option<String> = conn
+                .query_row(
+                    "SELECT content FROM search_text WHERE rowid = ?1",
+                    params![id],
+                    |r| r.get(0),
+                )
+                .optional()
+                .map_err(|e| format!("Query failed: {}", e))?;
+            hit.snippets = snippets(&content.unwrap_or_default(), &terms);
+            Ok(hit)
+        })
+        .collect::<Result<_, String>>()?;
+    Ok(SearchResults {
+        hits,
+        total,
+        facets,
+    })
+}
+
+/// Documents matching every term, best first: (id, source, path, score).
+fn matches(conn: &Connection, terms: &[String]) -> Result<Vec<(i64, String, String, f64)>, String> {
+    let (sql, expression) = if terms.is_empty() {
+        (
+            "SELECT id, source, path, 0.0 FROM search_docs ORDER BY path",
+            None,
+        )
+    } else {
+        let expression = terms
+            .iter()
+            .map(|t| format!("\"{}\"", t))
+            .collect::<Vec<_>>()
+            .join(" ");
+        (
+            // Titles count ten times what body text does.
+            "SELECT d.id, d.source, d.path, -bm25(search_text, 10.0, 1.0)
+             FROM search_text JOIN search_docs d ON d.id = search_text.rowid
+             WHERE search_text MATCH ?1
+             ORDER BY bm25(search_text, 10.0, 1.0)",
+            Some(expression),
+        )
+    };
+    let mut stmt = conn
+        .prepare(sql)
+        .map_err(|e| format!("Query failed: {}", e))?;
+    let row = |r: &rusqlite::Row<'_>| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?));
+    let rows = match &expression {
+        Some(expression) => stmt.query_map(params![expression], row),
+        None => stmt.query_map([], row),
+    }
+    .map_err(|e| format!("Search failed: {}", e))?
+    .collect::<Result<_, _>>()
+    .map_err(|e| format!("Search failed: {}", e))?;
+    Ok(rows)
+}
+
+/// Words and "quoted phrases", lowercased. Anything without a letter or
+/// digit in it can't match, so it's left out.
+fn terms(text: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    for (i, part) in text.split('"').enumerate() {
+        let words = part.split_whitespace().map(str::to_lowercase);
+        if i % 2 == 1 {
+            terms.push(words.collect::<Vec<_>>().join(" "));
+        } else {
+            terms.extend(words);
+        }
+    }
+    terms.retain(|t| t.chars().any(char::is_alphanumeric));
+    terms
+}
+
+fn note_hit(note: &Note, score: f64) -> SearchHit {
+    SearchHit {
+        source: "vault".into(),
+        path: note.path.clone(),
+        slug: note.slug.clone(),
+        title: note.title.clone().or_else(|| note.heading.clone()),
+        content_type: vault::content_type_for_path(&note.path).into(),
+        tags: note.tags.clone(),
+        target_id: None,
+        published_url: note.published.as_ref().map(|p| p.url.clone()),
+        score,
+        snippets: Vec::new(),
+    }
+}
+
+fn post_hit(path: &str, post: &RepoPost, score: f64) -> SearchHit {
+    SearchHit {
+        source: "repo".into(),
+        path: path.to_string(),
+        slug: post.slug.clone(),
+        title: post.title.clone(),
+        content_type: vault::content_type_for_path(path).into(),
+        tags: post.tags.clone(),
+        target_id: Some(post.target_id.clone()),
+        published_url: Some(post.url.clone()),
+        score,
+        snippets: Vec::new(),
+    }
+}
+
+/// The tag, folder and content type filters. `rel` is the file's path
+/// relative to the vault or its repo.
+fn passes_filters(query: &SearchQuery, hit: &SearchHit, rel: &str) -> bool {
+    let has_tags = query.tags.iter().all(|tag| {
+        let tag = tag.trim_start_matches('#');
+        hit.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    });
+    let dir = query.dir.as_deref().map(|d| d.trim_matches('/'));
+    let in_dir = match dir {
+        Some(dir) if !dir.is_empty() => rel.starts_with(&format!("{}/", dir)),
+        _ => true,
+    };
+    let of_type = query
+        .content_type
+        .as_deref()
+        .is_none_or(|t| t == hit.content_type);
+    has_tags && in_dir && of_type
+}
+
+/// The first lines with a term in them, each cut down to around the
+/// match.
+fn snippets(content: &str, terms: &[String]) -> Vec<Snippet> {
+    let stems: Vec<&str> = terms.iter().map(|t| stem(t)).collect();
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let text = line.trim();
+            let lower = text.to_lowercase();
+            let at = stems.iter().filter_map(|s| lower.find(s)).min()?;
+            Some(Snippet {
+                line: i + 1,
+                text: excerpt(text, lower[..at].chars().count()),
+            })
+        })
+        .take(SNIPPETS)
+        .collect()
+}
+
+/// Enough of a word to find it in a line whatever its ending, since the
+/// porter tokenizer matched it that way.
+fn stem(term: &str) -> &str {
+    if term.contains(' ') {
+        return term;
+    }
+    ["ing", "ed", "es", "s"]
+        .iter()
+        .filter_map(|suffix| term.strip_suffix(suffix))
+        .find(|stem| stem.chars().count() >= 3)
+        .unwrap_or(term)
+}
+
+/// At most `SNIPPET_CHARS` of `text`, starting a little before character
+/// `at`.
+fn excerpt(text: &str, at: usize) -> String {
+    let count = text.chars().count();
+    if count <= SNIPPET_CHARS {
+        return text.to_string();
+    }
+    let start = at
+        .saturating_sub(SNIPPET_CHARS / 4)
+        .min(count - SNIPPET_CHARS);
+    let cut: String = text.chars().skip(start).take(SNIPPET_CHARS).collect();
+    format!(
+        "{}{}{}",
+        if start > 0 { "…" } else { "" },
+        cut.trim(),
+        if start + SNIPPET_CHARS < count {
+            "…"
+        } else {
+            ""
+        }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config;
+    use crate::git::tests::{remote_and_clone, scratch, write};
+
+    const TEA: &str =
+        "---\ntitle: Tea\ntags: [drinks]\n---\n# Tea\n\nGreen tea is brewing in the pot.\n";
+
+    #[test]
+    fn search_ranks_filters_and_quotes_lines() {
+        let vault = scratch("search-vault");
+        let (_remote, site) = remote_and_clone("search-site");
+        let mut target = config::AppConfig::default().publish_targets[0].clone();
+        target.repo_path = site.to_string_lossy().to_string();
+        write(&vault, "blog/tea.md", TEA);
+        write(
+            &vault,
+            "drafts/coffee.md",
+            "# Coffee\n\nCoffee brewed strong, with tea for later.\n",
+        );
+        write(&site, "content/blog/2026/tea.md", TEA);
+        write(
+            &site,
+            "content/blog/2020/kettle.md",
+            "---\ntitle: Kettles\ntags: [gear]\n---\nA kettle for tea.\n",
+        );
+        write(
+            &site,
+            "content/blog/2020/unfinished.md",
+            "---\ndraft: true\n---\nTea, eventually.\n",
+        );
+
+        let conn = vault_index::tests::synced(&vault, &target);
+        sync_posts(&conn, std::slice::from_ref(&target)).unwrap();
+        let vault_path = vault.to_string_lossy().to_string();
+        let search = |query: SearchQuery| search_in(&conn, &vault_path, &query).unwrap();
+        let slugs = |results: &SearchResults| -> Vec<String> {
+            results.hits.iter().map(|h| h.slug.clone()).collect()
+        };
+
+        // The repo's copy of tea is found through the note; the draft isn't
+        // found at all.
+        let tea = search(SearchQuery {
+            text: "tea".into(),
+            ..Default::default()
+        });
+        assert_eq!(tea.total, 3);
+        assert_eq!(tea.hits[0].slug, "tea");
+        assert_eq!(tea.hits[0].source, "vault");
+        assert_eq!(
+            tea.hits[0].published_url.as_deref(),
+            Some("https://ejfox.com/blog/2026/tea")
+        );
+        assert_eq!(
+            tea.facets,
+            Facets {
+                published: 2,
+                unpublished: 1
+            }
+        );
+        let kettle = tea.hits.iter().find(|h| h.slug == "kettle").unwrap();
+        assert_eq!(kettle.source, "repo");
+        assert_eq!(
+            kettle.snippets,
+            [Snippet {
+                line: 5,
+                text: "A kettle for tea.".into()
+            }]
+        );
+
+        let brew = search(SearchQuery {
+            text: "brew".into(),
+            ..Default::default()
+        });
+        let mut brewing = slugs(&brew);
+        brewing.sort();
+        assert_eq!(brewing, ["coffee", "tea"]);
+        let lines: Vec<usize> = brew.hits.iter().map(|h| h.snippets[0].line).collect();
+        assert!(lines.contains(&7) && lines.contains(&3), "{:?}", lines);
+
+        let phrase = search(SearchQuery {
+            text: "\"green tea\"".into(),
+            ..Default::default()
+        });
+        assert_eq!(slugs(&phrase), ["tea"]);
+
+        let unpublished = search(SearchQuery {
+            text: "tea".into(),
+            published: Some(false),
+            ..Default::default()
+        });
+        assert_eq!(slugs(&unpublished), ["coffee"]);
+        assert_eq!(unpublished.facets, tea.facets);
+
+        let tagged = search(SearchQuery {
+            tags: vec!["#gear".into()],
+            ..Default::default()
+        });
+        assert_eq!(slugs(&tagged), ["kettle"]);
+
+        let in_drafts = search(SearchQuery {
+            text: "tea".into(),
+            dir: Some("drafts/".into()),
+            ..Default::default()
+        });
+        assert_eq!(slugs(&in_drafts), ["coffee"]);
+
+        // Live on a second target too: still one hit.
+        let (_remote2, mirror) = remote_and_clone("search-mirror");
+        let mut second = target.clone();
+        second.id = "mirror".into();
+        second.repo_path = mirror.to_string_lossy().to_string();
+        write(&mirror, "content/blog/2026/tea.md", TEA);
+        sync_posts(&conn, &[target.clone(), second]).unwrap();
+        let phrase = search(SearchQuery {
+            text: "\"green tea\"".into(),
+            ..Default::default()
+        });
+        assert_eq!(slugs(&phrase), ["tea"]);
+        assert_eq!(phrase.hits[0].source, "vault");
+
+        // A deleted post drops out on the next search.
+        fs::remove_file(site.join("content/blog/2020/kettle.md")).unwrap();
+        sync_posts(&conn, &[target]).unwrap();
+        assert_eq!(
+            search(SearchQuery {
+                text: "kettle".into(),
+                ..Default::default()
+            })
+            .total,
+            0
+        );
+    }
+}
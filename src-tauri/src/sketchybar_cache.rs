@@ -49,10 +49,9 @@ pub fn update() {
         let _ = std::fs::create_dir_all(parent);
     }
 
-    // Pull files from the vault. We re-scan rather than depend on the
-    // frontend's cached list — this function may be called from background
-    // threads (publish completion, schedule trigger) where no frontend state
-    // is available.
+    // Pull files from the vault index rather than the frontend's cached
+    // list — this function may be called from background threads (publish
+    // completion, schedule trigger) where no frontend state is available.
     let files = match crate::vault::get_recent_files(10_000) {
         Ok(f) => f,
         Err(e) => {
@@ -65,7 +64,7 @@ pub fn update() {
     let draft_count = files.len().saturating_sub(live_count);
     let has_modified = files
         .iter()
-        .any(|f| f.warnings.iter().any(|w| w == "Modified since publish"));
+        .any(|f| f.lint.iter().any(|l| l.rule == "modified"));
 
     // "Pending" mirrors the older RSS-based heuristic but locally: posts
     // modified in the last 21 days that aren't yet live.
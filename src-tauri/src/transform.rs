@@ -0,0 +1,566 @@
+//! Vault -> repo transforms, run on every publish.
+//!
+//! Notes are written for Obsidian; the site renders plain markdown. Each step
+//! turns one piece of Obsidian syntax into something the site can serve, or
+//! drops it, and each can be switched off per target via
+//! `PublishTarget.transforms`. Fenced code blocks and inline code are left
+//! alone. Order matters: embeds are inlined first so the later steps also
+//! clean up what they pulled in.
+
+use crate::config::{PublishTarget, VaultConfig};
+use crate::frontmatter::{self, Frontmatter};
+use crate::patterns::{BLOCK_ID, OBSIDIAN_COMMENT, PRIVATE_CALLOUT, WIKI_LINK};
+use regex::Captures;
+use std::cell::{OnceCell, RefCell};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Frontmatter keys only Dispatch reads.
+const DISPATCH_KEYS: &[&str] = &["publish_at", "unpublish_at"];
+
+/// Frontmatter keys only Obsidian reads.
+const OBSIDIAN_KEYS: &[&str] = &["aliases", "cssclass", "cssclasses"];
+
+/// How many levels of note-embeds-note before we assume a cycle.
+const MAX_EMBED_DEPTH: usize = 3;
+
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp", "svg", "avif"];
+const VIDEO_EXTENSIONS: &[&str] = &["mp4", "mov", "webm", "m4v"];
+const OTHER_ATTACHMENT_EXTENSIONS: &[&str] = &["pdf", "mp3", "m4a", "wav"];
+
+/// Uploads a vault attachment and returns its public URL.
+pub type Uploader<'a> = &'a dyn Fn(&Path) -> Result<String, String>;
+
+pub struct Context<'a> {
+    pub target: &'a PublishTarget,
+    pub vault: &'a VaultConfig,
+    /// None leaves `![[image.png]]` embeds as written — for previews and
+    /// diffs, which mustn't upload anything.
+    pub upload: Option<Uploader<'a>>,
+}
+
+/// Run the target's enabled steps over a note's full text.
+pub fn apply(content: &str, ctx: &Context) -> Result<String, String> {
+    let steps = &ctx.target.transforms;
+    let (frontmatter, body) = frontmatter::split(content);
+    let run = Run {
+        ctx,
+        files: OnceCell::new(),
+        uploaded: RefCell::new(HashMap::new()),
+    };
+
+    let mut body = body.to_string();
+    if steps.embeds {
+        body = run.embeds(&body, 0)?;
+    }
+    if steps.comments {
+        body = outside_code(&body, |text| {
+            let text = OBSIDIAN_COMMENT.replace_all(text, "");
+            Ok(BLOCK_ID.replace_all(&text, "").into_owned())
+        })?;
+    }
+    if steps.private_blocks {
+        body = outside_code(&body, |text| Ok(strip_private_callouts(text)))?;
+    }
+    if steps.wiki_links {
+        body = outside_code(&body, |text| Ok(run.links(text)))?;
+    }
+
+    Ok(match frontmatter {
+        Some(fm) if steps.frontmatter => format!("{}{}", normalize_frontmatter(fm), body),
+        Some(fm) => format!("{}{}", fm, body),
+        None => body,
+    })
+}
+
+/// What a publish to `target` would write, without uploading anything. Used
+/// to compare a note against its live copy; falls back to the raw text if a
+/// step fails.
+pub fn preview(content: &str, target: &PublishTarget, vault: &VaultConfig) -> String {
+    let ctx = Context {
+        target,
+        vault,
+        upload: None,
+    };
+    apply(content, &ctx).unwrap_or_else(|_| content.to_string())
+}
+
+/// State for one `apply` call.
+struct Run<'c, 'a> {
+    ctx: &'c Context<'a>,
+    /// Vault files by lowercased file name, built on first lookup.
+    files: OnceCell<HashMap<String, Vec<PathBuf>>>,
+    /// Attachments already uploaded, so an image embedded twice uploads once.
+    uploaded: RefCell<HashMap<PathBuf, String>>,
+}
+
+impl Run<'_, '_> {
+    fn embeds(&self, text: &str, depth: usize) -> Result<String, String> {
+        outside_code(text, |segment| {
+            let mut out = String::with_capacity(segment.len());
+            let mut last = 0;
+            for caps in WIKI_LINK.captures_iter(segment) {
+                let whole = caps.get(0).unwrap();
+                if caps[1].is_empty() || in_inline_code(segment, whole.start()) {
+                    continue;
+                }
+                out.push_str(&segment[last..whole.start()]);
+                last = whole.end();
+                let section = caps.get(3).and_then(|m| m.as_str().rsplit('#').next());
+                let alias = caps.get(4).map(|m| m.as_str().trim());
+                match self.embed(caps[2].trim(), section, alias, depth)? {
+                    Some(replacement) => out.push_str(&replacement),
+                    None => out.push_str(whole.as_str()),
+                }
+            }
+            out.push_str(&segment[last..]);
+            Ok(out)
+        })
+    }
+
+    /// Replacement for one `![[name#section|alias]]`; None leaves it as written.
+    fn embed(
+        &self,
+        name: &str,
+        section: Option<&str>,
+        alias: Option<&str>,
+        depth: usize,
+    ) -> Result<Option<String>, String> {
+        if name.is_empty() {
+            return Ok(None);
+        }
+        let publishing = self.ctx.upload.is_some();
+
+        if is_attachment(name) {
+            let Some(upload) = self.ctx.upload else {
+                return Ok(None);
+            };
+            let path = self
+                .find(name)
+                .ok_or_else(|| format!("Embedded file not found in the vault: {}", name))?;
+            let url = match self.uploaded.borrow().get(&path).cloned() {
+                Some(url) => url,
+                None => upload(&path).map_err(|e| format!("Failed to upload {}: {}", name, e))?,
+            };
+            self.uploaded.borrow_mut().insert(path.clone(), url.clone());
+            return Ok(Some(attachment_markdown(&path, &url, alias)));
+        }
+
+        let file_name = if name.ends_with(".md") {
+            name.to_string()
+        } else {
+            format!("{}.md", name)
+        };
+        let Some(path) = self.find(&file_name) else {
+            if publishing {
+                return Err(format!("Embedded note not found in the vault: {}", name));
+            }
+            return Ok(None);
+        };
+        if !self.is_publishable(&path) {
+            return Err(format!(
+                "Embedded note \"{}\" isn't in a publishable folder; publishing would expose it",
+                name
+            ));
+        }
+        if depth >= MAX_EMBED_DEPTH {
+            return Err(format!(
+                "Embeds nested more than {} deep at \"{}\" (does a note embed itself?)",
+                MAX_EMBED_DEPTH, name
+            ));
+        }
+
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read embedded note {}: {}", name, e))?;
+        let (_, body) = frontmatter::split(&content);
+        let body = match section.map(str::trim).filter(|s| !s.is_empty()) {
+            Some(section) => extract_section(body, section)
+                .ok_or_else(|| format!("No section \"{}\" in embedded note {}", section, name))?,
+            None => body.trim().to_string(),
+        };
+        self.embeds(&body, depth + 1).map(Some)
+    }
+
+    fn links(&self, text: &str) -> String {
+        WIKI_LINK
+            .replace_all(text, |caps: &Captures| {
+                let whole = caps.get(0).unwrap();
+                if !caps[1].is_empty() || in_inline_code(text, whole.start()) {
+                    return whole.as_str().to_string();
+                }
+                let heading = caps
+                    .get(3)
+                    .and_then(|m| m.as_str().rsplit('#').next())
+                    .map(str::trim)
+                    .filter(|h| !h.is_empty());
+                let alias = caps
+                    .get(4)
+                    .map(|m| m.as_str().trim())
+                    .filter(|a| !a.is_empty());
+                let anchor = heading
+                    .map(|h| format!("#{}", anchor_slug(h)))
+                    .unwrap_or_default();
+
+                let name = caps[2].trim();
+                if name.is_empty() {
+                    // [[#Heading]] points within the same post.
+                    return match heading {
+                        Some(h) => format!("[{}]({})", alias.unwrap_or(h), anchor),
+                        None => whole.as_str().to_string(),
+                    };
+                }
+                let note = name
+                    .rsplit('/')
+                    .next()
+                    .unwrap_or(name)
+                    .trim_end_matches(".md");
+                let label = alias.unwrap_or(note);
+                match self.published_path(note) {
+                    Some(path) => format!("[{}]({}{})", label, path, anchor),
+                    None => label.to_string(),
+                }
+            })
+            .into_owned()
+    }
+
+    /// Site-relative URL of a linked note, if it's live on the target. Tries
+    /// the note name as-is, then slugified ("My Note" -> "my-note").
+    fn published_path(&self, note: &str) -> Option<String> {
+        let target = self.ctx.target;
+        [note.to_string(), anchor_slug(note)]
+            .into_iter()
+            .find_map(|slug| {
+                let loc = crate::vault::find_published_locations(target, &slug)
+                    .into_iter()
+                    .next()?;
+                let url = target.post_url(loc.year.as_deref(), loc.month.as_deref(), &slug);
+                Some(
+                    url.strip_prefix(target.domain.as_str())
+                        .unwrap_or(&url)
+                        .to_string(),
+                )
+            })
+    }
+
+    /// Resolve an embed name the way Obsidian does: by file name anywhere in
+    /// the vault, or by path suffix if the name has folders, shortest path
+    /// winning.
+    fn find(&self, name: &str) -> Option<PathBuf> {
+        let files = self.files.get_or_init(|| index_vault(&self.ctx.vault.path));
+        let wanted = name.trim_start_matches('/').to_lowercase();
+        let file_name = wanted.rsplit('/').next()?;
+        files
+            .get(file_name)?
+            .iter()
+            .filter(|p| {
+                !wanted.contains('/') || p.to_string_lossy().to_lowercase().ends_with(&wanted)
+            })
+            .min_by_key(|p| p.components().count())
+            .cloned()
+    }
+
+    /// Same folder rules `publish` applies to sources.
+    fn is_publishable(&self, path: &Path) -> bool {
+        let vault = self.ctx.vault;
+        let path = path.to_string_lossy();
+        let in_dir = |dir: &String| path.contains(&format!("/{}/", dir));
+        vault.publishable_dirs.iter().any(in_dir)
+            && !vault
+                .excluded_dirs
+                .iter()
+                .filter(|d| !vault.publishable_dirs.contains(d))
+                .any(in_dir)
+    }
+}
+
+fn index_vault(vault_path: &str) -> HashMap<String, Vec<PathBuf>> {
+    let mut files: HashMap<String, Vec<PathBuf>> = HashMap::new();
+    for entry in WalkDir::new(vault_path)
+        .into_iter()
+        .filter_entry(|e| e.depth() == 0 || !e.file_name().to_string_lossy().starts_with('.'))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+    {
+        files
+            .entry(entry.file_name().to_string_lossy().to_lowercase())
+            .or_default()
+            .push(entry.into_path());
+    }
+    files
+}
+
+fn extension(name: &str) -> String {
+    Path::new(name)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+pub(crate) fn is_attachment(name: &str) -> bool {
+    let ext = extension(name);
+    [
+        IMAGE_EXTENSIONS,
+        VIDEO_EXTENSIONS,
+        OTHER_ATTACHMENT_EXTENSIONS,
+    ]
+    .iter()
+    .any(|list| list.contains(&ext.as_str()))
+}
+
+/// Markdown for an uploaded attachment. Obsidian uses the alias for sizing
+/// (`|300`, `|300x200`), so only a non-numeric alias becomes alt text.
+fn attachment_markdown(path: &Path, url: &str, alias: Option<&str>) -> String {
+    let alt = alias
+        .filter(|a| !a.chars().all(|c| c.is_ascii_digit() || c == 'x'))
+        .unwrap_or("");
+    let ext = extension(&path.to_string_lossy());
+    if VIDEO_EXTENSIONS.contains(&ext.as_str()) {
+        format!("<video src=\"{}\" controls></video>", url)
+    } else if IMAGE_EXTENSIONS.contains(&ext.as_str()) {
+        format!("![{}]({})", alt, url)
+    } else {
+        let label = match alt {
+            "" => path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string(),
+            alt => alt.to_string(),
+        };
+        format!("[{}]({})", label, url)
+    }
+}
+
+/// Lines from `heading` up to the next heading of the same or higher level,
+/// or the single line carrying a `^block` id.
+fn extract_section(body: &str, section: &str) -> Option<String> {
+    if let Some(block_id) = section.strip_prefix('^') {
+        let marker = format!(" ^{}", block_id);
+        return body
+            .lines()
+            .find_map(|l| l.trim_end().strip_suffix(marker.as_str()))
+            .map(|l| l.trim().to_string());
+    }
+    let lines: Vec<&str> = body.lines().collect();
+    let start = lines.iter().position(|l| {
+        heading_level(l).is_some_and(|(_, text)| text.eq_ignore_ascii_case(section))
+    })?;
+    let level = heading_level(lines[start])?.0;
+    let end = lines[start + 1..]
+        .iter()
+        .position(|l| heading_level(l).is_some_and(|(lvl, _)| lvl <= level))
+        .map_or(lines.len(), |i| start + 1 + i);
+    Some(lines[start..end].join("\n").trim().to_string())
+}
+
+fn heading_level(line: &str) -> Option<(usize, &str)> {
+    let hashes = line.chars().take_while(|c| *c == '#').count();
+    let rest = &line[hashes..];
+    if (1..=6).contains(&hashes) && rest.starts_with(' ') {
+        Some((hashes, rest.trim()))
+    } else {
+        None
+    }
+}
+
+/// GitHub-style anchor: "Why It Matters!" -> "why-it-matters".
+fn anchor_slug(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            c if c.is_whitespace() => Some('-'),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Drop `> [!private]` callouts: the opening line and the quoted lines that
+/// follow it.
+fn strip_private_callouts(text: &str) -> String {
+    let mut in_callout = false;
+    text.split_inclusive('\n')
+        .filter(|line| {
+            if PRIVATE_CALLOUT.is_match(line) {
+                in_callout = true;
+            } else if in_callout && !line.trim_start().starts_with('>') {
+                in_callout = false;
+            }
+            !in_callout
+        })
+        .collect()
+}
+
+/// Drop Dispatch- and Obsidian-only keys, with their list or block lines.
+fn normalize_frontmatter(frontmatter: &str) -> String {
+    let (mut frontmatter, _) = Frontmatter::parse(frontmatter);
+    for key in DISPATCH_KEYS.iter().chain(OBSIDIAN_KEYS) {
+        frontmatter.remove(key);
+    }
+    frontmatter.block()
+}
+
+/// Whether byte `pos` sits inside an inline `code` span on its line.
+pub(crate) fn in_inline_code(text: &str, pos: usize) -> bool {
+    let line_start = text[..pos].rfind('\n').map_or(0, |i| i + 1);
+    text[line_start..pos].matches('`').count() % 2 == 1
+}
+
+/// Run `f` over the stretches of `text` outside fenced code blocks.
+pub(crate) fn outside_code(
+    text: &str,
+    mut f: impl FnMut(&str) -> Result<String, String>,
+) -> Result<String, String> {
+    let mut out = String::with_capacity(text.len());
+    let mut prose = String::new();
+    let mut fence: Option<char> = None;
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim();
+        match fence {
+            Some(marker) => {
+                out.push_str(line);
+                if trimmed.len() >= 3 && trimmed.chars().all(|c| c == marker) {
+                    fence = None;
+                }
+            }
+            None => {
+                if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                    out.push_str(&f(&prose)?);
+                    prose.clear();
+                    out.push_str(line);
+                    fence = trimmed.chars().next();
+                } else {
+                    prose.push_str(line);
+                }
+            }
+        }
+    }
+    out.push_str(&f(&prose)?);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AppConfig, TransformConfig};
+    use crate::git::tests::{scratch, write};
+
+    struct Fixture {
+        target: PublishTarget,
+        vault: VaultConfig,
+    }
+
+    fn fixture(name: &str) -> Fixture {
+        let root = scratch(name);
+        let app_config = AppConfig::default();
+        let mut vault = app_config.vault.clone();
+        vault.path = root.join("vault").to_string_lossy().to_string();
+        let mut target = app_config.publish_targets[0].clone();
+        target.repo_path = root.join("site").to_string_lossy().to_string();
+        Fixture { target, vault }
+    }
+
+    fn run(fx: &Fixture, content: &str) -> Result<String, String> {
+        let upload = |path: &Path| -> Result<String, String> {
+            Ok(format!(
+                "https://cdn.test/{}",
+                path.file_name().unwrap().to_string_lossy()
+            ))
+        };
+        apply(
+            content,
+            &Context {
+                target: &fx.target,
+                vault: &fx.vault,
+                upload: Some(&upload),
+            },
+        )
+    }
+
+    #[test]
+    fn wiki_links_point_at_live_posts_and_unwrap_otherwise() {
+        let fx = fixture("links");
+        write(
+            &fx.target.repo_path,
+            "content/blog/2024/my-note.md",
+            "# x\n",
+        );
+        let out = run(
+            &fx,
+            "See [[My Note#Why It Matters|this]], [[Draft Idea]] and [[#Intro]].\n",
+        )
+        .unwrap();
+        assert_eq!(
+            out,
+            "See [this](/blog/2024/my-note#why-it-matters), Draft Idea and [Intro](#intro).\n"
+        );
+    }
+
+    #[test]
+    fn embeds_inline_notes_and_upload_attachments() {
+        let fx = fixture("embeds");
+        let vault = &fx.vault.path;
+        write(
+            vault,
+            "blog/parts.md",
+            "---\ntitle: Parts\n---\n# Parts\n\n## Recipe\nflour ^step1\n\n## Notes\nsecret\n",
+        );
+        write(vault, "attachments/cat.png", "png");
+        write(vault, "private/diary.md", "dear diary\n");
+
+        let out = run(
+            &fx,
+            "![[parts#Recipe]]\n\n![[cat.png|300]] ![[cat.png|A cat]]\n",
+        )
+        .unwrap();
+        assert_eq!(
+            out,
+            "## Recipe\nflour\n\n![](https://cdn.test/cat.png) ![A cat](https://cdn.test/cat.png)\n"
+        );
+        assert_eq!(run(&fx, "![[parts#^step1]]").unwrap(), "flour");
+
+        let err = run(&fx, "![[diary]]").unwrap_err();
+        assert!(err.contains("publishable"), "{}", err);
+        assert!(run(&fx, "![[missing.png]]").is_err());
+
+        write(vault, "blog/loop.md", "![[loop]]");
+        assert!(run(&fx, "![[loop]]").unwrap_err().contains("nested"));
+    }
+
+    #[test]
+    fn comments_block_ids_and_private_callouts_are_stripped_outside_code() {
+        let fx = fixture("strip");
+        let input = "a %%todo\nmore%% b ^ref1\n> [!PRIVATE] Notes\n> phone: 555\n\nafter\n```\n%%kept%% [[kept]]\n```\n`[[inline]]` [[gone]]\n";
+        assert_eq!(
+            run(&fx, input).unwrap(),
+            "a  b\n\nafter\n```\n%%kept%% [[kept]]\n```\n`[[inline]]` gone\n"
+        );
+    }
+
+    #[test]
+    fn frontmatter_loses_app_only_keys() {
+        let fx = fixture("frontmatter");
+        let input = "---\ntitle: Hi\naliases:\n  - hey\npublish_at: 2026-01-01\nunpublish_at: 2026-02-01T09:00:00Z\ncssclasses: [wide]\ntags:\n  - a\n---\nbody %%x%%\n";
+        assert_eq!(
+            run(&fx, input).unwrap(),
+            "---\ntitle: Hi\ntags:\n  - a\n---\nbody \n"
+        );
+    }
+
+    #[test]
+    fn disabled_steps_leave_text_alone() {
+        let mut fx = fixture("disabled");
+        fx.target.transforms = TransformConfig {
+            wiki_links: false,
+            embeds: false,
+            comments: false,
+            private_blocks: false,
+            frontmatter: false,
+        };
+        let input = "---\naliases: [x]\n---\n[[a]] ![[b.png]] %%c%%\n> [!private]\n> d\n";
+        assert_eq!(run(&fx, input).unwrap(), input);
+    }
+}
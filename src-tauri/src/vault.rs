@@ -1,166 +1,181 @@
+use crate::frontmatter::{self, Frontmatter};
+use crate::vault_index::Note;
 use crate::{config, Config, MarkdownFile};
-use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time::SystemTime;
-use walkdir::WalkDir;
 
 pub fn get_recent_files(limit: usize) -> Result<Vec<MarkdownFile>, String> {
-    let config = Config::from_app_config()?;
-    let mut files: Vec<MarkdownFile> = Vec::new();
-
-    // Use publishable dirs from config
-    let app_config = config::get()?;
-    let publishable_dirs = app_config.vault.publishable_dirs.clone();
+    let scan = Scan::new()?;
+    let eight_weeks_ago = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+        .saturating_sub(86400 * 56);
 
-    for entry in WalkDir::new(&config.vault_path)
+    let mut notes: Vec<Note> = crate::vault_index::notes()?
         .into_iter()
-        .filter_map(|e| e.ok())
-        .filter(|e| e.path().extension().is_some_and(|ext| ext == "md"))
-    {
-        let path = entry.path();
+        .filter(|note| {
+            let path = Path::new(&note.path);
+            // For week notes: skip old ones (>8 weeks by filename date)
+            let stale_weeknote = content_type_for_path(&note.path) == "weeknote"
+                && parse_weeknote_filename(path).is_some_and(|d| d < eight_weeks_ago);
+            scan.lists(path) && !stale_weeknote
+        })
+        .collect();
+
+    notes.sort_by(|a, b| b.modified.cmp(&a.modified));
+    notes.truncate(limit);
+    Ok(notes.iter().map(|note| scan.file(note)).collect())
+}
+
+/// One note, read the way `get_recent_files` reads it, however far down
+/// the list it would be. Always re-read from disk.
+pub fn get_file(path: &str) -> Result<MarkdownFile, String> {
+    let scan = Scan::new()?;
+    let path = Path::new(path);
+    if !scan.lists(path) {
+        return Err(format!(
+            "{} isn't in a publishable directory",
+            path.display()
+        ));
+    }
+    Ok(scan.file(&crate::vault_index::index_file(path)?))
+}
+
+/// What reading notes into `MarkdownFile`s needs, loaded once per scan.
+pub(crate) struct Scan {
+    config: Config,
+    app_config: config::AppConfig,
+    zone: crate::schedule::Zone,
+    now: chrono::DateTime<chrono::Utc>,
+}
+
+impl Scan {
+    pub(crate) fn new() -> Result<Self, String> {
+        let app_config = config::get()?;
+        Ok(Scan {
+            config: Config::from_app_config()?,
+            zone: crate::schedule::Zone::from_config(&app_config),
+            now: chrono::Utc::now(),
+            app_config,
+        })
+    }
+
+    pub(crate) fn vault_path(&self) -> &str {
+        &self.config.vault_path
+    }
+
+    pub(crate) fn zone(&self) -> &crate::schedule::Zone {
+        &self.zone
+    }
+
+    pub(crate) fn now(&self) -> chrono::DateTime<chrono::Utc> {
+        self.now
+    }
+
+    /// Whether `path` is a note the file list shows: in a publishable
+    /// directory and not an excluded, stale or archived one.
+    pub(crate) fn lists(&self, path: &Path) -> bool {
         let path_str = path.to_string_lossy();
 
         // Only include files from publishable directories
-        let in_publishable = publishable_dirs
+        let in_publishable = self
+            .app_config
+            .vault
+            .publishable_dirs
             .iter()
             .any(|dir| path_str.contains(&format!("/{}/", dir)));
-
-        // Determine content type from path
-        let content_type = if path_str.contains("/week-notes/") {
-            "weeknote"
-        } else {
-            "post"
-        };
-
         if !in_publishable {
-            continue;
+            return false;
         }
 
         // Skip excluded directories
-        if config
+        if self
+            .config
             .excluded_dirs
             .iter()
             .any(|dir| path_str.contains(&format!("/{}/", dir)))
         {
-            continue;
+            return false;
         }
 
         // Skip stale/archive folders
         if path_str.contains("/_stale/") || path_str.contains("/_archive/") {
-            continue;
+            return false;
         }
 
         // Skip week-notes nested inside blog/ (stray copies) — week-notes/ is its own publishable dir
-        if path_str.contains("/blog/week-notes/") {
-            continue;
+        !path_str.contains("/blog/week-notes/")
+    }
+
+    fn file(&self, note: &Note) -> MarkdownFile {
+        let path = Path::new(&note.path);
+        let content_type = content_type_for_path(&note.path);
+        let (frontmatter, _) = Frontmatter::parse(&note.frontmatter);
+        let filename = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        let source_dir = path
+            .parent()
+            .and_then(|p| p.strip_prefix(&self.config.vault_path).ok())
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        // The text's findings, plus what only the vault knows: whether the
+        // live copy has fallen behind, and the schedule.
+        let published = note.published.as_ref();
+        let mut findings = note.lint.clone();
+        if published.is_some_and(|p| p.differs) {
+            let message = "Modified since publish".to_string();
+            findings.push(crate::lint::finding(
+                &note.frontmatter,
+                "modified",
+                0,
+                0,
+                message,
+            ));
         }
-
-        if let Ok(metadata) = fs::metadata(path) {
-            let fs_modified = get_timestamp(metadata.modified());
-            let fs_created = metadata
-                .created()
-                .map(|t| get_timestamp(Ok(t)))
-                .unwrap_or(fs_modified);
-
-            let content = fs::read_to_string(path).unwrap_or_default();
-            let (frontmatter, body) = parse_frontmatter(&content);
-
-            // Prefer frontmatter dates over filesystem dates
-            // For week notes, derive date from filename (e.g. "2025-37.md" = week 37 of 2025)
-            let filename_date = if content_type == "weeknote" {
-                parse_weeknote_filename(path)
-            } else {
-                None
-            };
-            // For week notes: skip old ones (>8 weeks by filename date)
-            if content_type == "weeknote" {
-                if let Some(fn_date) = filename_date {
-                    let eight_weeks_ago = SystemTime::now()
-                        .duration_since(SystemTime::UNIX_EPOCH)
-                        .map(|d| d.as_secs())
-                        .unwrap_or(0)
-                        .saturating_sub(86400 * 56);
-                    if fn_date < eight_weeks_ago {
-                        continue;
-                    }
-                }
-            }
-
-            let modified = frontmatter
-                .get("modified")
-                .and_then(|d| parse_iso_date(d))
-                .unwrap_or(fs_modified);
-            let created = frontmatter
-                .get("date")
-                .and_then(|d| parse_iso_date(d))
-                .or(filename_date)
-                .unwrap_or(fs_created);
-            let title = extract_h1_title(&body);
-            let filename = path
-                .file_name()
-                .unwrap_or_default()
-                .to_string_lossy()
-                .to_string();
-            let slug = filename.trim_end_matches(".md");
-
-            let (published_url, published_date, published_content) =
-                find_published_info(&config.website_repo, slug);
-            let source_dir = path
-                .parent()
-                .and_then(|p| p.strip_prefix(&config.vault_path).ok())
-                .map(|p| p.to_string_lossy().to_string())
-                .unwrap_or_default();
-
-            let mut warnings = check_warnings(&body, &frontmatter, title.is_some(), content_type);
-
-            // Check if content actually differs from published version
-            if let Some(ref pub_content) = published_content {
-                if content_differs(&content, pub_content) {
-                    warnings.insert(0, "Modified since publish".into());
-                }
-            }
-
-            // Parse visibility controls
-            let unlisted = frontmatter
-                .get("unlisted")
-                .map(|v| v == "true" || v == "yes")
-                .unwrap_or(false);
-            let password = frontmatter.get("password").cloned();
-            let dek = frontmatter.get("dek").cloned();
-            let publish_at = frontmatter.get("publish_at").cloned();
-
-            let published_word_count = published_content
-                .as_ref()
-                .map(|c| c.split_whitespace().count());
-
-            files.push(MarkdownFile {
-                path: path.to_string_lossy().to_string(),
-                filename,
-                title,
-                dek,
-                date: frontmatter.get("date").cloned(),
-                tags: parse_tags(&frontmatter),
-                created,
-                modified,
-                word_count: body.split_whitespace().count(),
-                published_word_count,
-                is_safe: warnings.is_empty(),
-                warnings,
-                published_url,
-                published_date,
-                source_dir,
-                unlisted,
-                password,
-                publish_at,
-                content_type: content_type.into(),
-            });
+        findings.extend(crate::schedule::findings(
+            &note.frontmatter,
+            published.is_some(),
+            &self.zone,
+            self.now,
+        ));
+        findings.sort_by_key(|f| (f.line, f.column));
+        let lint = crate::lint::leveled(&findings, content_type, &self.app_config.lint);
+        let warnings = crate::lint::warnings(&lint);
+
+        // Parse visibility controls
+        let publish_at = frontmatter.get_str("publish_at");
+        let unpublish_at = frontmatter.get_str("unpublish_at");
+
+        MarkdownFile {
+            path: note.path.clone(),
+            filename,
+            title: note.heading.clone(),
+            dek: frontmatter.get_str("dek"),
+            date: frontmatter.get_str("date"),
+            tags: note.tags.clone(),
+            created: note.created,
+            modified: note.modified,
+            word_count: note.word_count,
+            published_word_count: published.map(|p| p.word_count),
+            is_safe: warnings.is_empty(),
+            warnings,
+            lint,
+            published_url: published.map(|p| p.url.clone()),
+            published_date: published.map(|p| p.date),
+            source_dir,
+            unlisted: frontmatter.get_bool("unlisted"),
+            password: frontmatter.get_str("password"),
+            publish_at,
+            unpublish_at,
+            content_type: content_type.into(),
         }
     }
-
-    files.sort_by(|a, b| b.modified.cmp(&a.modified));
-    files.truncate(limit);
-    Ok(files)
 }
 
 fn get_timestamp(time: std::io::Result<SystemTime>) -> u64 {
@@ -171,7 +186,7 @@ fn get_timestamp(time: std::io::Result<SystemTime>) -> u64 {
 }
 
 /// Parse a week note filename like "2025-37.md" or "2025-52-raw.md" into a timestamp
-fn parse_weeknote_filename(path: &Path) -> Option<u64> {
+pub(crate) fn parse_weeknote_filename(path: &Path) -> Option<u64> {
     let stem = path.file_stem()?.to_str()?;
     let mut parts = stem.split('-');
     let year: i32 = parts.next()?.parse().ok()?;
@@ -191,7 +206,7 @@ fn parse_weeknote_filename(path: &Path) -> Option<u64> {
     Some(dt.and_utc().timestamp() as u64)
 }
 
-fn parse_iso_date(date_str: &str) -> Option<u64> {
+pub(crate) fn parse_iso_date(date_str: &str) -> Option<u64> {
     // Parse ISO 8601 dates like "2024-01-01T00:00:00-05:00" or "2024-01-01"
     use chrono::{DateTime, NaiveDate};
 
@@ -221,76 +236,245 @@ pub fn find_published_info_for_target(
     find_published_info_inner(target, &target.repo_path, slug)
 }
 
-fn find_published_info(
+fn find_published_info_inner(
+    target: &config::PublishTarget,
     website_repo: &str,
     slug: &str,
 ) -> (Option<String>, Option<u64>, Option<String>) {
-    let target = match config::default_target() {
-        Ok(t) => t,
-        Err(_) => return (None, None, None),
-    };
-    find_published_info_inner(&target, website_repo, slug)
+    if let Some(found) = find_published_locations_in(target, website_repo, Some(slug))
+        .into_iter()
+        .find(|loc| !is_drafted(&loc.path))
+    {
+        let url = target.post_url(found.year.as_deref(), found.month.as_deref(), slug);
+        let date = fs::metadata(&found.path)
+            .and_then(|m| m.modified())
+            .map(|t| get_timestamp(Ok(t)))
+            .ok();
+        let published_content = fs::read_to_string(&found.path).ok();
+        return (Some(url), date, published_content);
+    }
+    (None, None, None)
 }
 
-fn find_published_info_inner(
+/// A copy of a post in the target repo, with the date folders it was filed under.
+#[derive(Debug, Clone)]
+pub struct PublishedLocation {
+    pub slug: String,
+    pub path: PathBuf,
+    pub year: Option<String>,
+    pub month: Option<String>,
+}
+
+/// Every file in the target repo that `content_path_pattern` could have
+/// produced for `slug`, in path order.
+pub fn find_published_locations(
     target: &config::PublishTarget,
-    website_repo: &str,
     slug: &str,
-) -> (Option<String>, Option<u64>, Option<String>) {
-    // Derive blog content base from content_path_pattern (e.g. "content/blog/{year}" -> "content/blog")
-    let content_base = target
-        .content_path_pattern
-        .split("/{year}")
-        .next()
-        .unwrap_or("content/blog");
-    let blog_path = format!("{}/{}", website_repo, content_base);
-
-    if let Ok(entries) = fs::read_dir(&blog_path) {
-        for entry in entries.flatten() {
-            if entry.path().is_dir() {
-                let dir_name = entry.file_name().to_string_lossy().to_string();
-                // Only look in year folders (4 digits), skip drafts/projects/etc
-                if dir_name.len() != 4 || !dir_name.chars().all(|c| c.is_ascii_digit()) {
-                    continue;
-                }
-                let file_path = format!("{}/{}/{}.md", blog_path, dir_name, slug);
-                let path = Path::new(&file_path);
+) -> Vec<PublishedLocation> {
+    find_published_locations_in(target, &target.repo_path, Some(slug))
+}
+
+/// Every post in the target repo that sits where `content_path_pattern`
+/// would put one, whatever its slug.
+pub fn find_all_published(target: &config::PublishTarget) -> Vec<PublishedLocation> {
+    find_published_locations_in(target, &target.repo_path, None)
+}
+
+fn find_published_locations_in(
+    target: &config::PublishTarget,
+    website_repo: &str,
+    slug: Option<&str>,
+) -> Vec<PublishedLocation> {
+    // Expand everything except the date folders and content type (and the
+    // slug, when listing everything), then walk the pattern one segment at a
+    // time: literal segments are a single `exists` check, token segments a
+    // `read_dir` of the current level.
+    let rel = target.content_path(&config::ContentPathFields {
+        year: "{year}",
+        month: "{month}",
+        slug: slug.unwrap_or("{slug}"),
+        content_type: "{content_type}",
+    });
+    let mut found = vec![PublishedLocation {
+        slug: slug.unwrap_or_default().to_string(),
+        path: PathBuf::from(website_repo),
+        year: None,
+        month: None,
+    }];
+    for segment in rel.split('/').filter(|s| !s.is_empty()) {
+        let mut next = Vec::new();
+        if !segment.contains('{') {
+            for loc in found {
+                let path = loc.path.join(segment);
                 if path.exists() {
-                    let url = format!("{}/blog/{}/{}", target.domain, dir_name, slug);
-                    let date = fs::metadata(path)
-                        .and_then(|m| m.modified())
-                        .map(|t| get_timestamp(Ok(t)))
-                        .ok();
-                    let published_content = fs::read_to_string(path).ok();
-                    return (Some(url), date, published_content);
+                    next.push(PublishedLocation { path, ..loc });
+                }
+            }
+            found = next;
+            continue;
+        }
+        let Some(re) = segment_regex(segment) else {
+            return Vec::new();
+        };
+        for loc in found {
+            let Ok(entries) = fs::read_dir(&loc.path) else {
+                continue;
+            };
+            let mut names: Vec<String> = entries
+                .flatten()
+                .map(|e| e.file_name().to_string_lossy().to_string())
+                .collect();
+            names.sort();
+            for name in names {
+                if let Some(caps) = re.captures(&name) {
+                    next.push(PublishedLocation {
+                        slug: caps
+                            .name("slug")
+                            .map(|m| m.as_str().to_string())
+                            .unwrap_or_else(|| loc.slug.clone()),
+                        path: loc.path.join(&name),
+                        year: caps
+                            .name("year")
+                            .map(|m| m.as_str().to_string())
+                            .or_else(|| loc.year.clone()),
+                        month: caps
+                            .name("month")
+                            .map(|m| m.as_str().to_string())
+                            .or_else(|| loc.month.clone()),
+                    });
                 }
             }
         }
+        found = next;
     }
-    (None, None, None)
+    found.retain(|loc| loc.path.is_file());
+    found
 }
 
-fn normalize_content(content: &str) -> String {
-    // Extract body after frontmatter and normalize whitespace
-    let body = if let Some(after_start) = content.strip_prefix("---") {
-        if let Some(end) = after_start.find("---") {
-            after_start[end + 3..].to_string()
-        } else {
-            content.to_string()
-        }
+/// Matches the repo-relative paths `content_path_pattern` could have filed
+/// `slug` under, in any year or month.
+pub fn post_path_regex(target: &config::PublishTarget, slug: &str) -> Option<regex::Regex> {
+    let rel = target.content_path(&config::ContentPathFields {
+        year: "{year}",
+        month: "{month}",
+        slug,
+        content_type: "{content_type}",
+    });
+    regex::Regex::new(&format!("^{}$", token_pattern(rel.trim_matches('/')))).ok()
+}
+
+/// Regex for one path segment of a content pattern. Year folders are four
+/// digits and months two, which keeps drafts/projects/etc. out of the match.
+fn segment_regex(segment: &str) -> Option<regex::Regex> {
+    regex::Regex::new(&format!("^{}$", token_pattern(segment))).ok()
+}
+
+/// Regex source for a pattern's tokens. The first of each is captured; the
+/// `[^/]` classes keep a token within one path segment.
+fn token_pattern(pattern: &str) -> String {
+    regex::escape(pattern)
+        .replacen(r"\{year\}", r"(?P<year>\d{4})", 1)
+        .replace(r"\{year\}", r"\d{4}")
+        .replacen(r"\{month\}", r"(?P<month>\d{2})", 1)
+        .replace(r"\{month\}", r"\d{2}")
+        .replace(r"\{content_type\}", r"[^/]+")
+        .replacen(r"\{slug\}", r"(?P<slug>[^/]+?)", 1)
+        .replace(r"\{slug\}", r"[^/]+?")
+}
+
+/// "weeknote" for files under week-notes/, otherwise "post".
+pub fn content_type_for_path(path: &str) -> &'static str {
+    if path.contains("/week-notes/") {
+        "weeknote"
     } else {
-        content.to_string()
-    };
+        "post"
+    }
+}
+
+/// Frontmatter `title`, else the first heading.
+pub fn post_title(path: &Path) -> Option<String> {
+    post_summary(path)?.title
+}
 
-    // Normalize: trim, collapse whitespace, remove trailing newlines
-    body.trim()
+/// What commit templates say about a note: title as `post_title` reads it,
+/// body word count and tags as the file list has them.
+#[derive(Debug, Clone, Default)]
+pub struct PostSummary {
+    pub title: Option<String>,
+    pub word_count: usize,
+    pub tags: Vec<String>,
+}
+
+pub fn post_summary(path: &Path) -> Option<PostSummary> {
+    let content = fs::read_to_string(path).ok()?;
+    let (frontmatter, body) = Frontmatter::parse(&content);
+    Some(PostSummary {
+        title: frontmatter
+            .get_str("title")
+            .filter(|t| !t.is_empty())
+            .or_else(|| extract_h1_title(body)),
+        word_count: body.split_whitespace().count(),
+        tags: frontmatter.tags(),
+    })
+}
+
+/// The post's own calendar date from frontmatter `date`, taken as written
+/// (2025-12-31T23:00:00-05:00 is still 2025, whatever UTC says).
+pub fn post_date(path: &str) -> Option<chrono::NaiveDate> {
+    let content = fs::read_to_string(path).ok()?;
+    let (frontmatter, _) = Frontmatter::parse(&content);
+    parse_calendar_date(&frontmatter.get_str("date")?)
+}
+
+/// A note's `publish_at` and `unpublish_at` as written, or None if it can't
+/// be read.
+pub fn schedule_fields(path: &Path) -> Option<(Option<String>, Option<String>)> {
+    let content = fs::read_to_string(path).ok()?;
+    let (frontmatter, _) = Frontmatter::parse(&content);
+    Some((
+        frontmatter.get_str("publish_at"),
+        frontmatter.get_str("unpublish_at"),
+    ))
+}
+
+/// A copy in a target repo that was unpublished in place with `draft: true`.
+/// It's still in the content folder but not live.
+pub fn is_drafted(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|content| is_draft_content(&content))
+}
+
+/// Whether a post's text has `draft: true`.
+pub fn is_draft_content(content: &str) -> bool {
+    Frontmatter::parse(content).0.get_bool("draft")
+}
+
+/// Publish target ids from frontmatter `targets:`, inline (`[main, mirror]`)
+/// or as a block list. Empty when the post doesn't say.
+pub fn post_targets(path: &str) -> Vec<String> {
+    fs::read_to_string(path)
+        .map(|content| Frontmatter::parse(&content).0.get_list("targets"))
+        .unwrap_or_default()
+}
+
+fn parse_calendar_date(date_str: &str) -> Option<chrono::NaiveDate> {
+    let date_str = date_str.trim();
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(date_str) {
+        return Some(dt.date_naive());
+    }
+    chrono::NaiveDate::parse_from_str(date_str.get(..10)?, "%Y-%m-%d").ok()
+}
+
+fn normalize_content(content: &str) -> String {
+    // Normalize the body after frontmatter: trim, collapse whitespace, remove trailing newlines
+    frontmatter::body(content)
+        .trim()
         .lines()
         .map(|l| l.trim_end())
         .collect::<Vec<_>>()
         .join("\n")
 }
 
-fn content_differs(source: &str, published: &str) -> bool {
+pub(crate) fn content_differs(source: &str, published: &str) -> bool {
     // Simple comparison - if files are byte-identical, no diff
     if source == published {
         return false;
@@ -299,55 +483,7 @@ fn content_differs(source: &str, published: &str) -> bool {
     normalize_content(source) != normalize_content(published)
 }
 
-fn parse_frontmatter(content: &str) -> (HashMap<String, String>, String) {
-    let mut frontmatter = HashMap::new();
-    let mut body = content.to_string();
-
-    if let Some(after_start) = content.strip_prefix("---") {
-        if let Some(end) = after_start.find("---") {
-            let yaml = &after_start[..end];
-            body = after_start[end + 3..].to_string();
-
-            let lines: Vec<&str> = yaml.lines().collect();
-            let mut i = 0;
-            while i < lines.len() {
-                let line = lines[i];
-                if let Some((key, value)) = line.split_once(':') {
-                    let key = key.trim().to_string();
-                    let value = value.trim().trim_matches('"').to_string();
-
-                    // Handle YAML block scalar indicators (>-, >, |-, |)
-                    if value == ">-" || value == ">" || value == "|-" || value == "|" {
-                        // Read indented continuation lines
-                        let mut block_lines = Vec::new();
-                        while i + 1 < lines.len() {
-                            let next = lines[i + 1];
-                            if next.starts_with(' ') || next.starts_with('\t') {
-                                block_lines.push(next.trim());
-                                i += 1;
-                            } else {
-                                break;
-                            }
-                        }
-                        let joined = if value.starts_with('>') {
-                            block_lines.join(" ") // folded: join with spaces
-                        } else {
-                            block_lines.join("\n") // literal: preserve newlines
-                        };
-                        frontmatter.insert(key, joined);
-                    } else {
-                        frontmatter.insert(key, value);
-                    }
-                }
-                i += 1;
-            }
-        }
-    }
-
-    (frontmatter, body)
-}
-
-fn extract_h1_title(body: &str) -> Option<String> {
+pub(crate) fn extract_h1_title(body: &str) -> Option<String> {
     // Find first heading (# or ##)
     body.lines()
         .find(|line| {
@@ -366,259 +502,25 @@ fn extract_h1_title(body: &str) -> Option<String> {
         })
 }
 
-fn parse_tags(frontmatter: &HashMap<String, String>) -> Vec<String> {
-    frontmatter
-        .get("tags")
-        .map(|t| {
-            t.trim_matches(|c| c == '[' || c == ']')
-                .split(',')
-                .map(|s| s.trim().to_string())
-                .filter(|s| !s.is_empty())
-                .collect()
-        })
-        .unwrap_or_default()
-}
-
 pub fn add_tag_to_file(path: &str, tag: &str) -> Result<(), String> {
-    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read file: {}", e))?;
-
-    // Parse frontmatter
-    if !content.starts_with("---") {
-        // No frontmatter - add it with the tag
-        let new_content = format!("---\ntags: [{}]\n---\n{}", tag, content);
-        fs::write(path, new_content).map_err(|e| format!("Failed to write file: {}", e))?;
-        return Ok(());
-    }
-
-    // Find frontmatter boundaries
-    let end_pos = content[3..]
-        .find("---")
-        .ok_or("Invalid frontmatter: no closing ---")?;
-    let fm_content = &content[3..end_pos + 3];
-    let body = &content[end_pos + 6..];
-
-    // Check if tags line exists - filter empty lines to keep frontmatter clean
-    let mut lines: Vec<String> = fm_content
-        .lines()
-        .map(|l| l.to_string())
-        .filter(|l| !l.trim().is_empty())
-        .collect();
-    let mut found_tags = false;
-
-    for line in lines.iter_mut() {
-        if line.starts_with("tags:") {
-            // Parse existing tags and add the new one
-            let existing = line.trim_start_matches("tags:").trim();
-            let mut tags: Vec<String> = if existing.starts_with('[') {
-                // Array format: [tag1, tag2]
-                existing
-                    .trim_matches(|c| c == '[' || c == ']')
-                    .split(',')
-                    .map(|s| s.trim().to_string())
-                    .filter(|s| !s.is_empty())
-                    .collect()
-            } else {
-                // Single value or empty
-                if existing.is_empty() {
-                    vec![]
-                } else {
-                    vec![existing.to_string()]
-                }
-            };
-
-            // Don't add duplicate
-            if !tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
-                tags.push(tag.to_string());
-            }
-
-            *line = format!("tags: [{}]", tags.join(", "));
-            found_tags = true;
-            break;
+    frontmatter::edit_file(path, |frontmatter| {
+        let mut tags = frontmatter.tags();
+        // Don't add duplicate
+        if !tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            tags.push(tag.to_string());
+            frontmatter.set_list("tags", &tags);
         }
-    }
-
-    if !found_tags {
-        // Insert tags after first line (or at end of frontmatter)
-        lines.push(format!("tags: [{}]", tag));
-    }
-
-    // Rebuild the file
-    let new_content = format!("---\n{}\n---{}", lines.join("\n"), body);
-    fs::write(path, new_content).map_err(|e| format!("Failed to write file: {}", e))?;
-
-    Ok(())
+    })
 }
 
 pub fn set_frontmatter_field(path: &str, key: &str, value: &str) -> Result<(), String> {
-    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read file: {}", e))?;
-
-    if !content.starts_with("---") {
-        // No frontmatter - add it with the field
-        let new_content = format!("---\n{}: {}\n---\n{}", key, value, content);
-        fs::write(path, new_content).map_err(|e| format!("Failed to write file: {}", e))?;
-        return Ok(());
-    }
-
-    let end_pos = content[3..]
-        .find("---")
-        .ok_or("Invalid frontmatter: no closing ---")?;
-    let fm_content = &content[3..end_pos + 3];
-    let body = &content[end_pos + 6..];
-
-    let mut lines: Vec<String> = fm_content
-        .lines()
-        .map(|l| l.to_string())
-        .filter(|l| !l.trim().is_empty())
-        .collect();
-
-    let mut found = false;
-    for line in lines.iter_mut() {
-        if line.starts_with(&format!("{}:", key)) {
-            *line = format!("{}: {}", key, value);
-            found = true;
-            break;
-        }
-    }
-
-    if !found {
-        lines.push(format!("{}: {}", key, value));
-    }
-
-    let new_content = format!("---\n{}\n---{}", lines.join("\n"), body);
-    fs::write(path, new_content).map_err(|e| format!("Failed to write file: {}", e))?;
-    Ok(())
+    frontmatter::edit_file(path, |frontmatter| frontmatter.set(key, value))
 }
 
 pub fn remove_frontmatter_field(path: &str, key: &str) -> Result<(), String> {
-    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read file: {}", e))?;
-
-    if !content.starts_with("---") {
-        return Ok(()); // No frontmatter, nothing to remove
-    }
-
-    let end_pos = content[3..]
-        .find("---")
-        .ok_or("Invalid frontmatter: no closing ---")?;
-    let fm_content = &content[3..end_pos + 3];
-    let body = &content[end_pos + 6..];
-
-    let lines: Vec<String> = fm_content
-        .lines()
-        .map(|l| l.to_string())
-        .filter(|l| !l.trim().is_empty())
-        .filter(|l| !l.starts_with(&format!("{}:", key)))
-        .collect();
-
-    let new_content = format!("---\n{}\n---{}", lines.join("\n"), body);
-    fs::write(path, new_content).map_err(|e| format!("Failed to write file: {}", e))?;
-    Ok(())
-}
-
-fn check_warnings(
-    body: &str,
-    frontmatter: &HashMap<String, String>,
-    _has_title: bool,
-    content_type: &str,
-) -> Vec<String> {
-    let mut warnings = Vec::new();
-
-    // Title is now optional - website will derive from filename if missing
-    // Date is still required for proper sorting/display
-    if !frontmatter.contains_key("date") {
-        warnings.push("No date".into());
-    }
-    if body.contains("TODO") || body.contains("FIXME") {
-        warnings.push("Has TODOs".into());
-    }
-    if body.contains("](./") || body.contains("](/attachments") {
-        warnings.push("Local images".into());
-    }
-
-    // Privacy linter for weeknotes — flag PII before publishing
-    if content_type == "weeknote" {
-        let privacy_warnings = check_privacy(body);
-        warnings.extend(privacy_warnings);
-    }
-
-    // Check for broken/empty links
-    let broken_link_patterns = [
-        "]()",     // Empty link
-        "](#)",    // Empty anchor
-        "](http)", // Incomplete http
-        "[[]]",    // Empty wikilink
-    ];
-    for pattern in broken_link_patterns {
-        if body.contains(pattern) {
-            warnings.push("Broken link".into());
-            break;
-        }
-    }
-
-    // Check for images with missing or junk alt text
-    let mut bad_alt_count = 0;
-    for caps in crate::patterns::MD_IMAGE_ALT_CHECK.captures_iter(body) {
-        let alt = caps.get(1).map(|m| m.as_str()).unwrap_or("");
-        if is_junk_alt(alt) {
-            bad_alt_count += 1;
-        }
-    }
-    if bad_alt_count > 0 {
-        warnings.push(format!("Missing alt text ({})", bad_alt_count));
-    }
-
-    // Check for potentially broken image embeds
-    let broken_image_patterns = [
-        "![]()",          // Empty image
-        "src=\"\"",       // Empty src in HTML
-        "src=''",         // Empty src single quotes
-        ".png)",          // Might be local
-        ".jpg)",          // Might be local
-        ".jpeg)",         // Might be local
-        ".gif)",          // Might be local
-        "](attachments/", // Obsidian attachments folder
-        "](Attachments/", // Obsidian attachments (capitalized)
-        "](assets/",      // Common local assets folder
-        "](images/",      // Common local images folder
-    ];
-
-    let mut has_local_media = false;
-    for pattern in broken_image_patterns {
-        if body.contains(pattern) {
-            // Skip if it's a cloudinary URL (those are fine)
-            if !body.contains("cloudinary") || pattern == "![]()" || pattern == "src=\"\"" {
-                has_local_media = true;
-                break;
-            }
-        }
-    }
-
-    if has_local_media && !warnings.contains(&"Local images".to_string()) {
-        warnings.push("Local media".into());
-    }
-
-    // Check for broken HTML embeds
-    if body.contains("<img")
-        && !body.contains("cloudinary")
-        && !body.contains("https://")
-        && !warnings.contains(&"Local media".to_string())
-    {
-        warnings.push("Local media".into());
-    }
-
-    // Check for video embeds that might be broken
-    if (body.contains("<video") || body.contains(".mp4)") || body.contains(".webm)"))
-        && !body.contains("cloudinary")
-        && !body.contains("https://")
-    {
-        warnings.push("Local video".into());
-    }
-
-    // Check for long link text (over 4 words)
-    if has_long_link_text(body, 4) {
-        warnings.push("Long link text".into());
-    }
-
-    warnings
+    frontmatter::edit_file(path, |frontmatter| {
+        frontmatter.remove(key);
+    })
 }
 
 /// Returns true if alt text is empty or junk (filenames, timestamps, UUIDs).
@@ -645,65 +547,6 @@ pub fn is_junk_alt(alt: &str) -> bool {
     false
 }
 
-fn has_long_link_text(body: &str, max_words: usize) -> bool {
-    for caps in crate::patterns::MD_LINK.captures_iter(body) {
-        if caps.get(1).is_some() {
-            continue;
-        }
-        let text = caps.get(2).map(|m| m.as_str()).unwrap_or("");
-        let word_count = text.split_whitespace().count();
-        if word_count > max_words {
-            return true;
-        }
-    }
-    false
-}
-
-/// Privacy linter for weeknotes — flags content that might be too personal for public publishing.
-/// Uses regex patterns to detect PII: phone numbers, emails, dollar amounts, SSNs,
-/// addresses, and references to specific people by name.
-fn check_privacy(body: &str) -> Vec<String> {
-    let mut warnings = Vec::new();
-
-    // Phone numbers (US formats: 555-123-4567, (555) 123-4567, +1 555 123 4567)
-    if crate::patterns::PHONE_NUMBER.is_match(body) {
-        warnings.push("[privacy]Phone number detected".into());
-    }
-
-    // Email addresses
-    if crate::patterns::EMAIL_ADDRESS.is_match(body) {
-        warnings.push("[privacy]Email address detected".into());
-    }
-
-    // Dollar amounts ($X,XXX or $XX,XXX+ — flag larger amounts as financial info)
-    if crate::patterns::MONEY_AMOUNT.is_match(body) {
-        warnings.push("[privacy]Financial amount detected".into());
-    }
-
-    // SSN patterns (XXX-XX-XXXX)
-    if crate::patterns::SSN.is_match(body) {
-        warnings.push("[privacy]Possible SSN detected".into());
-    }
-
-    // Street addresses (number + street name + type)
-    if crate::patterns::STREET_ADDRESS.is_match(body) {
-        warnings.push("[privacy]Street address detected".into());
-    }
-
-    // "Met with [Name]" / "talked to [Name]" / "called [Name]" patterns
-    // Catches "met with John Smith", "called Sarah", "lunch with Mike", etc.
-    if crate::patterns::PEOPLE_REFERENCE.is_match(body) {
-        warnings.push("[privacy]Named person reference".into());
-    }
-
-    // Health/medical terms
-    if crate::patterns::HEALTH_MEDICAL.is_match(body) {
-        warnings.push("[privacy]Health/medical info".into());
-    }
-
-    warnings
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -837,17 +680,18 @@ mod tests {
     #[test]
     fn test_parse_frontmatter_multiline_tags() {
         let content = "---\ntags:\n  - weekly-notes\n  - reflections\n  - progress\ndate: 2026-03-01T16:00:00-05:00\nmodified: 2026-03-01T19:04:57-05:00\n---\n## Week 2026-W09\n";
-        let (fm, body) = parse_frontmatter(content);
+        let (fm, body) = Frontmatter::parse(content);
         assert_eq!(
-            fm.get("date").unwrap(),
+            fm.get_str("date").unwrap(),
             "2026-03-01T16:00:00-05:00",
             "date should be parsed"
         );
         assert_eq!(
-            fm.get("modified").unwrap(),
+            fm.get_str("modified").unwrap(),
             "2026-03-01T19:04:57-05:00",
             "modified should be parsed"
         );
+        assert_eq!(fm.tags(), ["weekly-notes", "reflections", "progress"]);
         assert!(
             body.contains("## Week 2026-W09"),
             "body should contain heading"
@@ -864,59 +708,67 @@ mod tests {
     }
 
     #[test]
-    fn test_privacy_linter() {
-        // Phone number
-        let warnings = check_privacy("Called them at 555-123-4567 today");
-        assert!(
-            warnings.iter().any(|w| w.contains("Phone")),
-            "Should detect phone: {:?}",
-            warnings
+    fn test_parse_calendar_date_keeps_written_day() {
+        let d = |s| parse_calendar_date(s).map(|d| d.to_string());
+        assert_eq!(d("2025-12-30").as_deref(), Some("2025-12-30"));
+        assert_eq!(
+            d("2025-12-31T23:00:00-05:00").as_deref(),
+            Some("2025-12-31")
         );
+        assert_eq!(d("2025-12-31T23:00:00").as_deref(), Some("2025-12-31"));
+        assert_eq!(d("someday"), None);
+    }
 
-        // Email
-        let warnings = check_privacy("Emailed john@example.com about the project");
-        assert!(
-            warnings.iter().any(|w| w.contains("Email")),
-            "Should detect email: {:?}",
-            warnings
-        );
+    #[test]
+    fn test_find_published_locations_follows_pattern() {
+        let repo = std::env::temp_dir().join(format!("dispatch-vault-pub-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&repo);
+        for dir in [
+            "content/post/2024/03",
+            "content/post/drafts/01",
+            "content/post/2025/11",
+        ] {
+            fs::create_dir_all(repo.join(dir)).unwrap();
+        }
+        fs::write(repo.join("content/post/2024/03/hello.md"), "# hi\n").unwrap();
+        fs::write(repo.join("content/post/drafts/01/hello.md"), "# hi\n").unwrap();
 
-        // Money
-        let warnings = check_privacy("Got paid $12,500 for the project");
-        assert!(
-            warnings.iter().any(|w| w.contains("Financial")),
-            "Should detect money: {:?}",
-            warnings
-        );
+        let mut target = config::AppConfig::default().publish_targets[0].clone();
+        target.repo_path = repo.to_string_lossy().to_string();
+        target.content_path_pattern = "content/{content_type}/{year}/{month}".into();
 
-        // Named person
-        let warnings = check_privacy("Met with Sarah Johnson for coffee");
-        assert!(
-            warnings.iter().any(|w| w.contains("Named person")),
-            "Should detect person: {:?}",
-            warnings
-        );
+        let found = find_published_locations(&target, "hello");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].year.as_deref(), Some("2024"));
+        assert_eq!(found[0].month.as_deref(), Some("03"));
+        assert!(find_published_locations(&target, "missing").is_empty());
+        let all = find_all_published(&target);
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].slug, "hello");
 
-        // Health
-        let warnings = check_privacy("Had my therapy session on Tuesday");
-        assert!(
-            warnings.iter().any(|w| w.contains("Health")),
-            "Should detect health: {:?}",
-            warnings
-        );
+        let (url, _, content) = find_published_info_for_target(&target, "hello");
+        assert_eq!(url.as_deref(), Some("https://ejfox.com/blog/2024/hello"));
+        assert_eq!(content.as_deref(), Some("# hi\n"));
+    }
 
-        // Clean content
-        let warnings =
-            check_privacy("This week I worked on the website redesign and wrote some code.");
-        assert!(warnings.is_empty(), "Should be clean: {:?}", warnings);
+    #[test]
+    fn test_frontmatter_list_inline_and_block() {
+        let frontmatter_list = |content, key| Frontmatter::parse(content).0.get_list(key);
+        let inline = "---\ntargets: [main, \"mirror\"]\n---\nbody\n";
+        assert_eq!(frontmatter_list(inline, "targets"), vec!["main", "mirror"]);
+        let block = "---\ntitle: x\ntargets:\n  - main\n  - mirror\ndate: 2026-01-01\n---\n";
+        assert_eq!(frontmatter_list(block, "targets"), vec!["main", "mirror"]);
+        let single = "---\ntargets: mirror\n---\n";
+        assert_eq!(frontmatter_list(single, "targets"), vec!["mirror"]);
+        assert!(frontmatter_list("---\ntitle: x\n---\n", "targets").is_empty());
     }
 
     #[test]
     fn test_parse_frontmatter_with_publish_at() {
         let content = "---\ndate: 2026-01-31\npublish_at: 2026-03-01T09:00:00Z\n---\n\n# Test\n";
-        let (fm, body) = parse_frontmatter(content);
-        assert_eq!(fm.get("publish_at").unwrap(), "2026-03-01T09:00:00Z");
-        assert_eq!(fm.get("date").unwrap(), "2026-01-31");
+        let (fm, body) = Frontmatter::parse(content);
+        assert_eq!(fm.get_str("publish_at").unwrap(), "2026-03-01T09:00:00Z");
+        assert_eq!(fm.get_str("date").unwrap(), "2026-01-31");
         assert!(body.contains("# Test"));
     }
 }
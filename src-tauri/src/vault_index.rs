@@ -0,0 +1,902 @@
+//! A persistent index of the vault's notes, so listing them is a query
+//! instead of a walk that re-reads every file.
+//!
+//! Each note's row holds what's read from its text (frontmatter, word
+//! count, lint findings, links, media) and where its published copy is in the
+//! default target's repo. A row is re-read only when the file's mtime or
+//! size moves and its content hash changed with them. `vault_watcher`
+//! passes along the notes each save touched; the first query of a session
+//! walks the vault once (stat only) for whatever changed while the app
+//! wasn't watching. Published status is refreshed when the target repo's
+//! HEAD or the publish config changes, which every publish, unpublish and
+//! rename does by committing.
+
+use crate::config::{self, PublishTarget, VaultConfig};
+use crate::frontmatter::Frontmatter;
+use crate::git::Repo;
+use crate::vault::{self, PublishedLocation};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use walkdir::WalkDir;
+
+/// Bumped when what's read from a note changes, so rows written by an
+/// older build get read again.
+const INDEX_VERSION: &str = "3";
+
+/// Whether this session has walked the vault yet.
+static SYNCED: AtomicBool = AtomicBool::new(false);
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// One note as the index holds it.
+#[derive(Debug, Clone, Default)]
+pub struct Note {
+    pub path: String,
+    pub slug: String,
+    /// The file's mtime, in milliseconds.
+    pub mtime_ms: i64,
+    /// Frontmatter `date`, the week-note filename, or the file's creation
+    /// time.
+    pub created: u64,
+    /// Frontmatter `modified`, or the file's mtime.
+    pub modified: u64,
+    /// Frontmatter `title`.
+    pub title: Option<String>,
+    /// The first heading.
+    pub heading: Option<String>,
+    pub tags: Vec<String>,
+    /// The frontmatter block as written; `Frontmatter::parse` reads it.
+    pub frontmatter: String,
+    pub word_count: usize,
+    /// Every lint rule's findings, at their default levels; `lint::leveled`
+    /// applies the config.
+    pub lint: Vec<crate::lint::Finding>,
+    pub links: Vec<Link>,
+    pub media: Vec<MediaRef>,
+    pub published: Option<Published>,
+}
+
+/// A link to another note or a URL. Embeds of notes count; embedded
+/// images and files are `media`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Link {
+    /// The note name, path or URL, as written (percent-decoded for
+    /// markdown links). Empty for a link to a heading in the same note.
+    pub target: String,
+    /// After `#`: a heading, or `^block`, in the target.
+    pub anchor: Option<String>,
+    /// The text shown for it: after `|` in a wiki-link, or a markdown
+    /// link's text.
+    pub alias: Option<String>,
+    /// `![[note]]`.
+    pub embed: bool,
+    /// "wiki" or "markdown".
+    pub kind: String,
+    /// 1-based.
+    pub line: usize,
+    /// The line it's on, trimmed to 100 characters.
+    pub context: String,
+}
+
+/// An image, video or other file the note shows.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MediaRef {
+    /// The URL or vault path as written.
+    pub src: String,
+    /// 1-based.
+    pub line: usize,
+    /// The line it's on, trimmed to 100 characters.
+    pub context: String,
+}
+
+/// The note's live copy in the default target's repo.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Published {
+    pub path: String,
+    pub mtime_ms: i64,
+    pub url: String,
+    /// The copy's mtime, in seconds.
+    pub date: u64,
+    pub word_count: usize,
+    /// What a publish would write now isn't what's there.
+    pub differs: bool,
+}
+
+/// What indexing needs from the config, loaded once per call.
+struct Context {
+    vault_path: String,
+    vault: VaultConfig,
+    target: Option<PublishTarget>,
+}
+
+impl Context {
+    fn load() -> Result<Context, String> {
+        let app_config = config::get()?;
+        Ok(Context {
+            vault_path: app_config.vault.path.clone(),
+            vault: app_config.vault,
+            target: config::default_target().ok(),
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Database
+// ---------------------------------------------------------------------------
+
+static DB: OnceLock<Result<Mutex<Connection>, String>> = OnceLock::new();
+
+fn db_path() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_default();
+    PathBuf::from(home)
+        .join("Library/Caches/com.ejfox.dispatch")
+        .join("vault_index.db")
+}
+
+fn init_db() -> Result<Mutex<Connection>, String> {
+    let path = db_path();
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    let conn = match try_open(&path) {
+        Ok(conn) => conn,
+        Err(first_err) => {
+            // It's a cache: start over rather than fail. Without a usable
+            // file, index in memory for this session.
+            log::warn!("Vault index unusable ({}); rebuilding.", first_err);
+            let _ = std::fs::remove_file(&path);
+            try_open(&path).or_else(|e| {
+                log::warn!("Vault index kept in memory: {}", e);
+                let conn = Connection::open_in_memory()
+                    .map_err(|e| format!("Failed to open vault index: {}", e))?;
+                init_schema(&conn)?;
+                Ok::<_, String>(conn)
+            })?
+        }
+    };
+    Ok(Mutex::new(conn))
+}
+
+fn try_open(path: &Path) -> Result<Connection, String> {
+    let conn = Connection::open(path).map_err(|e| format!("Failed to open vault index: {}", e))?;
+    conn.execute_batch("PRAGMA journal_mode=WAL;")
+        .map_err(|e| format!("Failed to set pragmas: {}", e))?;
+    init_schema(&conn)?;
+    Ok(conn)
+}
+
+fn get_db() -> Result<&'static Mutex<Connection>, String> {
+    DB.get_or_init(init_db).as_ref().map_err(|e| e.clone())
+}
+
+fn init_schema(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS meta (
+            key   TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        );",
+    )
+    .map_err(|e| format!("Failed to create vault index schema: {}", e))?;
+    let version: Option<String> = conn
+        .query_row("SELECT value FROM meta WHERE key = 'version'", [], |r| {
+            r.get(0)
+        })
+        .optional()
+        .map_err(|e| format!("Query failed: {}", e))?;
+    // Rows from another version may not even have this one's columns.
+    if version.as_deref() != Some(INDEX_VERSION) {
+        conn.execute("DROP TABLE IF EXISTS notes", [])
+            .map_err(|e| format!("Drop failed: {}", e))?;
+    }
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS notes (
+            path                 TEXT PRIMARY KEY,
+            mtime_ms             INTEGER NOT NULL,
+            size                 INTEGER NOT NULL,
+            hash                 TEXT NOT NULL,
+            slug                 TEXT NOT NULL,
+            created              INTEGER NOT NULL,
+            modified             INTEGER NOT NULL,
+            title                TEXT,
+            heading              TEXT,
+            tags                 TEXT NOT NULL,
+            frontmatter          TEXT NOT NULL,
+            word_count           INTEGER NOT NULL,
+            lint                 TEXT NOT NULL,
+            links                TEXT NOT NULL,
+            media                TEXT NOT NULL,
+            published_path       TEXT,
+            published_mtime_ms   INTEGER,
+            published_url        TEXT,
+            published_date       INTEGER,
+            published_word_count INTEGER,
+            published_differs    INTEGER NOT NULL DEFAULT 0
+        );
+        CREATE INDEX IF NOT EXISTS idx_notes_slug ON notes(slug);
+        CREATE INDEX IF NOT EXISTS idx_notes_modified ON notes(modified);",
+    )
+    .map_err(|e| format!("Failed to create vault index schema: {}", e))?;
+    // Search tables that are new need the text of notes already indexed.
+    let new_search = crate::search::init_schema(conn)?;
+    if new_search || version.as_deref() != Some(INDEX_VERSION) {
+        conn.execute("DELETE FROM notes", [])
+            .map_err(|e| format!("Delete failed: {}", e))?;
+        conn.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES ('version', ?1)",
+            params![INDEX_VERSION],
+        )
+        .map_err(|e| format!("Update failed: {}", e))?;
+    }
+    Ok(())
+}
+
+const COLUMNS: &str = "path, slug, mtime_ms, created, modified, title, heading, tags, frontmatter, word_count, lint, links, media, published_path, published_mtime_ms, published_url, published_date, published_word_count, published_differs";
+
+fn note_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Note> {
+    let json = |i: usize| row.get::<_, String>(i);
+    let published = match row.get::<_, Option<String>>(13)? {
+        Some(path) => Some(Published {
+            path,
+            mtime_ms: row.get::<_, Option<i64>>(14)?.unwrap_or_default(),
+            url: row.get::<_, Option<String>>(15)?.unwrap_or_default(),
+            date: row.get::<_, Option<i64>>(16)?.unwrap_or_default() as u64,
+            word_count: row.get::<_, Option<i64>>(17)?.unwrap_or_default() as usize,
+            differs: row.get(18)?,
+        }),
+        None => None,
+    };
+    Ok(Note {
+        path: row.get(0)?,
+        slug: row.get(1)?,
+        mtime_ms: row.get(2)?,
+        created: row.get::<_, i64>(3)? as u64,
+        modified: row.get::<_, i64>(4)? as u64,
+        title: row.get(5)?,
+        heading: row.get(6)?,
+        tags: serde_json::from_str(&json(7)?).unwrap_or_default(),
+        frontmatter: row.get(8)?,
+        word_count: row.get::<_, i64>(9)? as usize,
+        lint: serde_json::from_str(&json(10)?).unwrap_or_default(),
+        links: serde_json::from_str(&json(11)?).unwrap_or_default(),
+        media: serde_json::from_str(&json(12)?).unwrap_or_default(),
+        published,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Queries
+// ---------------------------------------------------------------------------
+
+/// Every note in the vault, in no particular order. The first call of a
+/// session walks the vault for changes made while the app wasn't watching.
+pub fn notes() -> Result<Vec<Note>, String> {
+    let ctx = Context::load()?;
+    let db = get_db()?.lock().map_err(|e| format!("DB lock: {}", e))?;
+    bring_up_to_date(&db, &ctx)?;
+    select_notes(&db)
+}
+
+/// Re-read one note from disk and return it.
+pub fn index_file(path: &Path) -> Result<Note, String> {
+    let ctx = Context::load()?;
+    let db = get_db()?.lock().map_err(|e| format!("DB lock: {}", e))?;
+    bring_up_to_date(&db, &ctx)?;
+    index_path(&db, &ctx, path, |slug| published_copy(&ctx, slug))?
+        .ok_or_else(|| format!("Failed to read {}", path.display()))
+}
+
+/// Run `f` against the index once it's up to date. `search` keeps its
+/// tables in the same database.
+pub(crate) fn with_index<T>(f: impl FnOnce(&Connection) -> Result<T, String>) -> Result<T, String> {
+    let ctx = Context::load()?;
+    let db = get_db()?.lock().map_err(|e| format!("DB lock: {}", e))?;
+    bring_up_to_date(&db, &ctx)?;
+    f(&db)
+}
+
+/// Walk the vault and re-read whatever changed since the index last saw
+/// it.
+pub fn sync() -> Result<(), String> {
+    let ctx = Context::load()?;
+    let db = get_db()?.lock().map_err(|e| format!("DB lock: {}", e))?;
+    sync_in(&db, &ctx)?;
+    SYNCED.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Re-read notes that changed on disk; ones that are gone drop out.
+pub fn refresh(paths: &[PathBuf]) {
+    let Ok(ctx) = Context::load() else {
+        return;
+    };
+    let Ok(db) = get_db().and_then(|db| db.lock().map_err(|e| e.to_string())) else {
+        return;
+    };
+    if let Err(e) = refresh_in(&db, &ctx, paths) {
+        log::warn!("vault_index: refresh failed: {}", e);
+    }
+}
+
+fn bring_up_to_date(conn: &Connection, ctx: &Context) -> Result<(), String> {
+    if SYNCED.swap(true, Ordering::SeqCst) {
+        return sync_published(conn, ctx, false);
+    }
+    sync_in(conn, ctx).inspect_err(|_| SYNCED.store(false, Ordering::SeqCst))
+}
+
+pub(crate) fn select_notes(conn: &Connection) -> Result<Vec<Note>, String> {
+    let mut stmt = conn
+        .prepare(&format!("SELECT {} FROM notes", COLUMNS))
+        .map_err(|e| format!("Query failed: {}", e))?;
+    let notes = stmt
+        .query_map([], note_from_row)
+        .map_err(|e| format!("Query failed: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Row read failed: {}", e))?;
+    Ok(notes)
+}
+
+fn select_note(conn: &Connection, path: &str) -> Result<Option<Note>, String> {
+    conn.query_row(
+        &format!("SELECT {} FROM notes WHERE path = ?1", COLUMNS),
+        params![path],
+        note_from_row,
+    )
+    .optional()
+    .map_err(|e| format!("Query failed: {}", e))
+}
+
+// ---------------------------------------------------------------------------
+// Indexing
+// ---------------------------------------------------------------------------
+
+fn sync_in(conn: &Connection, ctx: &Context) -> Result<(), String> {
+    let known: HashMap<String, (i64, i64)> = {
+        let mut stmt = conn
+            .prepare("SELECT path, mtime_ms, size FROM notes")
+            .map_err(|e| format!("Query failed: {}", e))?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, (row.get(1)?, row.get(2)?))))
+            .map_err(|e| format!("Query failed: {}", e))?
+            .collect::<Result<_, _>>()
+            .map_err(|e| format!("Row read failed: {}", e))?;
+        rows
+    };
+
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|e| format!("Vault index transaction: {}", e))?;
+    let mut seen = HashSet::new();
+    let mut reread = 0;
+    for entry in walk(&ctx.vault_path) {
+        let path = entry.path().to_string_lossy().to_string();
+        let stamp = entry
+            .metadata()
+            .ok()
+            .map(|m| (mtime_ms(&m), m.len() as i64));
+        if stamp.is_some() && known.get(&path) == stamp.as_ref() {
+            seen.insert(path);
+            continue;
+        }
+        // Published status is filled in for all of them at once below.
+        if index_path(&tx, ctx, entry.path(), |_| None)?.is_some() {
+            reread += 1;
+            seen.insert(path);
+        }
+    }
+    let gone: Vec<&String> = known.keys().filter(|p| !seen.contains(*p)).collect();
+    for path in &gone {
+        delete(&tx, path)?;
+    }
+    sync_published(&tx, ctx, true)?;
+    tx.commit()
+        .map_err(|e| format!("Vault index commit: {}", e))?;
+    log::info!(
+        "vault_index: {} note(s), {} re-read, {} gone",
+        seen.len(),
+        reread,
+        gone.len()
+    );
+    Ok(())
+}
+
+fn refresh_in(conn: &Connection, ctx: &Context, paths: &[PathBuf]) -> Result<(), String> {
+    for path in paths {
+        let indexable = path.starts_with(&ctx.vault_path)
+            && is_markdown(path)
+            && !path
+                .strip_prefix(&ctx.vault_path)
+                .is_ok_and(|rel| rel.iter().any(|part| is_skipped(&part.to_string_lossy())));
+        if !indexable || index_path(conn, ctx, path, |slug| published_copy(ctx, slug))?.is_none() {
+            delete(conn, &path.to_string_lossy())?;
+        }
+    }
+    Ok(())
+}
+
+/// Bring one note's row up to date; None if it can't be read. A file that
+/// was touched but not changed keeps its row.
+fn index_path(
+    conn: &Connection,
+    ctx: &Context,
+    path: &Path,
+    find_copy: impl FnOnce(&str) -> Option<PublishedLocation>,
+) -> Result<Option<Note>, String> {
+    let (Ok(meta), Ok(content)) = (fs::metadata(path), fs::read_to_string(path)) else {
+        return Ok(None);
+    };
+    let key = path.to_string_lossy().to_string();
+    let (mtime, size, hash) = (mtime_ms(&meta), meta.len() as i64, content_hash(&content));
+    let stored_hash: Option<String> = conn
+        .query_row(
+            "SELECT hash FROM notes WHERE path = ?1",
+            params![key],
+            |r| r.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("Query failed: {}", e))?;
+    if stored_hash.as_deref() == Some(hash.as_str()) {
+        conn.execute(
+            "UPDATE notes SET mtime_ms = ?2, size = ?3 WHERE path = ?1",
+            params![key, mtime, size],
+        )
+        .map_err(|e| format!("Update failed: {}", e))?;
+        return select_note(conn, &key);
+    }
+
+    let mut note = read(path, &content, &meta);
+    note.published = find_copy(&note.slug).and_then(|copy| published(ctx, &content, &copy));
+    conn.execute(
+        "INSERT OR REPLACE INTO notes
+         (path, mtime_ms, size, hash, slug, created, modified, title, heading, tags, frontmatter,
+          word_count, lint, links, media)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+        params![
+            note.path,
+            mtime,
+            size,
+            hash,
+            note.slug,
+            note.created as i64,
+            note.modified as i64,
+            note.title,
+            note.heading,
+            to_json(&note.tags),
+            note.frontmatter,
+            note.word_count as i64,
+            to_json(&note.lint),
+            to_json(&note.links),
+            to_json(&note.media),
+        ],
+    )
+    .map_err(|e| format!("Insert failed: {}", e))?;
+    crate::search::index_note(
+        conn,
+        &note.path,
+        note.title.as_deref().or(note.heading.as_deref()),
+        &content,
+    )?;
+    set_published(conn, &note.path, note.published.as_ref())?;
+    Ok(Some(note))
+}
+
+/// Re-check every note's published copy, when the target repo or the
+/// publish config has changed since the last check (or always, if
+/// `force`). Only notes whose copy moved or changed are re-read.
+fn sync_published(conn: &Connection, ctx: &Context, force: bool) -> Result<(), String> {
+    let key = published_key(ctx);
+    let stored: Option<String> = conn
+        .query_row("SELECT value FROM meta WHERE key = 'published'", [], |r| {
+            r.get(0)
+        })
+        .optional()
+        .map_err(|e| format!("Query failed: {}", e))?;
+    if !force && stored.as_deref() == Some(key.as_str()) {
+        return Ok(());
+    }
+
+    let mut copies: HashMap<String, PublishedLocation> = HashMap::new();
+    if let Some(target) = &ctx.target {
+        for copy in vault::find_all_published(target) {
+            if !copies.contains_key(&copy.slug) && !vault::is_drafted(&copy.path) {
+                copies.insert(copy.slug.clone(), copy);
+            }
+        }
+    }
+
+    let rows: Vec<(String, String, Option<String>, Option<i64>)> = {
+        let mut stmt = conn
+            .prepare("SELECT path, slug, published_path, published_mtime_ms FROM notes")
+            .map_err(|e| format!("Query failed: {}", e))?;
+        let rows = stmt
+            .query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)))
+            .map_err(|e| format!("Query failed: {}", e))?
+            .collect::<Result<_, _>>()
+            .map_err(|e| format!("Row read failed: {}", e))?;
+        rows
+    };
+    for (path, slug, stored_path, stored_mtime) in rows {
+        let copy = copies.get(&slug);
+        let now = copy.map(|c| {
+            let mtime = fs::metadata(&c.path).map(|m| mtime_ms(&m)).ok();
+            (c.path.to_string_lossy().to_string(), mtime)
+        });
+        if now == stored_path.map(|p| (p, stored_mtime)) {
+            continue;
+        }
+        let published = copy.and_then(|copy| {
+            let content = fs::read_to_string(&path).ok()?;
+            published(ctx, &content, copy)
+        });
+        set_published(conn, &path, published.as_ref())?;
+    }
+
+    conn.execute(
+        "INSERT OR REPLACE INTO meta (key, value) VALUES ('published', ?1)",
+        params![key],
+    )
+    .map_err(|e| format!("Update failed: {}", e))?;
+    Ok(())
+}
+
+fn set_published(
+    conn: &Connection,
+    path: &str,
+    published: Option<&Published>,
+) -> Result<(), String> {
+    conn.execute(
+        "UPDATE notes SET published_path = ?2, published_mtime_ms = ?3, published_url = ?4,
+         published_date = ?5, published_word_count = ?6, published_differs = ?7
+         WHERE path = ?1",
+        params![
+            path,
+            published.map(|p| &p.path),
+            published.map(|p| p.mtime_ms),
+            published.map(|p| &p.url),
+            published.map(|p| p.date as i64),
+            published.map(|p| p.word_count as i64),
+            published.is_some_and(|p| p.differs),
+        ],
+    )
+    .map_err(|e| format!("Update failed: {}", e))?;
+    Ok(())
+}
+
+fn delete(conn: &Connection, path: &str) -> Result<(), String> {
+    // A deleted folder takes its notes with it.
+    conn.execute(
+        "DELETE FROM notes WHERE path = ?1 OR substr(path, 1, length(?1) + 1) = ?1 || '/'",
+        params![path],
+    )
+    .map_err(|e| format!("Delete failed: {}", e))?;
+    crate::search::remove_note(conn, path)
+}
+
+/// Changes whenever published status might have: the target repo moved
+/// to another commit, or the target or vault config was edited.
+fn published_key(ctx: &Context) -> String {
+    let mut hasher = Sha256::new();
+    if let Some(target) = &ctx.target {
+        hasher.update(serde_json::to_string(target).unwrap_or_default());
+        let head = Repo::open(&target.repo_path).ok().and_then(|r| r.head());
+        hasher.update(head.unwrap_or_default());
+    }
+    hasher.update(serde_json::to_string(&ctx.vault).unwrap_or_default());
+    hex::encode(hasher.finalize())
+}
+
+/// The note's live copy in the default target: the first one that isn't
+/// a draft.
+fn published_copy(ctx: &Context, slug: &str) -> Option<PublishedLocation> {
+    vault::find_published_locations(ctx.target.as_ref()?, slug)
+        .into_iter()
+        .find(|copy| !vault::is_drafted(&copy.path))
+}
+
+fn published(ctx: &Context, content: &str, copy: &PublishedLocation) -> Option<Published> {
+    let target = ctx.target.as_ref()?;
+    let meta = fs::metadata(&copy.path).ok()?;
+    let published_content = fs::read_to_string(&copy.path).ok()?;
+    // Against what a publish would write now, not the raw note.
+    let rendered = crate::transform::preview(content, target, &ctx.vault);
+    Some(Published {
+        path: copy.path.to_string_lossy().to_string(),
+        mtime_ms: mtime_ms(&meta),
+        url: target.post_url(copy.year.as_deref(), copy.month.as_deref(), &copy.slug),
+        date: (mtime_ms(&meta) / 1000) as u64,
+        word_count: published_content.split_whitespace().count(),
+        differs: vault::content_differs(&rendered, &published_content),
+    })
+}
+
+/// Read a note's text into its row, all but the published copy.
+fn read(path: &Path, content: &str, meta: &fs::Metadata) -> Note {
+    let path_str = path.to_string_lossy().to_string();
+    let content_type = vault::content_type_for_path(&path_str);
+    let (frontmatter, body) = Frontmatter::parse(content);
+    let fs_modified = (mtime_ms(meta) / 1000) as u64;
+    let fs_created = meta
+        .created()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map_or(fs_modified, |d| d.as_secs());
+
+    // Prefer frontmatter dates over filesystem dates. Week notes take
+    // theirs from the filename (e.g. "2025-37.md" = week 37 of 2025).
+    let filename_date = if content_type == "weeknote" {
+        vault::parse_weeknote_filename(path)
+    } else {
+        None
+    };
+    let heading = vault::extract_h1_title(body);
+    Note {
+        slug: path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string(),
+        mtime_ms: mtime_ms(meta),
+        created: frontmatter
+            .get_str("date")
+            .and_then(|d| vault::parse_iso_date(&d))
+            .or(filename_date)
+            .unwrap_or(fs_created),
+        modified: frontmatter
+            .get_str("modified")
+            .and_then(|d| vault::parse_iso_date(&d))
+            .unwrap_or(fs_modified),
+        title: frontmatter.get_str("title").filter(|t| !t.is_empty()),
+        tags: frontmatter.tags(),
+        lint: crate::lint::check(content),
+        heading,
+        word_count: body.split_whitespace().count(),
+        links: links(content),
+        media: media(content),
+        frontmatter: frontmatter.block(),
+        path: path_str,
+        published: None,
+    }
+}
+
+/// Wiki-links, embeds of other notes and markdown links in the body,
+/// outside fenced code blocks.
+fn links(content: &str) -> Vec<Link> {
+    use crate::patterns::{MD_LINK, WIKI_LINK};
+    let body = crate::frontmatter::body(content);
+    let first_line = content[..content.len() - body.len()].matches('\n').count() + 1;
+    let mut links = Vec::new();
+    let mut fenced = false;
+    for (i, line) in body.lines().enumerate() {
+        if line.trim_start().starts_with("```") {
+            fenced = !fenced;
+        }
+        if fenced {
+            continue;
+        }
+        let link =
+            |target: &str, anchor: Option<&str>, alias: Option<&str>, embed, kind: &str| Link {
+                target: target.to_string(),
+                anchor: non_empty(anchor),
+                alias: non_empty(alias),
+                embed,
+                kind: kind.into(),
+                line: first_line + i,
+                context: line.trim().chars().take(100).collect(),
+            };
+        for caps in WIKI_LINK.captures_iter(line) {
+            let target = caps[2].trim();
+            let embed = !caps[1].is_empty();
+            if embed && Path::new(target).extension().is_some_and(|ext| ext != "md") {
+                continue;
+            }
+            let anchor = caps.get(3).map(|m| &m.as_str()[1..]);
+            let alias = caps.get(4).map(|m| m.as_str());
+            links.push(link(target, anchor, alias, embed, "wiki"));
+        }
+        // Images are media.
+        let markdown = MD_LINK.captures_iter(line).filter(|c| c.get(1).is_none());
+        for caps in markdown {
+            // `(url "title")` and `(<url>)`
+            let dest = caps[3].split_whitespace().next().unwrap_or_default();
+            let dest = dest.trim_start_matches('<').trim_end_matches('>');
+            let (target, anchor) = match dest.split_once('#') {
+                Some((target, anchor)) if !dest.contains("://") => (target, Some(anchor)),
+                _ => (dest, None),
+            };
+            let target = urlencoding::decode(target).unwrap_or_else(|_| target.into());
+            links.push(link(&target, anchor, Some(&caps[2]), false, "markdown"));
+        }
+    }
+    // `[[#Heading]]` with no heading isn't a link.
+    links.retain(|l| !l.target.is_empty() || l.anchor.is_some());
+    links
+}
+
+fn non_empty(text: Option<&str>) -> Option<String> {
+    text.map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(String::from)
+}
+
+/// Cloudinary URLs anywhere in the note, plus markdown and HTML images
+/// and `![[file]]` embeds of anything but another note.
+fn media(content: &str) -> Vec<MediaRef> {
+    use crate::patterns::{CLOUDINARY_URL, HTML_IMG, HTML_VIDEO, MD_IMAGE, WIKI_LINK};
+    let mut refs = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        let cloudinary = CLOUDINARY_URL.find_iter(line).map(|m| m.as_str());
+        let images = MD_IMAGE
+            .captures_iter(line)
+            .chain(HTML_IMG.captures_iter(line))
+            .chain(HTML_VIDEO.captures_iter(line))
+            .filter_map(|caps| caps.iter().skip(1).flatten().last())
+            .map(|m| m.as_str().trim())
+            .filter(|src| !CLOUDINARY_URL.is_match(src));
+        let embeds = WIKI_LINK
+            .captures_iter(line)
+            .filter(|caps| !caps[1].is_empty())
+            .filter_map(|caps| caps.get(2))
+            .map(|m| m.as_str().trim())
+            .filter(|name| Path::new(name).extension().is_some_and(|ext| ext != "md"));
+        for src in cloudinary.chain(images).chain(embeds) {
+            refs.push(MediaRef {
+                src: src.to_string(),
+                line: i + 1,
+                context: line.trim().chars().take(100).collect(),
+            });
+        }
+    }
+    refs
+}
+
+fn walk(vault_path: &str) -> impl Iterator<Item = walkdir::DirEntry> {
+    WalkDir::new(vault_path)
+        .into_iter()
+        .filter_entry(|e| e.depth() == 0 || !is_skipped(&e.file_name().to_string_lossy()))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && is_markdown(e.path()))
+}
+
+/// `.obsidian`, `.trash`, `.git` and other hidden folders, and
+/// `node_modules`.
+fn is_skipped(name: &str) -> bool {
+    name.starts_with('.') || name == "node_modules"
+}
+
+fn is_markdown(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("md"))
+}
+
+pub(crate) fn mtime_ms(meta: &fs::Metadata) -> i64 {
+    meta.modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_millis() as i64)
+}
+
+fn to_json<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+fn content_hash(content: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(content.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::git::tests::{remote_and_clone, scratch, write};
+
+    /// An in-memory index of `vault`, synced against `target`.
+    pub(crate) fn synced(vault: &Path, target: &PublishTarget) -> Connection {
+        let app_config = config::AppConfig::default();
+        let ctx = Context {
+            vault_path: vault.to_string_lossy().to_string(),
+            vault: VaultConfig {
+                path: vault.to_string_lossy().to_string(),
+                ..app_config.vault
+            },
+            target: Some(target.clone()),
+        };
+        let conn = Connection::open_in_memory().unwrap();
+        init_schema(&conn).unwrap();
+        sync_in(&conn, &ctx).unwrap();
+        conn
+    }
+
+    const TEA: &str = "---\ntitle: Tea\ntags: [drinks]\n---\n# Tea\n\nSee [[Coffee|the other one]] and [a site](https://example.com).\n\n![pot](https://res.cloudinary.com/ejf/image/upload/v1/pot.jpg)\n![[leaf.png]]\n";
+
+    #[test]
+    fn index_follows_the_vault_and_the_repo() {
+        let vault = scratch("index-vault");
+        let (_remote, site) = remote_and_clone("index-site");
+        let app_config = config::AppConfig::default();
+        let mut target = app_config.publish_targets[0].clone();
+        target.repo_path = site.to_string_lossy().to_string();
+        let ctx = Context {
+            vault_path: vault.to_string_lossy().to_string(),
+            vault: VaultConfig {
+                path: vault.to_string_lossy().to_string(),
+                ..app_config.vault
+            },
+            target: Some(target.clone()),
+        };
+        let conn = Connection::open_in_memory().unwrap();
+        init_schema(&conn).unwrap();
+        let tea = write(&vault, "blog/tea.md", TEA);
+        let coffee = write(&vault, "drafts/coffee.md", "# Coffee\n\nTODO\n");
+        write(&vault, ".obsidian/workspace.md", "# not a note\n");
+
+        sync_in(&conn, &ctx).unwrap();
+        assert_eq!(select_notes(&conn).unwrap().len(), 2);
+        let note = select_note(&conn, &tea.to_string_lossy()).unwrap().unwrap();
+        assert_eq!(note.slug, "tea");
+        assert_eq!(note.title.as_deref(), Some("Tea"));
+        assert_eq!(note.tags, ["drinks"]);
+        let links: Vec<(&str, Option<&str>, usize)> = note
+            .links
+            .iter()
+            .map(|l| (l.target.as_str(), l.alias.as_deref(), l.line))
+            .collect();
+        assert_eq!(
+            links,
+            [
+                ("Coffee", Some("the other one"), 7),
+                ("https://example.com", Some("a site"), 7)
+            ]
+        );
+        let media: Vec<(&str, usize)> = note
+            .media
+            .iter()
+            .map(|m| (m.src.as_str(), m.line))
+            .collect();
+        assert_eq!(
+            media,
+            [
+                ("https://res.cloudinary.com/ejf/image/upload/v1/pot.jpg", 9),
+                ("leaf.png", 10)
+            ]
+        );
+        assert!(note.published.is_none());
+        let draft = select_note(&conn, &coffee.to_string_lossy())
+            .unwrap()
+            .unwrap();
+        assert!(draft.lint.iter().any(|f| f.rule == "todo"));
+
+        // Publishing commits to the repo, which is what brings the copy in.
+        let rendered = crate::transform::preview(TEA, &target, &ctx.vault);
+        write(&site, "content/blog/2026/tea.md", &rendered);
+        let repo = Repo::open(&site).unwrap();
+        repo.add(&["content/blog/2026/tea.md"]).unwrap();
+        repo.commit("Publish: tea").unwrap();
+        sync_published(&conn, &ctx, false).unwrap();
+        let published = select_note(&conn, &tea.to_string_lossy())
+            .unwrap()
+            .unwrap()
+            .published
+            .unwrap();
+        assert_eq!(published.url, "https://ejfox.com/blog/2026/tea");
+        assert!(!published.differs);
+
+        fs::write(&tea, TEA.replace("# Tea", "# Green tea")).unwrap();
+        refresh_in(&conn, &ctx, std::slice::from_ref(&tea)).unwrap();
+        let note = select_note(&conn, &tea.to_string_lossy()).unwrap().unwrap();
+        assert_eq!(note.heading.as_deref(), Some("Green tea"));
+        assert!(note.published.unwrap().differs);
+
+        fs::remove_file(&coffee).unwrap();
+        refresh_in(&conn, &ctx, std::slice::from_ref(&coffee)).unwrap();
+        assert_eq!(select_notes(&conn).unwrap().len(), 1);
+    }
+}
@@ -1,8 +1,6 @@
 use crate::config;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::fs;
-use walkdir::WalkDir;
 
 /// A signal detected from vault observation — never actionable toward publishing,
 /// purely informational about writing patterns.
@@ -55,21 +53,16 @@ pub fn scan_vault() -> Result<VaultPulse, String> {
         .unwrap_or_default()
         .as_secs();
 
-    for entry in WalkDir::new(vault_path)
-        .into_iter()
-        .filter_map(|e| e.ok())
-        .filter(|e| e.path().extension().is_some_and(|ext| ext == "md"))
-    {
-        let path = entry.path();
-        let path_str = path.to_string_lossy().to_string();
+    for note in crate::vault_index::notes()? {
+        let path = std::path::Path::new(&note.path);
+        let path_str = note.path.clone();
 
-        // Skip hidden dirs, .obsidian, templates, attachments
-        if path_str.contains("/.obsidian/")
-            || path_str.contains("/templates/")
+        // Skip templates, attachments, stale and archived notes (the index
+        // already leaves out hidden dirs and node_modules)
+        if path_str.contains("/templates/")
             || path_str.contains("/attachments/")
             || path_str.contains("/_stale/")
             || path_str.contains("/_archive/")
-            || path_str.contains("/node_modules/")
         {
             continue;
         }
@@ -82,20 +75,11 @@ pub fn scan_vault() -> Result<VaultPulse, String> {
             continue;
         }
 
-        let content = match fs::read_to_string(path) {
-            Ok(c) => c,
-            Err(_) => continue,
-        };
-
-        let modified = fs::metadata(path)
-            .and_then(|m| m.modified())
-            .ok()
-            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-            .map(|d| d.as_secs())
-            .unwrap_or(0);
-
-        let word_count = count_words(&content);
-        let (title, tags) = parse_frontmatter_light(&content);
+        let modified = (note.mtime_ms / 1000).max(0) as u64;
+        let word_count = note.word_count;
+        // Fallback: title from first heading
+        let title = note.title.clone().or_else(|| note.heading.clone());
+        let tags = note.tags;
 
         // Determine source dir
         let rel_path = path_str
@@ -249,88 +233,3 @@ pub fn scan_vault() -> Result<VaultPulse, String> {
         recent_edits,
     })
 }
-
-// ---------------------------------------------------------------------------
-// Helpers
-// ---------------------------------------------------------------------------
-
-fn count_words(content: &str) -> usize {
-    // Strip frontmatter
-    let body = if let Some(rest) = content.strip_prefix("---") {
-        if let Some(end) = rest.find("---") {
-            &rest[end + 3..]
-        } else {
-            content
-        }
-    } else {
-        content
-    };
-    body.split_whitespace().count()
-}
-
-fn parse_frontmatter_light(content: &str) -> (Option<String>, Vec<String>) {
-    let mut title = None;
-    let mut tags = Vec::new();
-
-    if !content.starts_with("---") {
-        // Try to get title from first heading
-        for line in content.lines().take(5) {
-            if let Some(h) = line.strip_prefix("# ") {
-                title = Some(h.trim().to_string());
-                break;
-            }
-        }
-        return (title, tags);
-    }
-
-    let after = &content[3..];
-    let end = match after.find("---") {
-        Some(e) => e,
-        None => return (title, tags),
-    };
-    let fm = &after[..end];
-
-    for line in fm.lines() {
-        let line = line.trim();
-        if let Some(val) = line.strip_prefix("title:") {
-            let val = val.trim().trim_matches('"').trim_matches('\'');
-            if !val.is_empty() {
-                title = Some(val.to_string());
-            }
-        }
-        if let Some(val) = line.strip_prefix("tags:") {
-            let val = val.trim().trim_start_matches('[').trim_end_matches(']');
-            for tag in val.split(',') {
-                let t = tag.trim().trim_matches('"').trim_matches('\'').trim();
-                if !t.is_empty() {
-                    tags.push(t.to_string());
-                }
-            }
-        }
-        // Also handle YAML list-style tags
-        if line.starts_with("- ") && !tags.is_empty() {
-            // We're probably in a tags list
-            let t = line
-                .strip_prefix("- ")
-                .unwrap_or(line)
-                .trim()
-                .trim_matches('"');
-            if !t.is_empty() {
-                tags.push(t.to_string());
-            }
-        }
-    }
-
-    // Fallback: title from first heading
-    if title.is_none() {
-        let body = &after[end + 3..];
-        for line in body.lines().take(10) {
-            if let Some(h) = line.strip_prefix("# ") {
-                title = Some(h.trim().to_string());
-                break;
-            }
-        }
-    }
-
-    (title, tags)
-}
@@ -69,7 +69,13 @@ pub fn start(app: AppHandle, vault_path: String) {
                         "vault_watcher: {} event(s), emitting vault-changed",
                         events.len()
                     );
+                    let paths: Vec<_> = events.into_iter().map(|ev| ev.path).collect();
+                    // Re-read the changed notes first, so the refresh the
+                    // frontend does on vault-changed sees them.
+                    crate::vault_index::refresh(&paths);
                     let _ = app.emit("vault-changed", ());
+                    // Pick up new, moved or cancelled schedules.
+                    crate::schedule_index::refresh(&paths);
                     // Refresh the ambient cache too so sketchybar reflects
                     // the new state without waiting on its 120s tick.
                     crate::sketchybar_cache::update();
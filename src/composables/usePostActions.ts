@@ -1,6 +1,15 @@
 import { ref, watch, type Ref } from 'vue'
 import { invoke } from '@tauri-apps/api/core'
-import type { WebmentionReport } from '../types'
+import type { RenamedSlug, Unpublished, UnpublishStrategy, WebmentionReport } from '../types'
+
+// Toast wording for where an unpublish left the post.
+const UNPUBLISHED: Record<UnpublishStrategy, string> = {
+  move_to_drafts: 'moved to drafts',
+  mark_draft: 'marked draft',
+  mark_unlisted: 'marked unlisted',
+  archive: 'archived',
+  delete: 'deleted',
+}
 
 export function usePostActions(options: {
   slug: Ref<string>
@@ -9,6 +18,8 @@ export function usePostActions(options: {
   getActiveTargetId: () => string | undefined
   showSuccessToast: (msg: string, duration?: number) => void
   onRefresh: () => void
+  /** After a slug rename, with the vault note's new path (null if none moved). */
+  onRenamed: (sourcePath: string | null) => void
 }) {
   const sendingWebmentions = ref(false)
   const webmentionReport = ref<WebmentionReport | null>(null)
@@ -16,6 +27,7 @@ export function usePostActions(options: {
   const converting = ref(false)
   const vuePageHue = ref(220)
   const unpublishing = ref(false)
+  const renaming = ref(false)
 
   // Check Vue-page status when slug changes
   watch(
@@ -103,12 +115,18 @@ export function usePostActions(options: {
 
   async function unpublish() {
     if (!options.isLive() || !options.slug.value || unpublishing.value) return
-    const confirmText = `Unpublish "${options.slug.value}" and move it back to drafts?`
+    const confirmText = `Unpublish "${options.slug.value}"?`
     if (!confirm(confirmText)) return
     unpublishing.value = true
     try {
-      await invoke('unpublish_file', { slug: options.slug.value, targetId: options.getActiveTargetId() || null })
-      options.showSuccessToast('Unpublished — moved to drafts')
+      const unpublished = await invoke<Unpublished>('unpublish_file', {
+        slug: options.slug.value,
+        targetId: options.getActiveTargetId() || null,
+      })
+      options.showSuccessToast(`Unpublished — ${UNPUBLISHED[unpublished.strategy]}`)
+      if (unpublished.pending_push) {
+        alert(`Unpublished locally, but the push failed — it's queued and goes out once the remote is reachable.\n\n${unpublished.pending_push}`)
+      }
       setTimeout(() => options.onRefresh(), 500)
     } catch (e) {
       alert(`Failed: ${e}`)
@@ -116,6 +134,43 @@ export function usePostActions(options: {
     unpublishing.value = false
   }
 
+  /** Change a live post's slug. The target repo gets a redirect from the old
+   *  URL, and links across the vault follow the note to its new name. */
+  async function renameSlug() {
+    if (!options.isLive() || !options.slug.value || renaming.value) return
+    const next = prompt(`New slug for "${options.slug.value}"`, options.slug.value)?.trim()
+    if (!next || next === options.slug.value) return
+    renaming.value = true
+    try {
+      const renamed = await invoke<RenamedSlug>('rename_published_slug', {
+        old: options.slug.value,
+        new: next,
+        targetId: options.getActiveTargetId() || null,
+      })
+      const relinked = renamed.relinked.length
+      options.showSuccessToast(
+        [
+          `Renamed to ${next}`,
+          relinked ? `${relinked} note${relinked === 1 ? '' : 's'} relinked` : '',
+          renamed.redirects_file ? '' : 'no redirect (set one up in Settings)',
+        ]
+          .filter(Boolean)
+          .join(' · '),
+        5000,
+      )
+      if (renamed.push_error) {
+        alert(`Renamed locally, but the push failed — it's queued and goes out once the remote is reachable.\n\n${renamed.push_error}`)
+      }
+      if (renamed.vault_error) {
+        alert(`Renamed on the site, but the vault wasn't updated — rename the note and its links by hand.\n\n${renamed.vault_error}`)
+      }
+      options.onRenamed(renamed.source_path)
+    } catch (e) {
+      alert(`Rename failed: ${e}`)
+    }
+    renaming.value = false
+  }
+
   return {
     sendingWebmentions,
     webmentionReport,
@@ -123,9 +178,11 @@ export function usePostActions(options: {
     converting,
     vuePageHue,
     unpublishing,
+    renaming,
     convertToVuePage,
     triggerWebmentions,
     autoTriggerOnPublish,
     unpublish,
+    renameSlug,
   }
 }
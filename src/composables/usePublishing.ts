@@ -1,6 +1,7 @@
 import { ref, computed } from 'vue'
 import { invoke } from '@tauri-apps/api/core'
 import { useToasts } from './useToasts'
+import type { HookRun, Published, TargetOutcome } from '../types'
 
 // Fire-and-forget native macOS system sound. Names map to files in
 // /System/Library/Sounds (Glass, Hero, Pop, Tink, Sosumi, Submarine, etc).
@@ -34,6 +35,9 @@ export function usePublishing(options: {
   const publishConfirmRepublish = ref(false)
   const showSyndicationWizard = ref(false)
   const showAltTextReviewer = ref(false)
+  // Pull-request targets: the branch the last publish pushed for review,
+  // until it's merged from here.
+  const reviewBranch = ref<{ branch: string; compareUrl: string | null; targetId: string | null } | null>(null)
 
   const toasts = useToasts()
   const journalStatsCache = ref<any>(null)
@@ -66,7 +70,95 @@ export function usePublishing(options: {
     showPublishConfirm.value = false
   }
 
-  async function publish(isRepublish = false) {
+  // Post-publish hooks can fail without failing the publish; say so.
+  function reportHookFailures(hooks: HookRun[]) {
+    for (const h of hooks.filter((h) => !h.success)) {
+      toasts.warn(`Hook failed: ${h.command}`, (h.stderr || h.stdout).trim())
+    }
+  }
+
+  // A commit that couldn't push sits in the backend's push queue and goes
+  // live on a later retry; it's not a failure, but it isn't live either.
+  function reportQueued(where: string, reason: string) {
+    toasts.warn(`Committed ${where} — waiting to push`, `${reason}\nDispatch will keep retrying.`)
+  }
+
+  // Pushed to a review branch: nothing is live until it's merged.
+  function reportReview(branch: string, compareUrl: string | null, targetId: string | null) {
+    reviewBranch.value = { branch, compareUrl, targetId }
+    toasts.push({
+      kind: 'info',
+      message: `Pushed ${branch} for review`,
+      detail: 'Merge it from the post once it has been approved.',
+      action: compareUrl ? { label: 'Open Pull Request', run: () => void window.open(compareUrl, '_blank') } : undefined,
+      ttl: 8000,
+    })
+  }
+
+  // Several targets fan out in one backend call; each failure gets its own
+  // toast and the first live URL stands in for the rest.
+  async function publishToTargets(targetIds: string[]): Promise<{ url: string; pending: boolean }> {
+    const outcomes = await invoke<TargetOutcome[]>('publish_file_to_targets', {
+      sourcePath: options.getFilePath(),
+      slug: options.getSlug(),
+      targetIds,
+    })
+    for (const o of outcomes) {
+      if (!o.success) toasts.error(`Publish to ${o.target_name} failed`, o.error || '')
+      else if (o.pending_push) reportQueued(`to ${o.target_name}`, o.pending_push)
+      else if (o.branch) reportReview(o.branch, o.compare_url, o.target_id)
+      else reportHookFailures(o.hooks)
+    }
+    const live = outcomes.find((o) => o.success && o.url && !o.pending_push && !o.branch)
+    const pending = outcomes.find((o) => o.success && o.url)
+    if (!live && !pending) throw new Error('No target published')
+    return { url: (live || pending)!.url!, pending: !live }
+  }
+
+  async function publishOne(targetId: string | null): Promise<{ url: string; pending: boolean }> {
+    const published = await invoke<Published>('publish_file', {
+      sourcePath: options.getFilePath(),
+      slug: options.getSlug(),
+      targetId,
+    })
+    if (published.pending_push) {
+      reportQueued('locally', published.pending_push)
+      return { url: published.url, pending: true }
+    }
+    if (published.branch) {
+      reportReview(published.branch, published.compare_url, targetId)
+      return { url: published.url, pending: true }
+    }
+    reportHookFailures(published.hooks)
+    return { url: published.url, pending: false }
+  }
+
+  // Fast-forward the reviewed branch into the target branch; the post goes
+  // live (and is journaled) now.
+  async function mergeReviewBranch() {
+    const review = reviewBranch.value
+    if (!review) return
+    publishing.value = true
+    try {
+      const published = await invoke<Published>('merge_publish_branch', {
+        sourcePath: options.getFilePath(),
+        slug: options.getSlug(),
+        targetId: review.targetId,
+      })
+      reviewBranch.value = null
+      reportHookFailures(published.hooks)
+      justPublished.value = published.url
+      playSound('Glass')
+      showSuccessToast('Merged and published!')
+      setTimeout(() => options.onPublished(), 500)
+      options.onPublishSuccess?.(published.url)
+    } catch (e) {
+      toasts.error(`Merging ${review.branch} failed`, String(e))
+    }
+    publishing.value = false
+  }
+
+  async function publish(isRepublish = false, targetIds: string[] = []) {
     if (!isRepublish && !options.getFileIsSafe()) return
     publishing.value = true
     try {
@@ -78,11 +170,17 @@ export function usePublishing(options: {
         /* journal may not be ready */
       }
 
-      const url = await invoke<string>('publish_file', {
-        sourcePath: options.getFilePath(),
-        slug: options.getSlug(),
-        targetId: options.getActiveTargetId() || null,
-      })
+      const { url, pending } =
+        targetIds.length > 1
+          ? await publishToTargets(targetIds)
+          : await publishOne(targetIds[0] || options.getActiveTargetId() || null)
+      if (pending) {
+        // Nothing is live yet; the journal and success chime wait for the
+        // push or the merge.
+        setTimeout(() => options.onPublished(), 500)
+        publishing.value = false
+        return
+      }
       justPublished.value = url
 
       let newMilestone: { label: string; description: string } | null = null
@@ -179,12 +277,14 @@ export function usePublishing(options: {
     publishConfirmRepublish,
     showSyndicationWizard,
     showAltTextReviewer,
+    reviewBranch,
     journalStatsCache,
     publishContext,
     openPublishConfirm,
     closePublishConfirm,
     publish,
     publishUnlisted,
+    mergeReviewBranch,
     showSuccessToast,
     onSyndicationQueued,
     onAltTextApplied,
@@ -11,19 +11,39 @@ export interface MarkdownFile {
   published_word_count: number | null
   is_safe: boolean
   warnings: string[]
+  /** Every lint finding at its configured level, `modified` and `schedule/…` included; `warnings` are their rules' labels. */
+  lint: LintFinding[]
   published_url: string | null
   published_date: number | null
   source_dir: string
   unlisted: boolean
   password: string | null
   publish_at: string | null
+  /** When a live post is taken down again. */
+  unpublish_at: string | null
   content_type: string
 }
 
+export type LintSeverity = 'info' | 'warn' | 'error'
+export type LintLevel = 'off' | LintSeverity
+
+/** One finding from a lint rule. Lines and columns are 1-based, in the whole file. */
+export interface LintFinding {
+  rule: string
+  severity: LintSeverity
+  message: string
+  line: number
+  column: number
+  end_line: number
+  end_column: number
+  fix: { label: string; start: number; end: number; replacement: string } | null
+}
+
 export interface Backlink {
   path: string
   title: string | null
   context: string
+  line: number
 }
 
 export interface LocalMediaRef {
@@ -60,8 +80,170 @@ export interface PublishTarget {
   repo_path: string
   domain: string
   content_path_pattern: string
+  /** Site path a post is served at; empty means `/blog/{year}/{slug}`. */
+  url_pattern: string
   branch: string
   is_default: boolean
+  mode: PublishMode
+  transforms: TransformConfig
+  hooks: PublishHooks
+  verify: DeployVerify
+  commit: CommitConfig
+  redirects: RedirectsConfig
+  unpublish: UnpublishConfig
+}
+
+/** Publish commit message templates and authorship. Tokens: {title} {slug}
+ *  {word_count} {tags} {content_type} {action}; republish also gets
+ *  {words_added} {words_removed} {word_delta}. */
+export interface CommitConfig {
+  publish_template: string
+  republish_template: string
+  unpublish_template: string
+  /** null = the repo's git config. */
+  author: GitIdentity | null
+  committer: GitIdentity | null
+  /** e.g. "Co-authored-by: Name <email>" */
+  trailers: string[]
+}
+
+export interface GitIdentity {
+  name: string
+  email: string
+}
+
+/** Where a slug rename writes its old URL -> new URL redirect. */
+export interface RedirectsConfig {
+  format: RedirectFormat
+  /** Repo-relative; empty = the format's usual file at the repo root. */
+  path: string
+}
+
+export type RedirectFormat = 'none' | 'netlify' | 'vercel' | 'json_map'
+
+/** What unpublishing does to the post's file in the target repo. */
+export interface UnpublishConfig {
+  strategy: UnpublishStrategy
+  /** Repo-relative; `archive` files posts under a folder per day here. */
+  archive_dir: string
+}
+
+/** `delete` also writes a 410 rule, so it needs a Netlify or JSON map redirects file. */
+export type UnpublishStrategy = 'move_to_drafts' | 'mark_draft' | 'mark_unlisted' | 'archive' | 'delete'
+
+/** Result of `unpublish_file`. */
+export interface Unpublished {
+  strategy: UnpublishStrategy
+  /** Where the post was live. */
+  url: string
+  commit_sha: string | null
+  /** Committed locally but not pushed; the push queue retries it. */
+  pending_push: string | null
+}
+
+/** Result of `revert_publish`. */
+export interface Reverted {
+  commit_sha: string
+  /** Committed locally but not pushed; the push queue retries it. */
+  pending_push: string | null
+}
+
+/** Result of `rename_published_slug`. */
+export interface RenamedSlug {
+  old_url: string
+  url: string
+  commit_sha: string | null
+  redirects_file: string | null
+  /** The vault note's new path, if one was renamed. */
+  source_path: string | null
+  relinked: string[]
+  /** Committed locally but not pushed; queued until the remote is reachable. */
+  push_error: string | null
+  /** Committed, but the vault note or links to it weren't updated. */
+  vault_error: string | null
+}
+
+/** `pull_request` commits to a `dispatch/{slug}` branch for review instead of `branch`. */
+export type PublishMode = 'direct' | 'pull_request'
+
+/** Post-push polling of the live URL. */
+export interface DeployVerify {
+  enabled: boolean
+  /** Poll this origin instead of the domain; empty = domain. */
+  base_url: string
+  interval_secs: number
+  timeout_secs: number
+}
+
+/** Payload of the `publish-live` / `publish-deploy-failed` events. */
+export interface DeployCheck {
+  target_id: string
+  slug: string
+  title: string | null
+  url: string
+  checked_url: string
+  live: boolean
+  attempts: number
+  elapsed_ms: number
+  status: number | null
+  error: string | null
+}
+
+/** A row of the publishing journal. */
+export interface JournalEntry {
+  id: number
+  timestamp: string
+  event: string
+  slug: string
+  title: string | null
+  word_count: number
+  tags: string
+  content_type: string
+  url: string | null
+  target_id: string | null
+  visibility: string
+  commit_sha: string | null
+  time_to_live_ms: number | null
+  unpublish_strategy: string | null
+}
+
+/** One entry of `get_post_history`, newest first: a commit to the post's
+ *  file in the target repo, a journal event with no commit there, or both. */
+export interface PostHistoryEntry {
+  timestamp: string
+  revision: PostRevision | null
+  events: JournalEntry[]
+}
+
+export interface PostRevision {
+  sha: string
+  message: string
+  change: 'added' | 'modified' | 'moved' | 'deleted'
+  /** Repo-relative; the path removed, for a deletion. */
+  path: string
+  moved_from: string | null
+  /** Filed where posts go and not `draft: true` after this commit. */
+  live: boolean
+  word_count: number
+  words_added: number
+  words_removed: number
+  lines_added: number
+  lines_removed: number
+}
+
+/** Shell commands run in the target repo around a publish. */
+export interface PublishHooks {
+  pre_publish: string[]
+  post_publish: string[]
+}
+
+/** Which vault -> repo rewrites run when publishing to a target. */
+export interface TransformConfig {
+  wiki_links: boolean
+  embeds: boolean
+  comments: boolean
+  private_blocks: boolean
+  frontmatter: boolean
 }
 
 export interface DiffLine {
@@ -89,6 +271,103 @@ export interface PublishDiff {
   error: string | null
 }
 
+export interface PublishPlan {
+  slug: string
+  target_id: string
+  source_path: string
+  dest_path: string
+  year_dir_exists: boolean
+  overwrites_existing: boolean
+  existing_published_url: string | null
+  is_republish: boolean
+  commit_message: string
+  /** Pull-request targets: the review branch the commit would go to. */
+  branch: string | null
+  git_commands: string[]
+  url: string
+  warnings: string[]
+  lint: LintFinding[]
+  blocked_reason: string | null
+}
+
+/** One target's result from `publish_file_to_targets`. */
+export interface TargetOutcome {
+  target_id: string
+  target_name: string
+  success: boolean
+  url: string | null
+  commit_sha: string | null
+  is_republish: boolean
+  error: string | null
+  hooks: HookRun[]
+  /** Committed but waiting in the push queue: why it couldn't push. */
+  pending_push: string | null
+  branch: string | null
+  compare_url: string | null
+}
+
+export interface BatchItem {
+  source_path: string
+  slug: string
+}
+
+export interface BatchPublished {
+  source_path: string
+  slug: string
+  url: string
+  is_republish: boolean
+  commit_sha: string | null
+  hooks: HookRun[]
+  pending_push: string | null
+}
+
+export interface HookRun {
+  stage: 'pre_publish' | 'post_publish'
+  command: string
+  exit_code: number | null
+  success: boolean
+  stdout: string
+  stderr: string
+  duration_ms: number
+}
+
+/** Result of `publish_file`. */
+export interface Published {
+  url: string
+  commit_sha: string | null
+  hooks: HookRun[]
+  /** Committed but waiting in the push queue: why it couldn't push. */
+  pending_push: string | null
+  /** Pull-request targets: the review branch; not live until merged. */
+  branch: string | null
+  /** Where to open the pull request, when the remote is a known host. */
+  compare_url: string | null
+}
+
+/** A publish commit the push queue is still retrying (or gave up on). */
+export interface PendingPush {
+  id: number
+  target_id: string
+  slug: string
+  url: string
+  commit_sha: string
+  status: 'pending' | 'pushed' | 'failed'
+  attempt_count: number
+  last_error: string | null
+  next_attempt_at: string
+  created_at: string
+}
+
+export type CollisionKind = 'duplicate' | 'orphan' | 'ambiguous'
+
+export interface Collision {
+  kind: CollisionKind
+  slug: string
+  repo_paths: string[]
+  vault_paths: string[]
+  detail: string
+}
+
 export type MediaDestinationKind = 'cloudinary' | 'r2'
 
 export interface CloudinaryCreds {
@@ -128,8 +407,17 @@ export interface AppConfig {
   cloudinary_cloud_name: string | null
   analytics_url: string | null
   mastodon_instance: string | null
+  timezone: string | null
   media: MediaConfig
   webmentions_bridgy_fed: boolean
+  lint: LintConfig
+}
+
+/** Rule id or group (`privacy`) -> level; `content_types` wins over `rules`. */
+export interface LintConfig {
+  rules: Record<string, LintLevel>
+  content_types: Record<string, Record<string, LintLevel>>
+  block_publish: boolean
 }
 
 export interface GitStatus {
@@ -138,6 +426,10 @@ export interface GitStatus {
   error: string | null
   dirty_files: string[]
   has_conflicts: boolean
+  /** Publishes committed but still waiting to push. */
+  pending_pushes: number
+  /** Queued publishes that stopped retrying; fix the repo, then retry. */
+  failed_pushes: number
 }
 
 export interface WebmentionResult {